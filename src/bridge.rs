@@ -0,0 +1,122 @@
+//! Forward data between two UniConnects, e.g. serial port exposed over TCP.
+
+use crate::UniConnect;
+
+use bytes::BytesMut;
+use tokio::prelude::{Async, AsyncRead, AsyncWrite, Future, Poll};
+
+use std::io;
+
+/// Inspect or modify chunks forwarded by [InspectingBridge].
+///
+/// Chunks are what a single read returned, a protocol frame may be split across many of them.
+/// Chunk left empty is not forwarded.
+pub trait InterceptHandler: Send {
+    fn intercept_a_to_b(&mut self, data: &mut BytesMut);
+    fn intercept_b_to_a(&mut self, data: &mut BytesMut);
+}
+
+/// Bidirectional copy between `a` and `b` passing every chunk through handler.
+pub struct InspectingBridge<H: InterceptHandler> {
+    a: UniConnect,
+    b: UniConnect,
+    handler: H,
+}
+
+impl<H: InterceptHandler> InspectingBridge<H> {
+    pub fn new(a: UniConnect, b: UniConnect, handler: H) -> Self {
+        Self { a, b, handler }
+    }
+
+    /// Copy data in both directions until one side reaches EOF, then flush the other side.
+    /// Resolves with number of bytes written as `(a_to_b, b_to_a)`, which differs from number of
+    /// bytes read when handler modified data.
+    pub fn run(self) -> impl Future<Item = (u64, u64), Error = io::Error> {
+        BridgeFuture {
+            bridge: self,
+            a_to_b: Direction::default(),
+            b_to_a: Direction::default(),
+        }
+    }
+
+    pub fn into_inner(self) -> (UniConnect, UniConnect, H) {
+        (self.a, self.b, self.handler)
+    }
+}
+
+#[derive(Default)]
+struct Direction {
+    chunk: BytesMut,
+    read_done: bool,
+    written: u64,
+}
+
+impl Direction {
+    // Forward from `reader` to `writer`, ready once reader reached EOF and writer is flushed.
+    fn poll_copy(
+        &mut self,
+        reader: &mut UniConnect,
+        writer: &mut UniConnect,
+        mut intercept: impl FnMut(&mut BytesMut),
+    ) -> Poll<(), io::Error> {
+        let mut buf = [0u8; 4096];
+        loop {
+            if self.chunk.is_empty() && !self.read_done {
+                match reader.poll_read(&mut buf)? {
+                    Async::Ready(0) => self.read_done = true,
+                    Async::Ready(n) => {
+                        self.chunk.extend_from_slice(&buf[..n]);
+                        intercept(&mut self.chunk);
+                    }
+                    Async::NotReady => return Ok(Async::NotReady),
+                }
+            }
+
+            while !self.chunk.is_empty() {
+                let n = match writer.poll_write(&self.chunk)? {
+                    Async::Ready(0) => return Err(io::ErrorKind::WriteZero.into()),
+                    Async::Ready(n) => n,
+                    Async::NotReady => return Ok(Async::NotReady),
+                };
+                self.chunk.advance(n);
+                self.written += n as u64;
+            }
+
+            if self.read_done {
+                return writer.poll_flush();
+            }
+        }
+    }
+}
+
+struct BridgeFuture<H: InterceptHandler> {
+    bridge: InspectingBridge<H>,
+    a_to_b: Direction,
+    b_to_a: Direction,
+}
+
+impl<H: InterceptHandler> Future for BridgeFuture<H> {
+    type Item = (u64, u64);
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        let bridge = &mut self.bridge;
+        let handler = &mut bridge.handler;
+        let a_to_b = self
+            .a_to_b
+            .poll_copy(&mut bridge.a, &mut bridge.b, |data| {
+                handler.intercept_a_to_b(data)
+            })?;
+        let b_to_a = self
+            .b_to_a
+            .poll_copy(&mut bridge.b, &mut bridge.a, |data| {
+                handler.intercept_b_to_a(data)
+            })?;
+
+        if a_to_b.is_ready() || b_to_a.is_ready() {
+            Ok(Async::Ready((self.a_to_b.written, self.b_to_a.written)))
+        } else {
+            Ok(Async::NotReady)
+        }
+    }
+}
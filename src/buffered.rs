@@ -0,0 +1,130 @@
+//! Buffered reads and writes.
+
+use crate::UniConnect;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::{Async, Poll};
+
+use std::io::{self, BufRead, BufReader, BufWriter, Read, Write};
+use std::net::SocketAddr;
+
+// Default capacity of both buffers, same as std
+const DEFAULT_CAPACITY: usize = 8 * 1024;
+
+// BufWriter reading directly from its inner stream, so that BufReader can wrap it
+struct ReadThrough<T: Write>(BufWriter<T>);
+
+impl<T: Read + Write> Read for ReadThrough<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.get_mut().read(buf)
+    }
+}
+
+/// Connection with read and write buffer, like `BufReader<BufWriter<T>>` of std.
+///
+/// Writes are collected until the write buffer is full or the connection is flushed. Flush
+/// writes the whole buffer and then flushes the underlying connection, a flush returning
+/// `WouldBlock` continues where it stopped when polled again. Shutdown flushes first.
+/// Implements [BufRead] so lines and delimited frames can be read without extra copies.
+pub struct Buffered<T: Write>(BufReader<ReadThrough<T>>);
+
+impl<T: Read + Write> Buffered<T> {
+    pub fn new(inner: T) -> Self {
+        Self::with_capacity(DEFAULT_CAPACITY, DEFAULT_CAPACITY, inner)
+    }
+
+    pub fn with_capacity(read_capacity: usize, write_capacity: usize, inner: T) -> Self {
+        Buffered(BufReader::with_capacity(
+            read_capacity,
+            ReadThrough(BufWriter::with_capacity(write_capacity, inner)),
+        ))
+    }
+}
+
+impl<T: Write> Buffered<T> {
+    pub fn get_ref(&self) -> &T {
+        self.0.get_ref().0.get_ref()
+    }
+
+    /// Reading or writing directly bypasses buffered data.
+    pub fn get_mut(&mut self) -> &mut T {
+        self.0.get_mut().0.get_mut()
+    }
+
+    /// Data read but not consumed yet
+    pub fn read_buffer(&self) -> &[u8] {
+        self.0.buffer()
+    }
+
+    /// Data written but not flushed yet
+    pub fn write_buffer(&self) -> &[u8] {
+        self.0.get_ref().0.buffer()
+    }
+
+    /// Unwrap the connection. Data left in both buffers is lost, flush before.
+    pub fn into_inner(self) -> T {
+        self.0.into_inner().0.into_parts().0
+    }
+}
+
+impl Buffered<UniConnect> {
+    /// See [UniConnect::local_addr]
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().local_addr()
+    }
+
+    /// See [UniConnect::peer_addr]
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        self.get_ref().peer_addr()
+    }
+
+    /// See [UniConnect::redacted]
+    pub fn redacted(&self) -> crate::address::RedactedDisplay<'static> {
+        self.get_ref().redacted()
+    }
+
+    /// See [UniConnect::kind]
+    pub fn kind(&self) -> crate::ConnectionKind {
+        self.get_ref().kind()
+    }
+}
+
+impl<T: Read + Write> Read for Buffered<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl<T: Read + Write> BufRead for Buffered<T> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.0.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.0.consume(amt)
+    }
+}
+
+impl<T: AsyncRead + Write> AsyncRead for Buffered<T> {}
+
+impl<T: Write> Write for Buffered<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.get_mut().0.write(buf)
+    }
+
+    /// Write whole buffer, then flush the underlying connection
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.get_mut().0.flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for Buffered<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self.flush() {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(Async::NotReady),
+            Err(err) => return Err(err),
+        }
+        self.get_mut().shutdown()
+    }
+}
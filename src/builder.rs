@@ -1,61 +1,445 @@
+use crate::resolver::resolve_first_blocking;
+use crate::retry::RetryConfig;
+use crate::retrying_serial::RetryingSerial;
 use crate::retrying_tcp_stream::RetryingTcpStream;
+use crate::serial::{normalize_serial_path_checked, validate_baud_rate};
 use crate::UniConnect;
+use log::warn;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::time::Duration;
 use tokio_serial::Serial;
 
-pub use crate::retrying_tcp_stream::TcpStreamSettings;
+pub use crate::address::ConnectionAddress;
+pub use crate::env::EnvConfigError;
+pub use crate::retrying_tcp_stream::{OverflowPolicy, TcpStreamSettings};
 pub use tokio_serial::{DataBits, FlowControl, Parity, SerialPortSettings, StopBits};
 
 /// Helps create UniConnect with correct settings doesn't matter TCP or Serial port.
 /// This `RetryingTcpOrSerial` will use RetryingTcpStream (this will reconnect internally on error) instead of
 /// [TcpStream](tokio::net::TcpStream).
 ///
+/// Setters take `&mut self`, `with_*` methods consume and return the builder:
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_uniconnect::builder::{RetryingTcpOrSerial, TcpStreamSettings};
+/// use tokio_uniconnect::retry::RetryConfig;
+///
+/// let builder = RetryingTcpOrSerial::new("tcp://127.0.0.1:5000".parse().unwrap())
+///     .with_tcp_settings(TcpStreamSettings::default())
+///     .with_retry(RetryConfig::default())
+///     .with_connect_timeout(Duration::from_secs(3));
+/// # drop(builder);
+/// ```
+///
 /// # Note
 /// Use it more like example to create own builder (for your specific purpose) for UniConnect.
-#[derive(Default)]
+#[derive(Clone)]
 pub struct RetryingTcpOrSerial {
-    connect_point: String,
+    address: ConnectionAddress,
     serial_port_settings: Option<SerialPortSettings>,
     tcp_settings: Option<TcpStreamSettings>,
+    retry_config: Option<RetryConfig>,
+    connect_timeout: Option<Duration>,
+    use_retrying_serial: bool,
+    #[cfg(feature = "tls")]
+    tls_connector: Option<crate::tls::TlsConnector>,
 }
 
 impl RetryingTcpOrSerial {
-    pub fn new(connect_point: String) -> Self {
+    pub fn new(address: ConnectionAddress) -> Self {
         Self {
-            connect_point,
-            ..Default::default()
+            address,
+            serial_port_settings: None,
+            tcp_settings: None,
+            retry_config: None,
+            connect_timeout: None,
+            use_retrying_serial: false,
+            #[cfg(feature = "tls")]
+            tls_connector: None,
         }
     }
 
     /// This settings will be used if UniConnect will be using Serial under hood.
-    pub fn set_serial_port_settings(&mut self, serial_port_settings: Option<SerialPortSettings>) {
+    pub fn set_serial_port_settings(
+        &mut self,
+        serial_port_settings: Option<SerialPortSettings>,
+    ) -> &mut Self {
         self.serial_port_settings = serial_port_settings;
+        self
+    }
+
+    /// Owned variant of [set_serial_port_settings](RetryingTcpOrSerial::set_serial_port_settings).
+    pub fn with_serial_port_settings(mut self, serial_port_settings: SerialPortSettings) -> Self {
+        self.set_serial_port_settings(Some(serial_port_settings));
+        self
+    }
+
+    /// Open serial port as [RetryingSerial] that reopens it on error instead of plain
+    /// [Serial]. Off by default.
+    pub fn set_use_retrying_serial(&mut self, use_retrying_serial: bool) -> &mut Self {
+        self.use_retrying_serial = use_retrying_serial;
+        self
+    }
+
+    /// Owned variant of [set_use_retrying_serial](RetryingTcpOrSerial::set_use_retrying_serial).
+    pub fn with_retrying_serial(mut self, use_retrying_serial: bool) -> Self {
+        self.set_use_retrying_serial(use_retrying_serial);
+        self
     }
 
     /// This settings will be used if UniConnect will be using TCP under hood.
-    pub fn set_tcp_settings(&mut self, tcp_settings: Option<TcpStreamSettings>) {
+    pub fn set_tcp_settings(&mut self, tcp_settings: Option<TcpStreamSettings>) -> &mut Self {
         self.tcp_settings = tcp_settings;
+        self
+    }
+
+    /// Owned variant of [set_tcp_settings](RetryingTcpOrSerial::set_tcp_settings).
+    pub fn with_tcp_settings(mut self, tcp_settings: TcpStreamSettings) -> Self {
+        self.set_tcp_settings(Some(tcp_settings));
+        self
+    }
+
+    /// Delays between reconnects of [RetryingTcpStream] and [RetryingSerial]. `None` uses
+    /// [RetryConfig::default].
+    pub fn set_retry_config(&mut self, retry_config: Option<RetryConfig>) -> &mut Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    /// Owned variant of [set_retry_config](RetryingTcpOrSerial::set_retry_config).
+    pub fn with_retry(mut self, retry_config: RetryConfig) -> Self {
+        self.set_retry_config(Some(retry_config));
+        self
+    }
+
+    /// Timeout of every TCP connect attempt, overrides
+    /// [TcpStreamSettings::set_connect_timeout] of TCP settings.
+    pub fn set_connect_timeout(&mut self, connect_timeout: Option<Duration>) -> &mut Self {
+        self.connect_timeout = connect_timeout;
+        self
+    }
+
+    /// Owned variant of [set_connect_timeout](RetryingTcpOrSerial::set_connect_timeout).
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> Self {
+        self.set_connect_timeout(Some(connect_timeout));
+        self
+    }
+
+    // TCP settings with connect timeout of the builder applied
+    fn effective_tcp_settings(&self) -> Option<TcpStreamSettings> {
+        let connect_timeout = match self.connect_timeout {
+            Some(connect_timeout) => connect_timeout,
+            None => return self.tcp_settings.clone(),
+        };
+        let mut tcp_settings = self.tcp_settings.clone().unwrap_or_default();
+        tcp_settings.set_connect_timeout(Some(connect_timeout));
+        Some(tcp_settings)
+    }
+
+    /// Connector used for `tls://` address, see [build_async](RetryingTcpOrSerial::build_async).
+    #[cfg(feature = "tls")]
+    pub fn set_tls_connector(
+        &mut self,
+        tls_connector: Option<crate::tls::TlsConnector>,
+    ) -> &mut Self {
+        self.tls_connector = tls_connector;
+        self
+    }
+
+    /// Owned variant of [set_tls_connector](RetryingTcpOrSerial::set_tls_connector).
+    #[cfg(feature = "tls")]
+    pub fn with_tls_connector(mut self, tls_connector: crate::tls::TlsConnector) -> Self {
+        self.set_tls_connector(Some(tls_connector));
+        self
+    }
+
+    /// Trust only server certificate (or CA) in `pem` for `tls://` address, see
+    /// [connector_with_server_cert_pem](crate::tls::connector_with_server_cert_pem).
+    #[cfg(feature = "tls")]
+    pub fn set_server_cert_pem(&mut self, pem: &[u8]) -> Result<(), tokio::io::Error> {
+        self.tls_connector = Some(crate::tls::connector_with_server_cert_pem(pem)?);
+        Ok(())
+    }
+
+    /// Check settings without opening connection. Fails with `InvalidInput` if serial port
+    /// would use [unsupported](crate::serial::validate_baud_rate) baud rate.
+    pub fn validate(&self) -> Result<(), tokio::io::Error> {
+        if let (ConnectionAddress::Serial(_), Some(settings)) =
+            (&self.address, &self.serial_port_settings)
+        {
+            validate_baud_rate(settings.baud_rate)?;
+        }
+        Ok(())
+    }
+
+    /// Settings of this builder as [UniConnectConfig](crate::config::UniConnectConfig).
+    #[cfg(feature = "serde")]
+    pub fn to_config(&self) -> crate::config::UniConnectConfig {
+        crate::config::UniConnectConfig {
+            address: self.address.clone(),
+            serial: self.serial_port_settings.map(Into::into),
+            tcp: self.effective_tcp_settings(),
+            use_retrying_serial: self.use_retrying_serial,
+            retry: self.retry_config.clone(),
+            priority: crate::config::UniConnectConfig::DEFAULT_PRIORITY,
+        }
+    }
+
+    /// Build UniConnect and rebuild it whenever `rx` receives a different config, see
+    /// [WatchedConnector](crate::reload::WatchedConnector).
+    #[cfg(feature = "config-reload")]
+    pub fn with_config_watch(
+        self,
+        rx: tokio::sync::watch::Receiver<crate::config::UniConnectConfig>,
+    ) -> Result<crate::reload::WatchedConnector, tokio::io::Error> {
+        crate::reload::WatchedConnector::new(self, rx)
     }
 
     /// Consume builder and try create UniConnect.
-    /// This build function will connect sync instead of async to make sure `connect_point` is
-    /// correct
+    /// Serial port path is [normalized](crate::serial::normalize_serial_path) first, a warning is
+    /// logged when it changes. Serial port, Unix socket and named pipe are opened sync, except
+    /// [RetryingSerial] which retries in background when the port can't be opened. UDP socket is
+    /// bound sync. Hostname is resolved sync (blocking), the first address is used. TCP
+    /// connection is established in background by [RetryingTcpStream], `retrying-tcp://` address
+    /// reconnects with its own policy instead of the retry config. `tls://` address returns
+    /// `InvalidInput`, TLS handshake needs [build_async](RetryingTcpOrSerial::build_async).
     pub fn build(self) -> Result<UniConnect, tokio::io::Error> {
-        let res_socket_addr = self.connect_point.parse::<std::net::SocketAddr>();
-        match res_socket_addr {
-            Ok(socket_addr) => {
-                let mut tcp_stream = RetryingTcpStream::connect(&socket_addr);
-                if let Some(tcp_settings) = self.tcp_settings {
-                    tcp_stream.set_tcp_settings(tcp_settings)?;
-                }
-
-                Ok(UniConnect::from(tcp_stream))
+        self.validate()?;
+        let tcp_settings = self.effective_tcp_settings();
+        let retry_config = self.retry_config.unwrap_or_default();
+        match self.address {
+            ConnectionAddress::Tcp(socket_addr) => {
+                Ok(Self::build_tcp(&socket_addr, tcp_settings, retry_config))
+            }
+            ConnectionAddress::TcpHost { hostname, port } => {
+                let socket_addr = resolve_first_blocking(&hostname, port)?;
+                Ok(Self::build_tcp(&socket_addr, tcp_settings, retry_config))
             }
-            Err(_) => {
-                let serial_settings = self.serial_port_settings.unwrap_or(Default::default());
-                let serial = Serial::from_path(self.connect_point, &serial_settings)?;
+            ConnectionAddress::Serial(path) => {
+                let path = match path.to_str() {
+                    Some(input) => {
+                        let (path, warning) = normalize_serial_path_checked(input);
+                        if let Some(warning) = warning {
+                            warn!("{}", warning);
+                        }
+                        path
+                    }
+                    None => path,
+                };
+                let serial_settings = self.serial_port_settings.unwrap_or_default();
+                if self.use_retrying_serial {
+                    return Ok(UniConnect::from(
+                        RetryingSerial::open(path, serial_settings).with_retry_config(retry_config),
+                    ));
+                }
+                let serial = Serial::from_path(path, &serial_settings)?;
 
                 Ok(UniConnect::from(serial))
             }
+            #[cfg(unix)]
+            ConnectionAddress::UnixSocket(path) => {
+                crate::unix::connect_unix_sync(&path).map(UniConnect::from)
+            }
+            #[cfg(not(unix))]
+            ConnectionAddress::UnixSocket(path) => Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::Unsupported,
+                format!("unix socket {} is not supported", path.display()),
+            )),
+            #[cfg(target_os = "linux")]
+            ConnectionAddress::AbstractUnixSocket(name) => {
+                crate::unix::connect_abstract_unix_sync(&name).map(UniConnect::from)
+            }
+            #[cfg(not(target_os = "linux"))]
+            ConnectionAddress::AbstractUnixSocket(_) => Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::Unsupported,
+                "abstract unix sockets are supported only on Linux",
+            )),
+            #[cfg(feature = "tls")]
+            ConnectionAddress::Tls { .. } => Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::InvalidInput,
+                "TLS connection needs handshake, use build_async",
+            )),
+            #[cfg(not(feature = "tls"))]
+            ConnectionAddress::Tls { .. } => Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::Unsupported,
+                "TLS needs `tls` feature",
+            )),
+            ConnectionAddress::Udp { hostname, port } => {
+                let socket_addr = resolve_first_blocking(&hostname, port)?;
+                crate::udp::connect_udp(&socket_addr)
+            }
+            ConnectionAddress::RetryingTcp { addr, policy } => {
+                Ok(Self::build_tcp(&addr, tcp_settings, policy))
+            }
+            #[cfg(windows)]
+            ConnectionAddress::NamedPipe(path) => {
+                crate::named_pipe::NamedPipeClient::connect(path).map(UniConnect::from)
+            }
+            #[cfg(not(windows))]
+            ConnectionAddress::NamedPipe(path) => Err(tokio::io::Error::new(
+                tokio::io::ErrorKind::Unsupported,
+                format!("named pipe {} is supported only on Windows", path.display()),
+            )),
         }
     }
+
+    fn build_tcp(
+        socket_addr: &SocketAddr,
+        tcp_settings: Option<TcpStreamSettings>,
+        retry_config: RetryConfig,
+    ) -> UniConnect {
+        // settings are needed before the first connect attempt starts
+        UniConnect::from(RetryingTcpStream::connect_with_retry(
+            socket_addr,
+            tcp_settings.unwrap_or_default(),
+            retry_config,
+        ))
+    }
+
+    /// Like [build](RetryingTcpOrSerial::build) but also connects to `tls://` address: hostname
+    /// is resolved, TCP connection established and TLS handshake done with connector set by
+    /// [set_tls_connector](RetryingTcpOrSerial::set_tls_connector) or
+    /// [set_server_cert_pem](RetryingTcpOrSerial::set_server_cert_pem). TLS connection doesn't
+    /// reconnect and TCP settings are not applied to it.
+    #[cfg(feature = "tls")]
+    pub fn build_async(
+        self,
+    ) -> impl tokio::prelude::Future<Item = UniConnect, Error = tokio::io::Error> {
+        use tokio::prelude::future::{self, Either};
+
+        if let ConnectionAddress::Tls { domain, port } = &self.address {
+            let connect = match self.tls_connector {
+                Some(connector) => {
+                    Either::A(crate::tls::tls_connect_host(domain, *port, connector))
+                }
+                None => Either::B(future::err(tokio::io::Error::new(
+                    tokio::io::ErrorKind::InvalidInput,
+                    "no TLS connector set for tls:// address",
+                ))),
+            };
+            return Either::A(connect);
+        }
+        Either::B(future::result(self.build()))
+    }
+}
+
+/// State of [Builder]. Sealed, implemented only by [NeedsAddress], [HasTcpAddress] and
+/// [HasSerialAddress].
+pub trait BuilderState: private::Sealed {}
+
+mod private {
+    pub trait Sealed {}
+    impl Sealed for super::NeedsAddress {}
+    impl Sealed for super::HasTcpAddress {}
+    impl Sealed for super::HasSerialAddress {}
+}
+
+/// [Builder] without connection address, it can't be built yet.
+#[derive(Clone, Debug, Default)]
+pub struct NeedsAddress;
+
+/// [Builder] that will connect to TCP address.
+#[derive(Clone, Debug)]
+pub struct HasTcpAddress {
+    addr: SocketAddr,
+    settings: Option<TcpStreamSettings>,
+}
+
+/// [Builder] that will open serial port.
+#[derive(Clone, Debug)]
+pub struct HasSerialAddress {
+    path: PathBuf,
+    settings: Option<SerialPortSettings>,
+}
+
+impl BuilderState for NeedsAddress {}
+impl BuilderState for HasTcpAddress {}
+impl BuilderState for HasSerialAddress {}
+
+/// Typestate version of [RetryingTcpOrSerial]. Only builder with address can be built and only
+/// settings matching the address can be set.
+///
+/// ```no_run
+/// use tokio_uniconnect::builder::Builder;
+///
+/// let conn = Builder::new()
+///     .set_tcp_addr("127.0.0.1:5000".parse().unwrap())
+///     .build();
+/// ```
+///
+/// Builder without address doesn't compile:
+/// ```compile_fail
+/// use tokio_uniconnect::builder::Builder;
+///
+/// let conn = Builder::new().build();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Builder<S: BuilderState> {
+    state: S,
+}
+
+impl Builder<NeedsAddress> {
+    pub fn new() -> Self {
+        Self {
+            state: NeedsAddress,
+        }
+    }
+}
+
+impl Default for Builder<NeedsAddress> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: BuilderState> Builder<S> {
+    /// Connect to `addr` with [RetryingTcpStream]. Replaces previously set address.
+    pub fn set_tcp_addr(self, addr: SocketAddr) -> Builder<HasTcpAddress> {
+        Builder {
+            state: HasTcpAddress {
+                addr,
+                settings: None,
+            },
+        }
+    }
+
+    /// Open serial port at `path`. Replaces previously set address.
+    pub fn set_serial_path(self, path: impl Into<PathBuf>) -> Builder<HasSerialAddress> {
+        Builder {
+            state: HasSerialAddress {
+                path: path.into(),
+                settings: None,
+            },
+        }
+    }
+}
+
+impl Builder<HasTcpAddress> {
+    pub fn set_tcp_settings(mut self, tcp_settings: TcpStreamSettings) -> Self {
+        self.state.settings = Some(tcp_settings);
+        self
+    }
+
+    /// Consume builder and create UniConnect, see [RetryingTcpOrSerial::build].
+    pub fn build(self) -> Result<UniConnect, tokio::io::Error> {
+        let mut builder = RetryingTcpOrSerial::new(ConnectionAddress::Tcp(self.state.addr));
+        builder.set_tcp_settings(self.state.settings);
+        builder.build()
+    }
+}
+
+impl Builder<HasSerialAddress> {
+    pub fn set_serial_port_settings(mut self, serial_port_settings: SerialPortSettings) -> Self {
+        self.state.settings = Some(serial_port_settings);
+        self
+    }
+
+    /// Consume builder and open serial port, see [RetryingTcpOrSerial::build].
+    pub fn build(self) -> Result<UniConnect, tokio::io::Error> {
+        let mut builder = RetryingTcpOrSerial::new(ConnectionAddress::Serial(self.state.path));
+        builder.set_serial_port_settings(self.state.settings);
+        builder.build()
+    }
 }
@@ -0,0 +1,309 @@
+//! Traffic recording in PCAP format for offline analysis in Wireshark.
+
+use crate::UniConnect;
+
+use log::warn;
+use pcap_file::pcap::{PcapHeader, PcapPacket, PcapWriter};
+use pcap_file::{DataLink, PcapError};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::Poll;
+
+use std::fs::File;
+use std::io::{self, BufWriter, Read, Write};
+use std::net::{IpAddr, SocketAddr};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// payload bytes per record, keeps records with headers below default snaplen 65535
+const MAX_PAYLOAD: usize = 65000;
+
+/// How captured data is framed in PCAP records
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CaptureLink {
+    /// Fake Ethernet + IP + TCP frames between `local` and `peer` so Wireshark can dissect
+    /// the application protocol
+    Tcp { local: SocketAddr, peer: SocketAddr },
+    /// `DLT_USER0` records, first byte is direction (`0` read, `1` written) followed by data
+    Raw,
+}
+
+// Buffered file shared with `PcapWriter` which doesn't give access to its writer
+#[derive(Clone)]
+struct CaptureFile(Arc<Mutex<BufWriter<File>>>);
+
+impl CaptureFile {
+    fn lock(&self) -> std::sync::MutexGuard<'_, BufWriter<File>> {
+        self.0.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl Write for CaptureFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+/// Connection wrapper appending every read and write to PCAP file.
+///
+/// Failing capture file (e.g. full disk) doesn't fail reads and writes of the connection: the
+/// error is logged, recording stops and the error is returned by
+/// [flush_capture](PcapCapture::flush_capture) or [into_inner](PcapCapture::into_inner).
+pub struct PcapCapture<T> {
+    inner: T,
+    writer: PcapWriter<CaptureFile>,
+    file: CaptureFile,
+    link: CaptureLink,
+    // first capture error, recording is stopped until it is reported
+    failure: Option<io::Error>,
+    rx_offset: u32,
+    tx_offset: u32,
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Rx,
+    Tx,
+}
+
+impl<T> PcapCapture<T> {
+    /// Create (truncate) PCAP file at `path` and record `inner` traffic into it.
+    pub fn create(inner: T, path: &Path, link: CaptureLink) -> io::Result<Self> {
+        let datalink = match link {
+            CaptureLink::Tcp { .. } => DataLink::ETHERNET,
+            CaptureLink::Raw => DataLink::USER0,
+        };
+        let header = PcapHeader {
+            datalink,
+            ..Default::default()
+        };
+        let file = CaptureFile(Arc::new(Mutex::new(BufWriter::new(File::create(path)?))));
+        let writer = PcapWriter::with_header(file.clone(), header).map_err(pcap_error)?;
+
+        Ok(Self {
+            inner,
+            writer,
+            file,
+            link,
+            failure: None,
+            rx_offset: 0,
+            tx_offset: 0,
+        })
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    /// Flush buffered records to the PCAP file. Returns capture error of an earlier read or
+    /// write, after that recording continues.
+    pub fn flush_capture(&mut self) -> io::Result<()> {
+        match self.failure.take() {
+            Some(err) => Err(err),
+            None => self.file.flush(),
+        }
+    }
+
+    /// Flush the PCAP file and return the inner connection
+    pub fn into_inner(mut self) -> io::Result<T> {
+        self.flush_capture()?;
+        Ok(self.inner)
+    }
+
+    // Record `data`, capture error is kept for `flush_capture` instead of failing the IO call
+    fn record_or_keep_error(&mut self, direction: Direction, data: &[u8]) {
+        if self.failure.is_some() {
+            return;
+        }
+        if let Err(err) = self.record(direction, data) {
+            self.keep_error(err);
+        }
+    }
+
+    fn flush_or_keep_error(&mut self) {
+        if self.failure.is_some() {
+            return;
+        }
+        if let Err(err) = self.file.flush() {
+            self.keep_error(err);
+        }
+    }
+
+    fn keep_error(&mut self, err: io::Error) {
+        warn!("PcapCapture => capture failed, recording stopped: {}", err);
+        self.failure.get_or_insert(err);
+    }
+
+    fn record(&mut self, direction: Direction, data: &[u8]) -> io::Result<()> {
+        for chunk in data.chunks(MAX_PAYLOAD) {
+            let frame = match self.link {
+                CaptureLink::Tcp { local, peer } => {
+                    let (src, dst, seq, ack) = match direction {
+                        Direction::Tx => (local, peer, self.tx_offset, self.rx_offset),
+                        Direction::Rx => (peer, local, self.rx_offset, self.tx_offset),
+                    };
+                    tcp_frame(src, dst, seq, ack, chunk)
+                }
+                CaptureLink::Raw => {
+                    let mut frame = Vec::with_capacity(chunk.len() + 1);
+                    frame.push(match direction {
+                        Direction::Rx => 0,
+                        Direction::Tx => 1,
+                    });
+                    frame.extend_from_slice(chunk);
+                    frame
+                }
+            };
+            let offset = match direction {
+                Direction::Rx => &mut self.rx_offset,
+                Direction::Tx => &mut self.tx_offset,
+            };
+            *offset = offset.wrapping_add(chunk.len() as u32);
+
+            let timestamp = SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap_or_default();
+            let packet = PcapPacket::new(timestamp, frame.len() as u32, &frame);
+            self.writer.write_packet(&packet).map_err(pcap_error)?;
+        }
+        Ok(())
+    }
+}
+
+// IO error of the capture file as it is, other errors wrapped
+fn pcap_error(err: PcapError) -> io::Error {
+    match err {
+        PcapError::IoError(err) => err,
+        err => io::Error::other(err),
+    }
+}
+
+// Ethernet + IPv4/IPv6 + TCP frame carrying `payload`, checksums are left zero
+fn tcp_frame(src: SocketAddr, dst: SocketAddr, seq: u32, ack: u32, payload: &[u8]) -> Vec<u8> {
+    const TCP_HEADER_LEN: usize = 20;
+    let mut frame = Vec::with_capacity(14 + 40 + TCP_HEADER_LEN + payload.len());
+
+    // locally administered MAC addresses
+    frame.extend_from_slice(&[0x02, 0, 0, 0, 0, 0x02]);
+    frame.extend_from_slice(&[0x02, 0, 0, 0, 0, 0x01]);
+    let segment_len = TCP_HEADER_LEN + payload.len();
+    match (src.ip(), dst.ip()) {
+        (IpAddr::V4(src_ip), IpAddr::V4(dst_ip)) => {
+            frame.extend_from_slice(&0x0800u16.to_be_bytes());
+            frame.extend_from_slice(&[0x45, 0]);
+            frame.extend_from_slice(&((20 + segment_len) as u16).to_be_bytes());
+            // identification, flags (don't fragment), TTL, protocol
+            frame.extend_from_slice(&[0, 0, 0x40, 0, 64, 6]);
+            let checksum_pos = frame.len();
+            frame.extend_from_slice(&[0, 0]);
+            frame.extend_from_slice(&src_ip.octets());
+            frame.extend_from_slice(&dst_ip.octets());
+            let checksum = ipv4_checksum(&frame[14..34]);
+            frame[checksum_pos..checksum_pos + 2].copy_from_slice(&checksum.to_be_bytes());
+        }
+        (src_ip, dst_ip) => {
+            let to_v6 = |ip: IpAddr| match ip {
+                IpAddr::V4(ip) => ip.to_ipv6_mapped(),
+                IpAddr::V6(ip) => ip,
+            };
+            frame.extend_from_slice(&0x86DDu16.to_be_bytes());
+            frame.extend_from_slice(&[0x60, 0, 0, 0]);
+            frame.extend_from_slice(&(segment_len as u16).to_be_bytes());
+            // next header TCP, hop limit
+            frame.extend_from_slice(&[6, 64]);
+            frame.extend_from_slice(&to_v6(src_ip).octets());
+            frame.extend_from_slice(&to_v6(dst_ip).octets());
+        }
+    }
+
+    frame.extend_from_slice(&src.port().to_be_bytes());
+    frame.extend_from_slice(&dst.port().to_be_bytes());
+    frame.extend_from_slice(&seq.to_be_bytes());
+    frame.extend_from_slice(&ack.to_be_bytes());
+    // data offset 5 words, PSH + ACK, window, checksum, urgent pointer
+    frame.extend_from_slice(&[0x50, 0x18, 0xff, 0xff, 0, 0, 0, 0]);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn ipv4_checksum(header: &[u8]) -> u16 {
+    let sum = header
+        .chunks(2)
+        .map(|word| u32::from(u16::from_be_bytes([word[0], word[1]])))
+        .sum::<u32>();
+    let sum = (sum & 0xffff) + (sum >> 16);
+    !((sum & 0xffff) + (sum >> 16)) as u16
+}
+
+impl<T: Read> Read for PcapCapture<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.record_or_keep_error(Direction::Rx, &buf[..n]);
+        Ok(n)
+    }
+}
+
+impl<T: Write> Write for PcapCapture<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        self.record_or_keep_error(Direction::Tx, &buf[..n]);
+        Ok(n)
+    }
+
+    /// Flush both the connection and the PCAP file
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()?;
+        self.flush_or_keep_error();
+        Ok(())
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for PcapCapture<T> {}
+
+impl<T: AsyncWrite> AsyncWrite for PcapCapture<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.flush_or_keep_error();
+        self.inner.shutdown()
+    }
+}
+
+impl UniConnect {
+    /// Record traffic into PCAP file at `path`. TCP connections are captured as fake TCP/IP
+    /// frames (TLS connections with decrypted payload), serial ports, UDP and WebSocket payloads
+    /// and not connected [RetryingTcpStream](crate::retrying_tcp_stream::RetryingTcpStream) as
+    /// [raw](CaptureLink::Raw) records.
+    pub fn with_pcap_capture(self, path: &Path) -> io::Result<PcapCapture<UniConnect>> {
+        let addrs = match &self {
+            UniConnect::TcpStream(inner) => Some((inner.local_addr(), inner.peer_addr())),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => Some((inner.local_addr(), inner.peer_addr())),
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(_) | UniConnect::RetryingSerial(_) => None,
+            #[cfg(unix)]
+            UniConnect::UnixStream(_) => None,
+            UniConnect::UdpSocket(_) => None,
+            #[cfg(windows)]
+            UniConnect::NamedPipe(_) => None,
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(_) => None,
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => {
+                let tcp = inner.get_ref().0;
+                Some((tcp.local_addr(), tcp.peer_addr()))
+            }
+        };
+        let link = match addrs {
+            Some((Ok(local), Ok(peer))) => CaptureLink::Tcp { local, peer },
+            _ => CaptureLink::Raw,
+        };
+        PcapCapture::create(self, path, link)
+    }
+}
@@ -0,0 +1,16 @@
+//! Codecs for protocols commonly spoken over UniConnect.
+//!
+//! All codecs implement [Decoder](tokio::codec::Decoder) and [Encoder](tokio::codec::Encoder)
+//! so they can be used with [Framed](tokio::codec::Framed).
+
+mod lin;
+#[cfg(feature = "msgpack")]
+mod msgpack;
+mod nmea;
+mod stacked;
+
+pub use self::lin::{protected_id, ChecksumType, LinCodec, LinFrame};
+#[cfg(feature = "msgpack")]
+pub use self::msgpack::MsgpackCodec;
+pub use self::nmea::{NmeaCodec, NmeaSentence};
+pub use self::stacked::StackedCodec;
@@ -0,0 +1,206 @@
+use bytes::{BufMut, BytesMut};
+use tokio::codec::{Decoder, Encoder};
+
+use std::io;
+
+const SYNC: u8 = 0x55;
+const MAX_DATA_LEN: usize = 8;
+
+/// LIN checksum model
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChecksumType {
+    /// LIN 1.x, checksum over data bytes only
+    Classic,
+    /// LIN 2.x, checksum over protected identifier and data bytes
+    Enhanced,
+}
+
+/// Single LIN frame (without break and sync fields)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinFrame {
+    /// Frame identifier with parity bits
+    pub protected_id: u8,
+    pub data: Vec<u8>,
+    pub checksum_type: ChecksumType,
+}
+
+impl LinFrame {
+    /// Create frame for 6-bit `id`. Parity bits are calculated.
+    pub fn new(id: u8, data: Vec<u8>, checksum_type: ChecksumType) -> Self {
+        Self {
+            protected_id: protected_id(id),
+            data,
+            checksum_type,
+        }
+    }
+
+    /// Frame identifier without parity bits
+    pub fn id(&self) -> u8 {
+        self.protected_id & 0x3F
+    }
+}
+
+/// Add parity bits P0 and P1 to 6-bit frame identifier
+pub fn protected_id(id: u8) -> u8 {
+    let id = id & 0x3F;
+    let bit = |n: u8| (id >> n) & 1;
+    let p0 = bit(0) ^ bit(1) ^ bit(2) ^ bit(4);
+    let p1 = !(bit(1) ^ bit(3) ^ bit(4) ^ bit(5)) & 1;
+    id | p0 << 6 | p1 << 7
+}
+
+fn checksum(protected_id: u8, data: &[u8], checksum_type: ChecksumType) -> u8 {
+    // diagnostic frames always use classic checksum
+    let seed = match checksum_type {
+        ChecksumType::Enhanced if protected_id & 0x3F < 0x3C => protected_id as u16,
+        _ => 0,
+    };
+    let sum = data.iter().fold(seed, |acc, &b| {
+        let acc = acc + b as u16;
+        if acc > 0xFF {
+            acc - 0xFF
+        } else {
+            acc
+        }
+    });
+    !(sum as u8)
+}
+
+/// Codec for LIN bus frames.
+///
+/// Encoded frame is `sync | protected id | data | checksum`. The 13-bit break field that starts
+/// every LIN frame can't be produced by a normal UART write, it has to be sent on the serial
+/// port right before the encoded frame is written, e.g. with `SerialExtensions::send_lin_break`
+/// of `serial` feature.
+///
+/// LIN frames don't carry their length. Decoder use LIN 1.x lengths (2, 4 or 8 bytes depending on
+/// identifier) unless changed with [set_data_len](LinCodec::set_data_len), encoder rejects frames
+/// of other length and with wrong parity bits. Checksum type of decoded frame is detected.
+#[derive(Debug)]
+pub struct LinCodec {
+    data_lens: [u8; 64],
+}
+
+impl Default for LinCodec {
+    fn default() -> Self {
+        let mut data_lens = [8; 64];
+        for (id, len) in data_lens.iter_mut().enumerate() {
+            *len = match id {
+                0..=31 => 2,
+                32..=47 => 4,
+                _ => 8,
+            };
+        }
+        Self { data_lens }
+    }
+}
+
+impl LinCodec {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set number of data bytes (1 to 8) of frames with identifier `id` (usually taken from LDF)
+    pub fn set_data_len(&mut self, id: u8, len: usize) {
+        assert!(
+            (1..=MAX_DATA_LEN).contains(&len),
+            "LIN frame carry 1 to 8 data bytes"
+        );
+        self.data_lens[(id & 0x3F) as usize] = len as u8;
+    }
+}
+
+impl Decoder for LinCodec {
+    type Item = LinFrame;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            // break is usually received as 0x00 with framing error, skip everything before sync
+            match src.iter().position(|&b| b == SYNC) {
+                Some(start) => {
+                    src.split_to(start);
+                }
+                None => {
+                    src.clear();
+                    return Ok(None);
+                }
+            }
+            if src.len() < 2 {
+                return Ok(None);
+            }
+
+            let pid = src[1];
+            if protected_id(pid) != pid {
+                // not a header, sync byte was part of something else
+                src.split_to(1);
+                continue;
+            }
+
+            let data_len = self.data_lens[(pid & 0x3F) as usize] as usize;
+            let frame_len = 2 + data_len + 1;
+            if src.len() < frame_len {
+                src.reserve(frame_len - src.len());
+                return Ok(None);
+            }
+
+            let frame = src.split_to(frame_len);
+            let data = &frame[2..2 + data_len];
+            let received = frame[frame_len - 1];
+            let checksum_type = if checksum(pid, data, ChecksumType::Enhanced) == received {
+                ChecksumType::Enhanced
+            } else if checksum(pid, data, ChecksumType::Classic) == received {
+                ChecksumType::Classic
+            } else {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid checksum of LIN frame 0x{:02X}", pid & 0x3F),
+                ));
+            };
+
+            return Ok(Some(LinFrame {
+                protected_id: pid,
+                data: data.to_vec(),
+                checksum_type,
+            }));
+        }
+    }
+}
+
+impl Encoder for LinCodec {
+    type Item = LinFrame;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let pid = item.protected_id;
+        if protected_id(pid) != pid {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "invalid parity bits of LIN protected identifier 0x{:02X}",
+                    pid
+                ),
+            ));
+        }
+        // decoder on the other side expects the same length
+        let data_len = self.data_lens[(pid & 0x3F) as usize] as usize;
+        if item.data.len() != data_len {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!(
+                    "LIN frame 0x{:02X} carry {} data bytes, got {}",
+                    pid & 0x3F,
+                    data_len,
+                    item.data.len()
+                ),
+            ));
+        }
+
+        dst.reserve(item.data.len() + 3);
+        dst.put_u8(SYNC);
+        dst.put_u8(item.protected_id);
+        dst.put_slice(&item.data);
+        dst.put_u8(checksum(item.protected_id, &item.data, item.checksum_type));
+        Ok(())
+    }
+}
@@ -0,0 +1,98 @@
+use bytes::{BufMut, BytesMut};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use tokio::codec::{Decoder, Encoder};
+
+use std::io;
+use std::marker::PhantomData;
+
+const HEADER_LEN: usize = 4;
+// refuse frames larger than this unless changed with `set_max_frame_len`
+const DEFAULT_MAX_FRAME_LEN: usize = 8 * 1024 * 1024;
+
+/// Codec for MessagePack values prefixed with u32 big-endian length.
+///
+/// Values are encoded with field names so both sides may add optional fields.
+#[derive(Debug)]
+pub struct MsgpackCodec<T> {
+    max_frame_len: usize,
+    _item: PhantomData<fn() -> T>,
+}
+
+impl<T> MsgpackCodec<T> {
+    pub fn new() -> Self {
+        Self {
+            max_frame_len: DEFAULT_MAX_FRAME_LEN,
+            _item: PhantomData,
+        }
+    }
+
+    /// Frames longer than `max_frame_len` bytes are rejected with `InvalidData`. Default is 8 MiB.
+    pub fn set_max_frame_len(&mut self, max_frame_len: usize) {
+        self.max_frame_len = max_frame_len;
+    }
+}
+
+impl<T> Default for MsgpackCodec<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn invalid_data<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+impl<T: DeserializeOwned> Decoder for MsgpackCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        if src.len() < HEADER_LEN {
+            return Ok(None);
+        }
+        let mut header = [0u8; HEADER_LEN];
+        header.copy_from_slice(&src[..HEADER_LEN]);
+        let frame_len = u32::from_be_bytes(header) as usize;
+        if frame_len > self.max_frame_len {
+            return Err(invalid_data(format!(
+                "MessagePack frame of {} bytes exceeds limit of {} bytes",
+                frame_len, self.max_frame_len
+            )));
+        }
+        if src.len() < HEADER_LEN + frame_len {
+            src.reserve(HEADER_LEN + frame_len - src.len());
+            return Ok(None);
+        }
+
+        src.split_to(HEADER_LEN);
+        let frame = src.split_to(frame_len);
+        rmp_serde::from_slice(&frame)
+            .map(Some)
+            .map_err(invalid_data)
+    }
+}
+
+impl<T: Serialize> Encoder for MsgpackCodec<T> {
+    type Item = T;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let frame = rmp_serde::encode::to_vec_named(&item).map_err(invalid_data)?;
+        if frame.len() > self.max_frame_len || frame.len() > u32::MAX as usize {
+            return Err(invalid_data(format!(
+                "MessagePack frame of {} bytes exceeds limit of {} bytes",
+                frame.len(),
+                self.max_frame_len
+            )));
+        }
+
+        dst.reserve(HEADER_LEN + frame.len());
+        dst.put_u32_be(frame.len() as u32);
+        dst.put_slice(&frame);
+        Ok(())
+    }
+}
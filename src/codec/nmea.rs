@@ -0,0 +1,144 @@
+use bytes::{BufMut, BytesMut};
+use log::warn;
+use tokio::codec::{Decoder, Encoder};
+
+use std::io;
+
+// NMEA 0183 limits a sentence to 82 characters including `$` and `\r\n`
+const MAX_SENTENCE_LEN: usize = 82;
+
+/// Single NMEA 0183 sentence like `$GPGGA,...*47`
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NmeaSentence {
+    /// Talker identifier like `GP` or `P` for proprietary sentences
+    pub talker: String,
+    /// Sentence formatter like `GGA`
+    pub sentence_type: String,
+    /// Comma separated data fields
+    pub fields: Vec<String>,
+    /// `true` only if sentence carried `*HH` checksum and it matched
+    pub checksum_valid: bool,
+}
+
+/// Codec for NMEA 0183 sentences used by GPS receivers and marine instruments.
+///
+/// Bytes before `$` are skipped. Sentence with missing, wrong or garbled checksum is decoded with
+/// `checksum_valid` set to `false`. The encoder always appends a checksum and rejects with
+/// `InvalidInput` address and fields containing `,`, `*`, `$`, `\r` or `\n`.
+#[derive(Debug, Default)]
+pub struct NmeaCodec;
+
+impl NmeaCodec {
+    pub fn new() -> Self {
+        NmeaCodec
+    }
+}
+
+fn checksum(body: &[u8]) -> u8 {
+    body.iter().fold(0, |acc, b| acc ^ b)
+}
+
+fn invalid_data<E>(err: E) -> io::Error
+where
+    E: Into<Box<dyn std::error::Error + Send + Sync>>,
+{
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+// characters that would change sentence structure
+const RESERVED: &[char] = &[',', '*', '$', '\r', '\n'];
+
+fn check_reserved(part: &str) -> Result<(), io::Error> {
+    if part.contains(RESERVED) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "NMEA sentence part `{}` contains reserved character",
+                part.escape_default()
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn parse_sentence(line: &[u8]) -> Result<NmeaSentence, io::Error> {
+    // line is without `$` and `\r\n`
+    let (body, checksum_valid) = match line.iter().position(|&b| b == b'*') {
+        Some(star) => {
+            // garbled checksum is as wrong as a mismatched one
+            let expected = std::str::from_utf8(&line[star + 1..])
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            (&line[..star], expected == Some(checksum(&line[..star])))
+        }
+        None => (line, false),
+    };
+
+    let body = std::str::from_utf8(body).map_err(invalid_data)?;
+    let mut fields = body.split(',');
+    let address = fields.next().unwrap_or_default();
+    let talker_len = if address.starts_with('P') { 1 } else { 2 };
+    if address.len() <= talker_len || !address.is_ascii() {
+        return Err(invalid_data(format!("invalid NMEA address `{}`", address)));
+    }
+
+    Ok(NmeaSentence {
+        talker: address[..talker_len].to_owned(),
+        sentence_type: address[talker_len..].to_owned(),
+        fields: fields.map(ToOwned::to_owned).collect(),
+        checksum_valid,
+    })
+}
+
+impl Decoder for NmeaCodec {
+    type Item = NmeaSentence;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            match src.iter().position(|&b| b == b'$') {
+                Some(start) => {
+                    src.split_to(start);
+                }
+                None => {
+                    src.clear();
+                    return Ok(None);
+                }
+            }
+
+            let end = match src.windows(2).position(|w| w == b"\r\n") {
+                Some(end) => end,
+                None if src.len() > MAX_SENTENCE_LEN => {
+                    warn!("NmeaCodec => discarding unterminated sentence");
+                    src.split_to(1);
+                    continue;
+                }
+                None => return Ok(None),
+            };
+
+            let line = src.split_to(end + 2);
+            return parse_sentence(&line[1..end]).map(Some);
+        }
+    }
+}
+
+impl Encoder for NmeaCodec {
+    type Item = NmeaSentence;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        check_reserved(&item.talker)?;
+        check_reserved(&item.sentence_type)?;
+        let mut body = item.talker + &item.sentence_type;
+        for field in &item.fields {
+            check_reserved(field)?;
+            body.push(',');
+            body.push_str(field);
+        }
+
+        let sentence = format!("${}*{:02X}\r\n", body, checksum(body.as_bytes()));
+        dst.reserve(sentence.len());
+        dst.put_slice(sentence.as_bytes());
+        Ok(())
+    }
+}
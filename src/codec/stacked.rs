@@ -0,0 +1,92 @@
+use bytes::BytesMut;
+use tokio::codec::{Decoder, Encoder};
+
+use std::io;
+
+/// Codec running `inner` on top of frames of `outer`.
+///
+/// Items are encoded by `inner` and the produced bytes are framed by `outer`. Decoded payloads of
+/// `outer` are fed to `inner` as a byte stream, so one inner item may span many outer frames.
+/// Use [codec_stack!](crate::codec_stack) for more than two layers.
+#[derive(Debug, Default)]
+pub struct StackedCodec<Outer, Inner> {
+    outer: Outer,
+    inner: Inner,
+    // outer payloads not consumed by `inner` yet
+    inner_buf: BytesMut,
+}
+
+impl<Outer, Inner> StackedCodec<Outer, Inner> {
+    pub fn new(outer: Outer, inner: Inner) -> Self {
+        Self {
+            outer,
+            inner,
+            inner_buf: BytesMut::new(),
+        }
+    }
+
+    pub fn outer(&self) -> &Outer {
+        &self.outer
+    }
+
+    pub fn inner(&self) -> &Inner {
+        &self.inner
+    }
+
+    pub fn into_parts(self) -> (Outer, Inner) {
+        (self.outer, self.inner)
+    }
+}
+
+impl<Outer, Inner> Decoder for StackedCodec<Outer, Inner>
+where
+    Outer: Decoder<Item = BytesMut>,
+    Outer::Error: Into<io::Error>,
+    Inner: Decoder,
+    Inner::Error: Into<io::Error>,
+{
+    type Item = Inner::Item;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        loop {
+            if let Some(item) = self.inner.decode(&mut self.inner_buf).map_err(Into::into)? {
+                return Ok(Some(item));
+            }
+            match self.outer.decode(src).map_err(Into::into)? {
+                Some(payload) => self.inner_buf.extend_from_slice(&payload),
+                None => return Ok(None),
+            }
+        }
+    }
+}
+
+impl<Outer, Inner> Encoder for StackedCodec<Outer, Inner>
+where
+    Outer: Encoder<Item = BytesMut>,
+    Outer::Error: Into<io::Error>,
+    Inner: Encoder,
+    Inner::Error: Into<io::Error>,
+{
+    type Item = Inner::Item;
+    type Error = io::Error;
+
+    fn encode(&mut self, item: Self::Item, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut payload = BytesMut::new();
+        self.inner.encode(item, &mut payload).map_err(Into::into)?;
+        self.outer.encode(payload, dst).map_err(Into::into)
+    }
+}
+
+/// Stack codecs from the outermost (closest to the wire) to the innermost.
+///
+/// `codec_stack!(a, b, c)` is `StackedCodec::new(a, StackedCodec::new(b, c))`.
+#[macro_export]
+macro_rules! codec_stack {
+    ($codec:expr $(,)?) => {
+        $codec
+    };
+    ($outer:expr, $($inner:expr),+ $(,)?) => {
+        $crate::codec::StackedCodec::new($outer, $crate::codec_stack!($($inner),+))
+    };
+}
@@ -0,0 +1,188 @@
+//! Adapter for crates using `futures::io` traits.
+
+use crate::UniConnect;
+
+use futures03::compat::Compat01As03;
+use futures03::io::{AsyncRead, AsyncWrite};
+use futures03::task::{Context, Poll};
+
+use std::io;
+use std::pin::Pin;
+
+/// UniConnect implementing `futures::io::AsyncRead` and `futures::io::AsyncWrite`.
+///
+/// Must be polled by a futures 0.3 executor. Readiness of the underlying tokio 0.1 I/O is
+/// forwarded to the futures 0.3 waker.
+pub struct FuturesIoConn(Compat01As03<UniConnect>);
+
+impl FuturesIoConn {
+    pub fn new(conn: UniConnect) -> Self {
+        FuturesIoConn(Compat01As03::new(conn))
+    }
+
+    pub fn get_ref(&self) -> &UniConnect {
+        self.0.get_ref()
+    }
+
+    pub fn get_mut(&mut self) -> &mut UniConnect {
+        self.0.get_mut()
+    }
+
+    pub fn into_uni_connect(self) -> UniConnect {
+        self.0.into_inner()
+    }
+}
+
+impl AsyncRead for FuturesIoConn {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for FuturesIoConn {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
+
+// Run tokio 0.1 poll `f` with its task notifications forwarded to the waker of `cx`
+#[cfg(feature = "async-std")]
+fn poll_01<T>(
+    cx: &mut Context<'_>,
+    f: impl FnOnce() -> futures::Poll<T, io::Error>,
+) -> Poll<io::Result<T>> {
+    use futures::executor::{self, Notify};
+    use std::sync::Arc;
+
+    struct WakerNotify(std::task::Waker);
+
+    impl Notify for WakerNotify {
+        fn notify(&self, _id: usize) {
+            self.0.wake_by_ref();
+        }
+    }
+
+    // tokio 0.1 registers interest with the current task, poll `f` once inside one
+    let mut f = Some(f);
+    let mut task = executor::spawn(futures::future::poll_fn(move || {
+        f.take().expect("polled once")()
+    }));
+    let notify = Arc::new(WakerNotify(cx.waker().clone()));
+    match task.poll_future_notify(&notify, 0) {
+        Ok(futures::Async::Ready(value)) => Poll::Ready(Ok(value)),
+        Ok(futures::Async::NotReady) => Poll::Pending,
+        Err(err) => Poll::Ready(Err(err)),
+    }
+}
+
+/// Same as [FuturesIoConn] without the wrapper, UniConnect is `Unpin`.
+#[cfg(feature = "async-std")]
+impl AsyncRead for UniConnect {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let conn = self.get_mut();
+        poll_01(cx, || tokio::io::AsyncRead::poll_read(conn, buf))
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl AsyncWrite for UniConnect {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let conn = self.get_mut();
+        poll_01(cx, || tokio::io::AsyncWrite::poll_write(conn, buf))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let conn = self.get_mut();
+        poll_01(cx, || tokio::io::AsyncWrite::poll_flush(conn))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let conn = self.get_mut();
+        poll_01(cx, || tokio::io::AsyncWrite::shutdown(conn))
+    }
+}
+
+/// UniConnect for APIs taking a dedicated async-std stream type. `async_std::io::Read` and
+/// `async_std::io::Write` are the `futures::io` traits, implemented by delegating to
+/// [UniConnect].
+#[cfg(feature = "async-std")]
+pub struct AsyncStdUniConnect(UniConnect);
+
+#[cfg(feature = "async-std")]
+impl AsyncStdUniConnect {
+    pub fn get_ref(&self) -> &UniConnect {
+        &self.0
+    }
+
+    pub fn get_mut(&mut self) -> &mut UniConnect {
+        &mut self.0
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl From<UniConnect> for AsyncStdUniConnect {
+    fn from(conn: UniConnect) -> Self {
+        AsyncStdUniConnect(conn)
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl From<AsyncStdUniConnect> for UniConnect {
+    fn from(conn: AsyncStdUniConnect) -> Self {
+        conn.0
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl AsyncRead for AsyncStdUniConnect {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+#[cfg(feature = "async-std")]
+impl AsyncWrite for AsyncStdUniConnect {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_close(cx)
+    }
+}
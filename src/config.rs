@@ -0,0 +1,164 @@
+//! File based configuration of UniConnect.
+
+use crate::address::ConnectionAddress;
+use crate::builder::RetryingTcpOrSerial;
+use crate::retry::RetryConfig;
+use crate::retrying_tcp_stream::TcpStreamSettings;
+use crate::serial::SerialConfig;
+use crate::UniConnect;
+
+use serde::{Deserialize, Serialize};
+
+use std::error::Error;
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Everything needed to build a UniConnect with [RetryingTcpOrSerial].
+///
+/// `serial` and `use_retrying_serial` are used only for serial addresses and `tcp` only for TCP
+/// ones. `retry` sets reconnect delays of both, `None` uses [RetryConfig::default]. `priority` is
+/// used only by [WeightedFailoverPool](crate::failover::WeightedFailoverPool).
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct UniConnectConfig {
+    pub address: ConnectionAddress,
+    #[serde(default)]
+    pub serial: Option<SerialConfig>,
+    #[serde(default)]
+    pub tcp: Option<TcpStreamSettings>,
+    /// Open serial port as [RetryingSerial](crate::retrying_serial::RetryingSerial)
+    #[serde(default)]
+    pub use_retrying_serial: bool,
+    #[serde(default)]
+    pub retry: Option<RetryConfig>,
+    /// Lower value is preferred
+    #[serde(default = "default_priority")]
+    pub priority: u8,
+}
+
+fn default_priority() -> u8 {
+    UniConnectConfig::DEFAULT_PRIORITY
+}
+
+impl UniConnectConfig {
+    pub const DEFAULT_PRIORITY: u8 = 128;
+
+    /// Read config from TOML file.
+    #[cfg(feature = "toml")]
+    pub fn from_toml_file(path: &Path) -> Result<Self, ConfigLoadError> {
+        let content = read(path)?;
+        toml::from_str(&content).map_err(|err| {
+            let line = err
+                .span()
+                .map(|span| content[..span.start].matches('\n').count() + 1);
+            ConfigLoadError::new(path, line, err)
+        })
+    }
+
+    /// Read config from JSON file.
+    #[cfg(feature = "json")]
+    pub fn from_json_file(path: &Path) -> Result<Self, ConfigLoadError> {
+        let content = read(path)?;
+        serde_json::from_str(&content).map_err(|err| {
+            let line = Some(err.line()).filter(|line| *line > 0);
+            ConfigLoadError::new(path, line, err)
+        })
+    }
+
+    /// Build UniConnect described by this config.
+    pub fn build(self) -> io::Result<UniConnect> {
+        let mut builder = RetryingTcpOrSerial::new(self.address);
+        builder
+            .set_serial_port_settings(self.serial.map(Into::into))
+            .set_tcp_settings(self.tcp)
+            .set_use_retrying_serial(self.use_retrying_serial)
+            .set_retry_config(self.retry);
+        builder.build()
+    }
+}
+
+/// Load [UniConnectConfig] from TOML file and build UniConnect.
+#[cfg(feature = "toml")]
+pub fn from_toml_file(path: &Path) -> Result<UniConnect, ConfigLoadError> {
+    build(path, UniConnectConfig::from_toml_file(path)?)
+}
+
+/// Load [UniConnectConfig] from JSON file and build UniConnect.
+#[cfg(feature = "json")]
+pub fn from_json_file(path: &Path) -> Result<UniConnect, ConfigLoadError> {
+    build(path, UniConnectConfig::from_json_file(path)?)
+}
+
+/// Build UniConnect for every config, result of each build is returned in order of `configs`.
+///
+/// Building doesn't wait for TCP connections, they are established in background by
+/// [RetryingTcpStream](crate::retrying_tcp_stream::RetryingTcpStream).
+pub fn build_all(configs: Vec<UniConnectConfig>) -> Vec<io::Result<UniConnect>> {
+    configs.into_iter().map(UniConnectConfig::build).collect()
+}
+
+/// Like [build_all] but fails on first config that can't be built, connections built before are
+/// closed.
+pub fn build_all_or_fail(configs: Vec<UniConnectConfig>) -> io::Result<Vec<UniConnect>> {
+    configs.into_iter().map(UniConnectConfig::build).collect()
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+fn read(path: &Path) -> Result<String, ConfigLoadError> {
+    std::fs::read_to_string(path).map_err(|err| ConfigLoadError::new(path, None, err))
+}
+
+#[cfg(any(feature = "toml", feature = "json"))]
+fn build(path: &Path, config: UniConnectConfig) -> Result<UniConnect, ConfigLoadError> {
+    config
+        .build()
+        .map_err(|err| ConfigLoadError::new(path, None, err))
+}
+
+/// Config file could not be read, parsed or used to build UniConnect.
+#[derive(Debug)]
+pub struct ConfigLoadError {
+    path: PathBuf,
+    line: Option<usize>,
+    error: Box<dyn Error + Send + Sync>,
+}
+
+impl ConfigLoadError {
+    #[cfg(any(feature = "toml", feature = "json"))]
+    fn new(
+        path: &Path,
+        line: Option<usize>,
+        error: impl Into<Box<dyn Error + Send + Sync>>,
+    ) -> Self {
+        Self {
+            path: path.to_path_buf(),
+            line,
+            error: error.into(),
+        }
+    }
+
+    /// Path of config file
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Line where parsing failed, if known
+    pub fn line(&self) -> Option<usize> {
+        self.line
+    }
+}
+
+impl fmt::Display for ConfigLoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "{}:{}: {}", self.path.display(), line, self.error),
+            None => write!(f, "{}: {}", self.path.display(), self.error),
+        }
+    }
+}
+
+impl Error for ConfigLoadError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&*self.error)
+    }
+}
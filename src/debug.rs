@@ -0,0 +1,227 @@
+//! Protocol debugging helpers.
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::Poll;
+
+use std::fmt::Write as _;
+use std::io::{self, Read, Write};
+#[cfg(feature = "tokio-console")]
+use std::time::Instant;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Connection wrapper emitting a `tracing` TRACE event with hex dump of every read and write.
+///
+/// Every event carries `direction` (`RX` or `TX`), `timestamp` (seconds since UNIX epoch),
+/// `offset` (bytes transferred in that direction before this chunk) and a dump in Wireshark
+/// format:
+///
+/// ```text
+/// 0000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0d 0a     Hello, w orld!..
+/// ```
+pub struct HexDumpConn<T> {
+    inner: T,
+    rx_offset: u64,
+    tx_offset: u64,
+}
+
+impl<T> HexDumpConn<T> {
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            rx_offset: 0,
+            tx_offset: 0,
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Read> Read for HexDumpConn<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        trace_dump("RX", self.rx_offset, &buf[..n]);
+        self.rx_offset += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: Write> Write for HexDumpConn<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.inner.write(buf)?;
+        trace_dump("TX", self.tx_offset, &buf[..n]);
+        self.tx_offset += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for HexDumpConn<T> {}
+
+impl<T: AsyncWrite> AsyncWrite for HexDumpConn<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
+
+fn trace_dump(direction: &'static str, offset: u64, data: &[u8]) {
+    if data.is_empty() || !tracing::enabled!(tracing::Level::TRACE) {
+        return;
+    }
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs_f64())
+        .unwrap_or_default();
+    tracing::trace!(
+        direction,
+        timestamp,
+        offset,
+        len = data.len(),
+        "\n{}",
+        hex_dump(data)
+    );
+}
+
+/// Format `data` in Wireshark hex dump format, 16 bytes per line.
+pub fn hex_dump(data: &[u8]) -> String {
+    let mut out = String::new();
+    for (line, chunk) in data.chunks(16).enumerate() {
+        if line > 0 {
+            out.push('\n');
+        }
+        let _ = write!(out, "{:04x}  ", line * 16);
+        for i in 0..16 {
+            match chunk.get(i) {
+                Some(byte) => {
+                    let _ = write!(out, "{:02x} ", byte);
+                }
+                None => out.push_str("   "),
+            }
+            if i == 7 {
+                out.push(' ');
+            }
+        }
+        out.push(' ');
+        for (i, byte) in chunk.iter().enumerate() {
+            if i == 8 {
+                out.push(' ');
+            }
+            out.push(if byte.is_ascii_graphic() || *byte == b' ' {
+                *byte as char
+            } else {
+                '.'
+            });
+        }
+    }
+    out
+}
+
+/// Connection wrapper recording every read and write poll as a `uniconnect.poll` TRACE span.
+///
+/// Span fields are `conn` (name given to [new](Self::new)), `op` (`read`, `write` or `flush`),
+/// `bytes` transferred, `ready` (`false` if the poll would block) and `duration_us` spent in the
+/// inner poll. Spans are picked up by any `tracing` subscriber, e.g. `console-subscriber`.
+#[cfg(feature = "tokio-console")]
+pub struct InstrumentedConn<T> {
+    inner: T,
+    name: String,
+}
+
+#[cfg(feature = "tokio-console")]
+impl<T> InstrumentedConn<T> {
+    pub fn new(inner: T, name: &str) -> Self {
+        Self {
+            inner,
+            name: name.to_string(),
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn instrument<R>(
+        &mut self,
+        op: &'static str,
+        bytes: impl FnOnce(&R) -> usize,
+        f: impl FnOnce(&mut T) -> io::Result<R>,
+    ) -> io::Result<R> {
+        let span = tracing::trace_span!(
+            "uniconnect.poll",
+            conn = %self.name,
+            op,
+            bytes = tracing::field::Empty,
+            ready = tracing::field::Empty,
+            duration_us = tracing::field::Empty,
+        );
+        let _entered = span.enter();
+
+        let start = Instant::now();
+        let res = f(&mut self.inner);
+        span.record("duration_us", start.elapsed().as_micros() as u64);
+        match &res {
+            Ok(ok) => {
+                span.record("bytes", bytes(ok) as u64);
+                span.record("ready", true);
+            }
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                span.record("bytes", 0u64);
+                span.record("ready", false);
+            }
+            Err(err) => tracing::trace!(error = %err, "poll failed"),
+        }
+        res
+    }
+}
+
+#[cfg(feature = "tokio-console")]
+impl<T: Read> Read for InstrumentedConn<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.instrument("read", |n| *n, |inner| inner.read(buf))
+    }
+}
+
+#[cfg(feature = "tokio-console")]
+impl<T: Write> Write for InstrumentedConn<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.instrument("write", |n| *n, |inner| inner.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.instrument("flush", |_| 0, |inner| inner.flush())
+    }
+}
+
+#[cfg(feature = "tokio-console")]
+impl<T: AsyncRead> AsyncRead for InstrumentedConn<T> {}
+
+#[cfg(feature = "tokio-console")]
+impl<T: AsyncWrite> AsyncWrite for InstrumentedConn<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
@@ -0,0 +1,79 @@
+//! Serde of `Duration` as seconds, e.g. `1.5`.
+//!
+//! Deserialization also accepts serde's default `{ secs, nanos }` form, so configs written
+//! before durations were (de)serialized as seconds keep working.
+
+use serde::de::{self, value::MapAccessDeserializer, Deserialize, Deserializer, Visitor};
+use serde::Serializer;
+
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+pub(crate) fn serialize<S: Serializer>(
+    duration: &Duration,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_f64(duration.as_secs_f64())
+}
+
+pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Duration, D::Error> {
+    deserializer.deserialize_any(SecsVisitor)
+}
+
+struct SecsVisitor;
+
+impl<'de> Visitor<'de> for SecsVisitor {
+    type Value = Duration;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("duration in seconds")
+    }
+
+    fn visit_f64<E: de::Error>(self, secs: f64) -> Result<Duration, E> {
+        Duration::try_from_secs_f64(secs).map_err(E::custom)
+    }
+
+    fn visit_u64<E: de::Error>(self, secs: u64) -> Result<Duration, E> {
+        Ok(Duration::from_secs(secs))
+    }
+
+    fn visit_i64<E: de::Error>(self, secs: i64) -> Result<Duration, E> {
+        u64::try_from(secs)
+            .map(Duration::from_secs)
+            .map_err(|_| E::custom("duration can't be negative"))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Duration, A::Error> {
+        Duration::deserialize(MapAccessDeserializer::new(map))
+    }
+}
+
+/// Same for `Option<Duration>`, use together with `#[serde(default)]`.
+#[cfg(feature = "retrying-tcp")]
+pub(crate) mod option {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use std::time::Duration;
+
+    #[derive(Deserialize)]
+    struct Secs(#[serde(with = "super")] Duration);
+
+    pub(crate) fn serialize<S: Serializer>(
+        duration: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match duration {
+            Some(duration) => serializer.serialize_some(&duration.as_secs_f64()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub(crate) fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<Secs>::deserialize(deserializer)?.map(|Secs(duration)| duration))
+    }
+}
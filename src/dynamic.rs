@@ -0,0 +1,86 @@
+//! Type erased connection.
+
+use crate::UniConnect;
+
+use tokio::prelude::{AsyncRead, AsyncWrite, Poll};
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Anything [DynUniConnect] can hold, implemented for every `Send` type implementing both
+/// [AsyncRead] and [AsyncWrite].
+pub trait AsyncReadWrite: AsyncRead + AsyncWrite + Send {}
+
+impl<T: AsyncRead + AsyncWrite + Send> AsyncReadWrite for T {}
+
+/// Connection with concrete type erased, so it can be stored without the [UniConnect] enum (or
+/// a generic parameter) showing up in type signatures.
+///
+/// Created from [UniConnect] with [into_dyn](UniConnect::into_dyn), other connections like
+/// [EchoConn](crate::testing::EchoConn) can be wrapped with [new](DynUniConnect::new).
+pub struct DynUniConnect(Box<dyn AsyncReadWrite>);
+
+impl DynUniConnect {
+    pub fn new(inner: impl AsyncReadWrite + 'static) -> Self {
+        Self(Box::new(inner))
+    }
+
+    pub fn into_inner(self) -> Box<dyn AsyncReadWrite> {
+        self.0
+    }
+}
+
+impl UniConnect {
+    /// Erase concrete variant, see [DynUniConnect].
+    pub fn into_dyn(self) -> DynUniConnect {
+        DynUniConnect::new(self)
+    }
+}
+
+impl From<UniConnect> for DynUniConnect {
+    fn from(conn: UniConnect) -> Self {
+        conn.into_dyn()
+    }
+}
+
+impl Read for DynUniConnect {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl AsyncRead for DynUniConnect {
+    fn poll_read(&mut self, buf: &mut [u8]) -> Poll<usize, io::Error> {
+        self.0.poll_read(buf)
+    }
+}
+
+impl Write for DynUniConnect {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl AsyncWrite for DynUniConnect {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.0.shutdown()
+    }
+
+    fn poll_write(&mut self, buf: &[u8]) -> Poll<usize, io::Error> {
+        self.0.poll_write(buf)
+    }
+
+    fn poll_flush(&mut self) -> Poll<(), io::Error> {
+        self.0.poll_flush()
+    }
+}
+
+impl fmt::Debug for DynUniConnect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("DynUniConnect")
+    }
+}
@@ -0,0 +1,226 @@
+//! Builder settings from environment variables, see
+//! [from_env](crate::builder::RetryingTcpOrSerial::from_env).
+
+use crate::address::ConnectionAddress;
+use crate::builder::{
+    DataBits, FlowControl, Parity, RetryingTcpOrSerial, SerialPortSettings, StopBits,
+    TcpStreamSettings,
+};
+use crate::serial::validate_baud_rate;
+
+use std::env::{self, VarError};
+use std::fmt;
+use std::time::Duration;
+
+/// Prefix of variables read by [from_env](RetryingTcpOrSerial::from_env)
+pub const DEFAULT_ENV_PREFIX: &str = "UNICONNECT";
+
+/// Environment variable is missing or can't be used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum EnvConfigError {
+    /// Required variable is not set
+    Missing(String),
+    /// Variable is set to a value that can't be used
+    Malformed {
+        name: String,
+        value: String,
+        reason: String,
+    },
+}
+
+impl fmt::Display for EnvConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EnvConfigError::Missing(name) => {
+                write!(f, "environment variable {} is not set", name)
+            }
+            EnvConfigError::Malformed {
+                name,
+                value,
+                reason,
+            } => write!(f, "invalid {}=`{}`: {}", name, value, reason),
+        }
+    }
+}
+
+impl std::error::Error for EnvConfigError {}
+
+impl From<EnvConfigError> for std::io::Error {
+    fn from(err: EnvConfigError) -> Self {
+        std::io::Error::new(std::io::ErrorKind::InvalidInput, err)
+    }
+}
+
+impl RetryingTcpOrSerial {
+    /// Builder configured by `UNICONNECT_*` environment variables, see
+    /// [from_env_prefix](RetryingTcpOrSerial::from_env_prefix).
+    pub fn from_env() -> Result<Self, EnvConfigError> {
+        Self::from_env_prefix(DEFAULT_ENV_PREFIX)
+    }
+
+    /// Builder configured by environment variables starting with `prefix` and `_`:
+    ///
+    /// * `{prefix}_ADDR` (required) -- [ConnectionAddress], e.g. `tcp://10.0.0.5:502` or
+    ///   `/dev/ttyUSB0`
+    /// * `{prefix}_BAUD` -- [supported](crate::serial::validate_baud_rate) baud rate
+    /// * `{prefix}_DATA_BITS` -- `5` to `8`
+    /// * `{prefix}_PARITY` -- `none`, `odd` or `even`
+    /// * `{prefix}_STOP_BITS` -- `1` or `2`
+    /// * `{prefix}_FLOW_CONTROL` -- `none`, `software` or `hardware`
+    /// * `{prefix}_TIMEOUT_MS` -- serial port timeout in milliseconds
+    /// * `{prefix}_NODELAY` -- `TCP_NODELAY`, `true`/`false` or `1`/`0`
+    /// * `{prefix}_CONNECT_TIMEOUT_MS` -- TCP connect timeout in milliseconds
+    ///
+    /// Serial and TCP settings are set only when at least one of their variables is set, the
+    /// rest take default values.
+    ///
+    /// ```no_run
+    /// use tokio_uniconnect::builder::RetryingTcpOrSerial;
+    ///
+    /// // MY_APP_ADDR=/dev/ttyUSB0 MY_APP_BAUD=19200
+    /// let conn = RetryingTcpOrSerial::from_env_prefix("MY_APP")?.build()?;
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    pub fn from_env_prefix(prefix: &str) -> Result<Self, EnvConfigError> {
+        let vars = EnvVars { prefix };
+        let address = vars.parse("ADDR", |value| {
+            ConnectionAddress::detect(value).map_err(|err| err.to_string())
+        })?;
+        let address = address.ok_or_else(|| EnvConfigError::Missing(vars.name("ADDR")))?;
+        let mut builder = RetryingTcpOrSerial::new(address);
+
+        let mut serial = SerialPortSettings::default();
+        let mut serial_set = false;
+        if let Some(baud) = vars.parse("BAUD", parse_baud)? {
+            serial.baud_rate = baud;
+            serial_set = true;
+        }
+        if let Some(data_bits) = vars.parse("DATA_BITS", parse_data_bits)? {
+            serial.data_bits = data_bits;
+            serial_set = true;
+        }
+        if let Some(parity) = vars.parse("PARITY", parse_parity)? {
+            serial.parity = parity;
+            serial_set = true;
+        }
+        if let Some(stop_bits) = vars.parse("STOP_BITS", parse_stop_bits)? {
+            serial.stop_bits = stop_bits;
+            serial_set = true;
+        }
+        if let Some(flow_control) = vars.parse("FLOW_CONTROL", parse_flow_control)? {
+            serial.flow_control = flow_control;
+            serial_set = true;
+        }
+        if let Some(timeout) = vars.parse("TIMEOUT_MS", parse_millis)? {
+            serial.timeout = timeout;
+            serial_set = true;
+        }
+        if serial_set {
+            builder.set_serial_port_settings(Some(serial));
+        }
+
+        if let Some(nodelay) = vars.parse("NODELAY", parse_bool)? {
+            let mut tcp = TcpStreamSettings::default();
+            tcp.set_nodelay(nodelay);
+            builder.set_tcp_settings(Some(tcp));
+        }
+        if let Some(connect_timeout) = vars.parse("CONNECT_TIMEOUT_MS", parse_millis)? {
+            builder.set_connect_timeout(Some(connect_timeout));
+        }
+        Ok(builder)
+    }
+}
+
+struct EnvVars<'a> {
+    prefix: &'a str,
+}
+
+impl EnvVars<'_> {
+    fn name(&self, suffix: &str) -> String {
+        format!("{}_{}", self.prefix, suffix)
+    }
+
+    // `None` if variable is not set
+    fn parse<T>(
+        &self,
+        suffix: &str,
+        parse: impl FnOnce(&str) -> Result<T, String>,
+    ) -> Result<Option<T>, EnvConfigError> {
+        let name = self.name(suffix);
+        let malformed = |value: String, reason: String| EnvConfigError::Malformed {
+            name: name.clone(),
+            value,
+            reason,
+        };
+        match env::var(&name) {
+            Ok(value) => match parse(value.trim()) {
+                Ok(parsed) => Ok(Some(parsed)),
+                Err(reason) => Err(malformed(value, reason)),
+            },
+            Err(VarError::NotPresent) => Ok(None),
+            Err(VarError::NotUnicode(value)) => Err(malformed(
+                value.to_string_lossy().into_owned(),
+                "not valid unicode".to_owned(),
+            )),
+        }
+    }
+}
+
+fn parse_baud(value: &str) -> Result<u32, String> {
+    let baud = value
+        .parse()
+        .map_err(|_| "expected baud rate like 9600".to_owned())?;
+    validate_baud_rate(baud).map_err(|err| err.to_string())?;
+    Ok(baud)
+}
+
+fn parse_data_bits(value: &str) -> Result<DataBits, String> {
+    match value {
+        "5" => Ok(DataBits::Five),
+        "6" => Ok(DataBits::Six),
+        "7" => Ok(DataBits::Seven),
+        "8" => Ok(DataBits::Eight),
+        _ => Err("expected 5 to 8".to_owned()),
+    }
+}
+
+fn parse_parity(value: &str) -> Result<Parity, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Ok(Parity::None),
+        "odd" => Ok(Parity::Odd),
+        "even" => Ok(Parity::Even),
+        _ => Err("expected none, odd or even".to_owned()),
+    }
+}
+
+fn parse_stop_bits(value: &str) -> Result<StopBits, String> {
+    match value {
+        "1" => Ok(StopBits::One),
+        "2" => Ok(StopBits::Two),
+        _ => Err("expected 1 or 2".to_owned()),
+    }
+}
+
+fn parse_flow_control(value: &str) -> Result<FlowControl, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "none" => Ok(FlowControl::None),
+        "software" => Ok(FlowControl::Software),
+        "hardware" => Ok(FlowControl::Hardware),
+        _ => Err("expected none, software or hardware".to_owned()),
+    }
+}
+
+fn parse_millis(value: &str) -> Result<Duration, String> {
+    value
+        .parse()
+        .map(Duration::from_millis)
+        .map_err(|_| "expected milliseconds".to_owned())
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err("expected true, false, 1 or 0".to_owned()),
+    }
+}
@@ -0,0 +1,56 @@
+//! Abstract connection creation.
+//!
+//! Application code holding an `Arc<dyn ConnectionFactory>` instead of a concrete builder can be
+//! tested with [MockFactory](crate::testing::MockFactory).
+//!
+//! ```no_run
+//! use std::sync::Arc;
+//! use tokio::prelude::Future;
+//! use tokio_uniconnect::factory::ConnectionFactory;
+//!
+//! struct Application {
+//!     factory: Arc<dyn ConnectionFactory>,
+//! }
+//!
+//! impl Application {
+//!     fn connect(&self) -> impl Future<Item = (), Error = std::io::Error> {
+//!         self.factory.create().map(|_conn| ())
+//!     }
+//! }
+//! ```
+
+use crate::UniConnect;
+
+use std::io;
+use tokio::prelude::Future;
+
+/// Future returned by [ConnectionFactory::create]
+pub type CreateFuture = Box<dyn Future<Item = UniConnect, Error = io::Error> + Send>;
+
+/// Creates new connections.
+pub trait ConnectionFactory: Send + Sync {
+    fn create(&self) -> CreateFuture;
+}
+
+/// [ConnectionFactory] building every connection with a [RetryingTcpOrSerial].
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use tokio_uniconnect::builder::{ConnectionAddress, RetryingTcpOrSerial};
+/// use tokio_uniconnect::factory::{ConnectionFactory, DefaultFactory};
+///
+/// let address: ConnectionAddress = "tcp://127.0.0.1:5000".parse().unwrap();
+/// let factory: Arc<dyn ConnectionFactory> =
+///     Arc::new(DefaultFactory(RetryingTcpOrSerial::new(address)));
+/// ```
+///
+/// [RetryingTcpOrSerial]: crate::builder::RetryingTcpOrSerial
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+pub struct DefaultFactory(pub crate::builder::RetryingTcpOrSerial);
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+impl ConnectionFactory for DefaultFactory {
+    fn create(&self) -> CreateFuture {
+        Box::new(tokio::prelude::future::result(self.0.clone().build()))
+    }
+}
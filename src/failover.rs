@@ -0,0 +1,245 @@
+//! Fail over between connections of different priority.
+
+use crate::config::UniConnectConfig;
+use crate::UniConnect;
+
+use log::{debug, warn};
+use tokio::prelude::{Async, AsyncRead, AsyncWrite, Future, Poll};
+use tokio::sync::watch;
+use tokio::timer::Delay;
+
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+/// Active connection of [WeightedFailoverPool] changed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SwitchEvent {
+    /// Priority of previously active connection, `None` if there was none
+    pub from: Option<u8>,
+    /// Priority of newly active connection, `None` if all connections are down
+    pub to: Option<u8>,
+}
+
+struct Member {
+    config: UniConnectConfig,
+    conn: Option<UniConnect>,
+}
+
+impl Member {
+    fn connect(&mut self) {
+        self.conn = match self.config.clone().build() {
+            Ok(conn) => Some(conn),
+            Err(err) => {
+                warn!(
+                    "WeightedFailoverPool => connecting {} failed: {}",
+                    self.config.address.redacted(),
+                    err
+                );
+                None
+            }
+        };
+    }
+
+    fn is_connected(&self) -> bool {
+        self.conn.as_ref().is_some_and(UniConnect::is_connected)
+    }
+
+    // Drive (re)connection of standby connection without touching its data
+    fn poll_standby(&mut self) -> bool {
+        if let Some(conn) = &mut self.conn {
+            if let Err(err) = conn.poll_flush() {
+                self.fail(err);
+            }
+        }
+        self.is_connected()
+    }
+
+    fn fail(&mut self, err: io::Error) {
+        warn!(
+            "WeightedFailoverPool => connection {} (priority {}) failed: {}",
+            self.config.address.redacted(),
+            self.config.priority,
+            err
+        );
+        self.conn = None;
+    }
+}
+
+/// Connections tried in priority order ([UniConnectConfig::priority], lowest value first).
+///
+/// Reads and writes go to the connected connection with the highest priority. As soon as a
+/// connection with higher priority than the active one is connected again the pool switches
+/// back to it. Connections that fail are rebuilt every
+/// [retry_interval](WeightedFailoverPool::set_retry_interval), connections using
+/// [RetryingTcpStream](crate::retrying_tcp_stream::RetryingTcpStream) reconnect on their own.
+///
+/// # Note
+/// Switching is checked on every read and write, data buffered by the previous connection is
+/// lost.
+pub struct WeightedFailoverPool {
+    members: Vec<Member>,
+    active: Option<usize>,
+    retry_interval: Duration,
+    retry_delay: Option<Delay>,
+    switch_tx: watch::Sender<SwitchEvent>,
+    switch_rx: watch::Receiver<SwitchEvent>,
+}
+
+impl WeightedFailoverPool {
+    /// Build connection for every config. Configs that fail to build are retried later.
+    pub fn new(mut configs: Vec<UniConnectConfig>) -> Self {
+        configs.sort_by_key(|config| config.priority);
+        let members = configs
+            .into_iter()
+            .map(|config| {
+                let mut member = Member { config, conn: None };
+                member.connect();
+                member
+            })
+            .collect();
+        let (switch_tx, switch_rx) = watch::channel(SwitchEvent {
+            from: None,
+            to: None,
+        });
+        Self {
+            members,
+            active: None,
+            retry_interval: Duration::from_secs(1),
+            retry_delay: None,
+            switch_tx,
+            switch_rx,
+        }
+    }
+
+    /// Time between attempts to rebuild failed connections, 1 second by default.
+    pub fn set_retry_interval(&mut self, retry_interval: Duration) {
+        self.retry_interval = retry_interval;
+    }
+
+    /// Priority of active connection, `None` if no connection is active.
+    pub fn active_priority(&self) -> Option<u8> {
+        self.active.map(|index| self.members[index].config.priority)
+    }
+
+    /// Receive [SwitchEvent] every time active connection changes.
+    pub fn switch_events(&self) -> watch::Receiver<SwitchEvent> {
+        self.switch_rx.clone()
+    }
+
+    // Rebuild failed connections once retry delay elapses
+    fn poll_retry(&mut self) {
+        if self.members.iter().all(|member| member.conn.is_some()) {
+            self.retry_delay = None;
+            return;
+        }
+        let retry_interval = self.retry_interval;
+        let delay = self
+            .retry_delay
+            .get_or_insert_with(|| Delay::new(Instant::now() + retry_interval));
+        if let Ok(Async::NotReady) = delay.poll() {
+            return;
+        }
+
+        for member in self
+            .members
+            .iter_mut()
+            .filter(|member| member.conn.is_none())
+        {
+            member.connect();
+        }
+        // register wakeup for next attempt
+        let mut delay = Delay::new(Instant::now() + retry_interval);
+        let _ = delay.poll();
+        self.retry_delay = Some(delay);
+    }
+
+    // Index of connection that should be used now
+    fn select(&mut self) -> Option<usize> {
+        self.poll_retry();
+        let active = self.active;
+        let selected = self
+            .members
+            .iter_mut()
+            .enumerate()
+            .position(|(index, member)| {
+                if Some(index) == active {
+                    member.is_connected()
+                } else {
+                    member.poll_standby()
+                }
+            });
+
+        if selected != self.active {
+            let event = SwitchEvent {
+                from: self.active_priority(),
+                to: selected.map(|index| self.members[index].config.priority),
+            };
+            debug!(
+                "WeightedFailoverPool => switching from {:?} to {:?}",
+                event.from, event.to
+            );
+            self.active = selected;
+            // pool holds a receiver, broadcast can't fail
+            let _ = self.switch_tx.broadcast(event);
+        }
+        selected
+    }
+
+    // Run `op` on selected connection, failing over on error
+    fn with_active<R>(
+        &mut self,
+        mut op: impl FnMut(&mut UniConnect) -> io::Result<R>,
+    ) -> io::Result<R> {
+        loop {
+            let index = match self.select() {
+                Some(index) => index,
+                None => return Err(io::ErrorKind::WouldBlock.into()),
+            };
+            let member = &mut self.members[index];
+            match op(member.conn.as_mut().expect("selected connection exists")) {
+                Err(err) if err.kind() != io::ErrorKind::WouldBlock => member.fail(err),
+                res => return res,
+            }
+        }
+    }
+}
+
+impl Read for WeightedFailoverPool {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let empty = buf.is_empty();
+        self.with_active(|conn| match conn.read(buf) {
+            Ok(0) if !empty => Err(io::ErrorKind::UnexpectedEof.into()),
+            res => res,
+        })
+    }
+}
+
+impl AsyncRead for WeightedFailoverPool {}
+
+impl Write for WeightedFailoverPool {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.with_active(|conn| conn.write(buf))
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.with_active(|conn| conn.flush())
+    }
+}
+
+impl AsyncWrite for WeightedFailoverPool {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        let mut ready = true;
+        for conn in self
+            .members
+            .iter_mut()
+            .filter_map(|member| member.conn.as_mut())
+        {
+            ready &= conn.shutdown()?.is_ready();
+        }
+        Ok(if ready {
+            Async::Ready(())
+        } else {
+            Async::NotReady
+        })
+    }
+}
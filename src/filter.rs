@@ -0,0 +1,118 @@
+//! Transparent transformation of connection data.
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::{Async, Poll};
+
+use std::borrow::Cow;
+use std::io::{self, Read, Write};
+
+/// Middleware transforming data read from and written to a connection.
+pub trait ConnFilter: Send {
+    /// Transform `n` bytes just read into `buf` in place and return new length. Returning `0`
+    /// for `n > 0` swallows the data, the next chunk is read instead.
+    fn on_read(&mut self, buf: &mut [u8], n: usize) -> io::Result<usize>;
+
+    /// Transform `buf` before it's written. Every byte of `buf` is seen exactly once.
+    fn on_write<'a>(&mut self, buf: &'a [u8]) -> io::Result<Cow<'a, [u8]>>;
+}
+
+/// Connection with a [ConnFilter] applied on every read and write.
+///
+/// Transformed output which the inner connection doesn't accept at once is kept and written
+/// before next write or on flush.
+pub struct Filtered<T, F> {
+    inner: T,
+    filter: F,
+    // transformed bytes not written yet
+    pending: Vec<u8>,
+}
+
+impl<T, F> Filtered<T, F> {
+    pub fn new(inner: T, filter: F) -> Self {
+        Self {
+            inner,
+            filter,
+            pending: Vec::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn filter(&self) -> &F {
+        &self.filter
+    }
+
+    /// Return inner connection and filter. Transformed bytes not written yet are lost.
+    pub fn into_inner(self) -> (T, F) {
+        (self.inner, self.filter)
+    }
+}
+
+impl<T: Write, F> Filtered<T, F> {
+    // Write pending bytes, `WouldBlock` if not all of them were written
+    fn drain_pending(&mut self) -> io::Result<()> {
+        while !self.pending.is_empty() {
+            match self.inner.write(&self.pending)? {
+                0 => return Err(io::ErrorKind::WriteZero.into()),
+                n => {
+                    self.pending.drain(..n);
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<T: Read, F: ConnFilter> Read for Filtered<T, F> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let n = self.inner.read(buf)?;
+            if n == 0 {
+                return Ok(0);
+            }
+            let n = self.filter.on_read(buf, n)?;
+            if n > 0 {
+                return Ok(n);
+            }
+        }
+    }
+}
+
+impl<T: Write, F: ConnFilter> Write for Filtered<T, F> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.drain_pending()?;
+        let out = self.filter.on_write(buf)?;
+        let written = match self.inner.write(&out) {
+            Ok(n) => n,
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => 0,
+            Err(err) => return Err(err),
+        };
+        // filter has already seen `buf` so the rest must be kept
+        self.pending.extend_from_slice(&out[written..]);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.drain_pending()?;
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncRead, F: ConnFilter> AsyncRead for Filtered<T, F> {}
+
+impl<T: AsyncWrite, F: ConnFilter> AsyncWrite for Filtered<T, F> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self.drain_pending() {
+            Ok(()) => {}
+            Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => return Ok(Async::NotReady),
+            Err(err) => return Err(err),
+        }
+        self.inner.shutdown()
+    }
+}
@@ -0,0 +1,32 @@
+//! Bodies of the `cargo fuzz` targets in `fuzz/`, so they are built and smoke tested with the
+//! rest of the crate.
+
+use crate::codec::{LinCodec, MsgpackCodec, NmeaCodec};
+
+use bytes::BytesMut;
+use tokio::codec::Decoder;
+
+pub fn lin(data: &[u8]) {
+    decode_chunked(LinCodec::new(), data);
+}
+
+pub fn nmea(data: &[u8]) {
+    decode_chunked(NmeaCodec::new(), data);
+}
+
+pub fn msgpack(data: &[u8]) {
+    decode_chunked(
+        MsgpackCodec::<(String, Vec<u8>, Option<i64>, Vec<f64>)>::new(),
+        data,
+    );
+}
+
+// Feed input in small chunks to exercise partial frames
+fn decode_chunked<D: Decoder>(mut codec: D, data: &[u8]) {
+    let mut buf = BytesMut::new();
+    for chunk in data.chunks(7) {
+        buf.extend_from_slice(chunk);
+        while let Ok(Some(_)) = codec.decode(&mut buf) {}
+    }
+    let _ = codec.decode_eof(&mut buf);
+}
@@ -0,0 +1,86 @@
+//! Recent connection events kept for post-mortem debugging.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+// events kept by `ConnectionHistory::default()`
+const DEFAULT_MAX_LEN: usize = 64;
+
+/// What happened to the connection
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HistoryEventKind {
+    Connected,
+    Disconnected,
+    Error,
+    Reconnecting,
+    Shutdown,
+}
+
+/// Single entry of [ConnectionHistory]
+#[derive(Clone, Debug)]
+pub struct HistoryEvent {
+    pub timestamp: Instant,
+    pub kind: HistoryEventKind,
+    pub detail: String,
+}
+
+/// Ring buffer of the last `max_len` connection events, oldest first.
+#[derive(Clone, Debug)]
+pub struct ConnectionHistory {
+    events: VecDeque<HistoryEvent>,
+    max_len: usize,
+}
+
+impl ConnectionHistory {
+    pub fn new(max_len: usize) -> Self {
+        Self {
+            events: VecDeque::with_capacity(max_len),
+            max_len,
+        }
+    }
+
+    /// Add event dropping the oldest one if history is full
+    pub fn push(&mut self, kind: HistoryEventKind, detail: impl Into<String>) {
+        if self.max_len == 0 {
+            return;
+        }
+        if self.events.len() == self.max_len {
+            self.events.pop_front();
+        }
+        self.events.push_back(HistoryEvent {
+            timestamp: Instant::now(),
+            kind,
+            detail: detail.into(),
+        });
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &HistoryEvent> {
+        self.events.iter()
+    }
+
+    pub fn last(&self) -> Option<&HistoryEvent> {
+        self.events.back()
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn max_len(&self) -> usize {
+        self.max_len
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear()
+    }
+}
+
+impl Default for ConnectionHistory {
+    fn default() -> Self {
+        Self::new(DEFAULT_MAX_LEN)
+    }
+}
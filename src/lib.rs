@@ -1,43 +1,230 @@
 //! This crate create abstract layer over common connections types. At this moment it support:
-//! * [tokio::net::TcpStream](tokio::net::TcpStream)
+//! * [tokio::net::TcpStream](tokio::net::TcpStream) -- built with socket options by
+//!   [TcpOnly](tcp_only::TcpOnly)
 //! * [RetringTcpStream](retrying_tcp_stream::RetryingTcpStream) -- only via
-//! [builder](builder::RetryingTcpOrSerial)
+//!   [builder](builder::RetryingTcpOrSerial)
 //! * [tokio_serial::Serial](tokio_serial::Serial)
+//! * [RetryingSerial](retrying_serial::RetryingSerial) -- reopens serial port on error
+//! * [tokio::net::UnixStream](tokio::net::UnixStream) -- on unix, including Linux abstract
+//!   namespace sockets
+//! * [UDP](udp::UdpConn) unicast to a single peer
+//! * [named pipe](named_pipe::NamedPipeClient) client -- on Windows
+//! * [WebSocket](websocket::WebSocketConn) -- with `websocket` feature
+//! * [TLS](tls::TlsStream) client connection -- with `tls` feature
 //!
 //! Idea is to create builder that will parse connection point and create proper UniConnect. An
 //! example builder can be found in [builder](builder).
 //!
 //! Version 0.1 is compatible with tokio 0.1
+//!
+//! # Features
+//! * `serial` (default) -- [tokio_serial::Serial] support
+//! * `retrying-tcp` (default) -- [RetringTcpStream](retrying_tcp_stream::RetryingTcpStream)
+//!   support
+//!
+//! * `serde` -- `Serialize` and `Deserialize` for settings and [ConnectionAddress](address::ConnectionAddress)
+//! * `toml`, `json` -- load UniConnect from config files, requires `serial` and `retrying-tcp`
+//! * `config-reload` -- [WatchedConnector](reload::WatchedConnector) rebuilding connection on
+//!   config change, requires `serial` and `retrying-tcp`
+//! * `tracing` -- [debug] helpers emitting `tracing` events
+//! * `tokio-console` -- per poll spans of [InstrumentedConn](debug::InstrumentedConn)
+//! * `futures-io` -- [FuturesIoConn](compat::FuturesIoConn) implementing `futures::io` traits
+//! * `async-std` -- `futures::io` traits implemented directly on [UniConnect] and
+//!   [AsyncStdUniConnect](compat::AsyncStdUniConnect), for async-std and tide applications
+//! * `pcap` -- [capture] traffic into PCAP files
+//! * `msgpack` -- length prefixed MessagePack codec
+//! * `websocket` -- [WebSocket](websocket::WebSocketConn) variant carrying binary messages
+//! * `rate-limit` -- bandwidth shaping with [TokenBucketConn](rate_limit::TokenBucketConn)
+//! * `tls` -- [TLS](tls) client connections with rustls
+//! * `mock` -- in-memory [MockUniConnect](mock::MockUniConnect) for unit tests, not meant for
+//!   production
+//! * `history` -- keep recent connection events in
+//!   [RetringTcpStream](retrying_tcp_stream::RetryingTcpStream)
+//! * `fuzz` -- [fuzz] target bodies used by `fuzz/`
+//!
+//! [builder](builder) is available only with both `serial` and `retrying-tcp`.
+//!
+//! # Quick start
+//! The [uniconnect!] macro covers simple cases of the builder.
+//!
+//! TCP connection that reconnects on error:
+//! ```no_run
+//! let conn = tokio_uniconnect::uniconnect!("tcp://127.0.0.1:5000")?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! TCP with `TCP_NODELAY`:
+//! ```no_run
+//! let conn = tokio_uniconnect::uniconnect!("tcp://127.0.0.1:5000", nodelay = true)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Serial port with baud rate and parity:
+//! ```no_run
+//! let conn = tokio_uniconnect::uniconnect!("serial:///dev/ttyUSB0", baud = 9600, parity = none)?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Serial port in 19200 7E2 with hardware flow control:
+//! ```no_run
+//! use tokio_uniconnect::uniconnect;
+//!
+//! let conn = uniconnect!(
+//!     "/dev/ttyS0",
+//!     baud = 19200,
+//!     data_bits = 7,
+//!     parity = even,
+//!     stop_bits = 2,
+//!     flow_control = hardware,
+//!     timeout_ms = 100,
+//! )?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
+//!
+//! Address taken from environment variable:
+//! ```no_run
+//! let conn = tokio_uniconnect::uniconnect!(env = "DEVICE_ADDR")?;
+//! # Ok::<(), std::io::Error>(())
+//! ```
 
+pub mod address;
+pub mod bridge;
+pub mod buffered;
 /// Contains common builders for UniConnect
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
 pub mod builder;
 
-mod retrying_tcp_stream;
+#[cfg(feature = "pcap")]
+pub mod capture;
+pub mod codec;
+#[cfg(feature = "futures-io")]
+pub mod compat;
+#[cfg(all(feature = "serde", feature = "serial", feature = "retrying-tcp"))]
+pub mod config;
+#[cfg(feature = "tracing")]
+pub mod debug;
+#[cfg(feature = "serde")]
+mod duration_secs;
+pub mod dynamic;
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+pub mod env;
+pub mod factory;
+#[cfg(all(feature = "serde", feature = "serial", feature = "retrying-tcp"))]
+pub mod failover;
+pub mod filter;
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+#[cfg(feature = "history")]
+pub mod history;
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[doc(hidden)]
+pub mod macros;
+pub mod merge;
+#[cfg(feature = "mock")]
+pub mod mock;
+#[cfg(windows)]
+pub mod named_pipe;
+pub mod parse;
+pub mod pool;
+#[cfg(feature = "rate-limit")]
+pub mod rate_limit;
+#[cfg(all(
+    feature = "config-reload",
+    feature = "serial",
+    feature = "retrying-tcp"
+))]
+pub mod reload;
+pub mod resolver;
+pub mod retry;
 
+#[cfg(feature = "serial")]
+pub mod retrying_serial;
+#[cfg(feature = "retrying-tcp")]
+pub mod retrying_tcp_stream;
+#[cfg(feature = "serial")]
+pub mod serial;
+pub mod shutdown;
+pub mod split;
+pub mod stats;
+#[cfg(target_os = "linux")]
+mod sys;
+#[cfg(feature = "retrying-tcp")]
+pub mod tcp_only;
+pub mod testing;
+pub mod timeout;
+#[cfg(feature = "tls")]
+pub mod tls;
+pub mod udp;
+#[cfg(unix)]
+pub mod unix;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+
+#[cfg(feature = "retrying-tcp")]
 use crate::retrying_tcp_stream::RetryingTcpStream;
+use bytes::Bytes;
+use log::warn;
+use tokio::codec::{BytesCodec, Decoder, Encoder, Framed, FramedWrite};
 use tokio::net::TcpStream;
-use tokio::prelude::{AsyncRead, AsyncWrite, Poll};
+#[cfg(unix)]
+use tokio::net::UnixStream;
+use tokio::prelude::{future, AsyncRead, AsyncWrite, Future, FutureExt, Poll, Sink};
+#[cfg(feature = "serial")]
 use tokio_serial::{self, Serial};
 
 use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::time::Duration;
 
 use derive_more::From;
 
 /// Universal Connector
 #[derive(From)]
+#[allow(clippy::large_enum_variant)]
 pub enum UniConnect {
     TcpStream(TcpStream),
     /// tokio TcpStream connector that reconnect on error
+    #[cfg(feature = "retrying-tcp")]
     RetringTcpStream(RetryingTcpStream),
+    #[cfg(feature = "serial")]
     Serial(Serial),
+    /// Serial port that reopens on error
+    #[cfg(feature = "serial")]
+    RetryingSerial(retrying_serial::RetryingSerial),
+    #[cfg(unix)]
+    UnixStream(UnixStream),
+    /// UDP datagrams to a single peer
+    UdpSocket(udp::UdpConn),
+    /// Client end of Windows named pipe
+    #[cfg(windows)]
+    NamedPipe(named_pipe::NamedPipeClient),
+    /// Binary messages over WebSocket
+    #[cfg(feature = "websocket")]
+    WebSocket(websocket::WebSocketConn),
+    /// TLS client connection over tokio TcpStream
+    #[cfg(feature = "tls")]
+    TlsStream(tls::TlsStream),
 }
 
 impl Read for UniConnect {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         match self {
             UniConnect::TcpStream(inner) => inner.read(buf),
+            #[cfg(feature = "retrying-tcp")]
             UniConnect::RetringTcpStream(inner) => inner.read(buf),
+            #[cfg(feature = "serial")]
             UniConnect::Serial(inner) => inner.read(buf),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.read(buf),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.read(buf),
+            UniConnect::UdpSocket(inner) => inner.read(buf),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => inner.read(buf),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => inner.read(buf),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.read(buf),
         }
     }
 }
@@ -46,15 +233,41 @@ impl Write for UniConnect {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         match self {
             UniConnect::TcpStream(inner) => inner.write(buf),
+            #[cfg(feature = "retrying-tcp")]
             UniConnect::RetringTcpStream(inner) => inner.write(buf),
+            #[cfg(feature = "serial")]
             UniConnect::Serial(inner) => inner.write(buf),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.write(buf),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.write(buf),
+            UniConnect::UdpSocket(inner) => inner.write(buf),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => inner.write(buf),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => inner.write(buf),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.write(buf),
         }
     }
     fn flush(&mut self) -> io::Result<()> {
         match self {
             UniConnect::TcpStream(inner) => inner.flush(),
+            #[cfg(feature = "retrying-tcp")]
             UniConnect::RetringTcpStream(inner) => inner.flush(),
+            #[cfg(feature = "serial")]
             UniConnect::Serial(inner) => inner.flush(),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.flush(),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.flush(),
+            UniConnect::UdpSocket(inner) => inner.flush(),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => inner.flush(),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => inner.flush(),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.flush(),
         }
     }
 }
@@ -63,24 +276,63 @@ impl AsyncWrite for UniConnect {
     fn shutdown(&mut self) -> Poll<(), io::Error> {
         match self {
             UniConnect::TcpStream(inner) => inner.shutdown(),
+            #[cfg(feature = "retrying-tcp")]
             UniConnect::RetringTcpStream(inner) => inner.shutdown(),
+            #[cfg(feature = "serial")]
             UniConnect::Serial(inner) => inner.shutdown(),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.shutdown(),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.shutdown(),
+            UniConnect::UdpSocket(inner) => inner.shutdown(),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => inner.shutdown(),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => inner.shutdown(),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.shutdown(),
         }
     }
 
     fn poll_write(&mut self, buf: &[u8]) -> Poll<usize, io::Error> {
         match self {
             UniConnect::TcpStream(inner) => inner.poll_write(buf),
+            #[cfg(feature = "retrying-tcp")]
             UniConnect::RetringTcpStream(inner) => inner.poll_write(buf),
+            #[cfg(feature = "serial")]
             UniConnect::Serial(inner) => inner.poll_write(buf),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.poll_write(buf),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.poll_write(buf),
+            UniConnect::UdpSocket(inner) => inner.poll_write(buf),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => inner.poll_write(buf),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => inner.poll_write(buf),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.poll_write(buf),
         }
     }
 
     fn poll_flush(&mut self) -> Poll<(), io::Error> {
         match self {
             UniConnect::TcpStream(inner) => inner.poll_flush(),
+            #[cfg(feature = "retrying-tcp")]
             UniConnect::RetringTcpStream(inner) => inner.poll_flush(),
+            #[cfg(feature = "serial")]
             UniConnect::Serial(inner) => inner.poll_flush(),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.poll_flush(),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.poll_flush(),
+            UniConnect::UdpSocket(inner) => inner.poll_flush(),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => inner.poll_flush(),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => inner.poll_flush(),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.poll_flush(),
         }
     }
 }
@@ -89,8 +341,630 @@ impl AsyncRead for UniConnect {
     fn poll_read(&mut self, buf: &mut [u8]) -> Poll<usize, io::Error> {
         match self {
             UniConnect::TcpStream(inner) => inner.poll_read(buf),
+            #[cfg(feature = "retrying-tcp")]
             UniConnect::RetringTcpStream(inner) => inner.poll_read(buf),
+            #[cfg(feature = "serial")]
             UniConnect::Serial(inner) => inner.poll_read(buf),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.poll_read(buf),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.poll_read(buf),
+            UniConnect::UdpSocket(inner) => inner.poll_read(buf),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => inner.poll_read(buf),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => inner.poll_read(buf),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.poll_read(buf),
+        }
+    }
+}
+
+/// Transport used by [UniConnect], see [kind](UniConnect::kind).
+///
+/// All variants exist regardless of enabled features.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ConnectionKind {
+    Tcp,
+    RetryingTcp,
+    Serial,
+    RetryingSerial,
+    Unix,
+    Udp,
+    NamedPipe,
+    WebSocket,
+    Tls,
+}
+
+impl std::fmt::Display for ConnectionKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            ConnectionKind::Tcp => "TCP",
+            ConnectionKind::RetryingTcp => "RetryingTCP",
+            ConnectionKind::Serial => "Serial",
+            ConnectionKind::RetryingSerial => "RetryingSerial",
+            ConnectionKind::Unix => "Unix",
+            ConnectionKind::Udp => "UDP",
+            ConnectionKind::NamedPipe => "NamedPipe",
+            ConnectionKind::WebSocket => "WebSocket",
+            ConnectionKind::Tls => "TLS",
+        })
+    }
+}
+
+/// Displays [redacted](UniConnect::redacted) peer.
+impl std::fmt::Display for UniConnect {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        self.redacted().fmt(f)
+    }
+}
+
+/// Variant with its peer, e.g. `TcpStream { peer: "1.2.3.4:9000" }`,
+/// `RetryingTcpStream { target: "1.2.3.4:9000", state: "Connecting" }` or
+/// `Serial { path: "/dev/ttyUSB0" }`. Inner connection is not printed, state of
+/// [RetryingTcpStream] is its [state_description](RetryingTcpStream::state_description).
+impl std::fmt::Debug for UniConnect {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            UniConnect::TcpStream(inner) => f
+                .debug_struct("TcpStream")
+                .field("peer", &debug_addr(inner.peer_addr()))
+                .finish(),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => f
+                .debug_struct("RetryingTcpStream")
+                .field("target", &debug_addr(inner.peer_addr()))
+                .field("state", &inner.state_description())
+                .finish(),
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(inner) => f
+                .debug_struct("Serial")
+                .field(
+                    "path",
+                    &tokio_serial::SerialPort::name(inner).unwrap_or_else(|| "<unknown>".into()),
+                )
+                .finish(),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => f
+                .debug_struct("RetryingSerial")
+                .field("path", &inner.path())
+                .field("connected", &inner.as_serial().is_some())
+                .finish(),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => f
+                .debug_struct("UnixStream")
+                .field("peer", &inner.peer_addr().ok())
+                .finish(),
+            UniConnect::UdpSocket(inner) => f
+                .debug_struct("UdpSocket")
+                .field("peer", &inner.peer_addr().to_string())
+                .finish(),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => f
+                .debug_struct("NamedPipe")
+                .field("path", &inner.path())
+                .finish(),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => f
+                .debug_struct("WebSocket")
+                .field(
+                    "peer",
+                    &debug_addr(tokio_tungstenite::PeerAddr::peer_addr(inner.get_ref())),
+                )
+                .finish(),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => f
+                .debug_struct("TlsStream")
+                .field("peer", &debug_addr(inner.get_ref().0.peer_addr()))
+                .finish(),
+        }
+    }
+}
+
+// Address quoted like in `TcpStream { peer: "1.2.3.4:9000" }`
+fn debug_addr(addr: io::Result<SocketAddr>) -> String {
+    addr.map_or_else(|_| "<unknown>".to_owned(), |addr| addr.to_string())
+}
+
+impl UniConnect {
+    /// Transport of this connection, e.g. for logging or metrics tags.
+    pub fn kind(&self) -> ConnectionKind {
+        match self {
+            UniConnect::TcpStream(_) => ConnectionKind::Tcp,
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(_) => ConnectionKind::RetryingTcp,
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(_) => ConnectionKind::Serial,
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(_) => ConnectionKind::RetryingSerial,
+            #[cfg(unix)]
+            UniConnect::UnixStream(_) => ConnectionKind::Unix,
+            UniConnect::UdpSocket(_) => ConnectionKind::Udp,
+            #[cfg(windows)]
+            UniConnect::NamedPipe(_) => ConnectionKind::NamedPipe,
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(_) => ConnectionKind::WebSocket,
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(_) => ConnectionKind::Tls,
+        }
+    }
+
+    /// Whether the connection is established right now, without polling it.
+    ///
+    /// Always `true` for variants that only exist while connected; retrying variants return
+    /// `false` while they (re)connect, wait to retry or gave up.
+    pub fn is_connected(&self) -> bool {
+        match self {
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner.is_connected(),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.as_serial().is_some(),
+            _ => true,
+        }
+    }
+
+    /// Extract blocking [std::net::TcpStream] from `UniConnect::TcpStream`. Other variants are
+    /// returned back as `Err`.
+    ///
+    /// # Note
+    /// Returned stream is in blocking mode and is no longer driven by tokio reactor, using it
+    /// inside a task will block executor thread. Supported only on Unix, elsewhere the connection
+    /// is always returned back.
+    #[allow(clippy::result_large_err)]
+    pub fn try_into_std(self) -> Result<std::net::TcpStream, Self> {
+        match self {
+            UniConnect::TcpStream(inner) => match tcp_into_std(&inner) {
+                Ok(stream) => Ok(stream),
+                Err(_) => Err(UniConnect::TcpStream(inner)),
+            },
+            #[allow(unreachable_patterns)]
+            other => Err(other),
+        }
+    }
+
+    /// Duplicate socket of `UniConnect::TcpStream` with `dup`, other variants and platforms
+    /// other than Unix return `Unsupported`.
+    ///
+    /// # Note
+    /// Both connections share the same OS socket: data read by one is not seen by the other and
+    /// socket options or shutdown affect both. The socket is closed when both are dropped.
+    pub fn try_clone(&self) -> io::Result<UniConnect> {
+        match self {
+            UniConnect::TcpStream(inner) => {
+                let std_stream = dup_tcp(inner)?;
+                TcpStream::from_std(std_stream, &tokio::reactor::Handle::default())
+                    .map(UniConnect::TcpStream)
+            }
+            #[allow(unreachable_patterns)]
+            _ => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "only TcpStream can be cloned",
+            )),
+        }
+    }
+
+    /// Flush pending writes and shutdown connection.
+    ///
+    /// Fails with `TimedOut` if flush and shutdown don't finish within `drain_timeout`, the
+    /// connection is closed anyway. [RetryingTcpStream] that is (re)connecting with nothing
+    /// queued is closed right away, otherwise it is given `drain_timeout` to connect and send the
+    /// queued bytes.
+    pub fn shutdown_gracefully(
+        self,
+        drain_timeout: Duration,
+    ) -> impl Future<Item = (), Error = io::Error> {
+        let queued = match &self {
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => {
+                if inner.as_tcp_stream().is_none() && inner.queued_write_len() == 0 {
+                    return future::Either::A(future::ok(()));
+                }
+                inner.queued_write_len()
+            }
+            _ => 0,
+        };
+
+        let drain = tokio::io::flush(self)
+            .and_then(tokio::io::shutdown)
+            .map(drop)
+            .timeout(drain_timeout)
+            .map_err(move |err| {
+                if err.is_elapsed() {
+                    warn!(
+                        "UniConnect => drain timed out, discarding up to {} queued bytes",
+                        queued
+                    );
+                    io::Error::new(io::ErrorKind::TimedOut, "graceful shutdown timed out")
+                } else {
+                    err.into_inner()
+                        .unwrap_or_else(|| io::Error::other("timer error"))
+                }
+            });
+        future::Either::<future::FutureResult<_, _>, _>::B(drain)
+    }
+
+    /// Shut down the write half of TCP connection, the read half stays open. Queued writes are
+    /// not flushed, call `poll_flush` first. Serial port returns `Unsupported`.
+    pub fn shutdown_write(&mut self) -> io::Result<()> {
+        match self {
+            UniConnect::TcpStream(inner) => TcpStream::shutdown(inner, std::net::Shutdown::Write),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner.shutdown_write(),
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(_) | UniConnect::RetryingSerial(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "serial port has no write half to shut down",
+            )),
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => UnixStream::shutdown(inner, std::net::Shutdown::Write),
+            UniConnect::UdpSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "UDP socket has no write half to shut down",
+            )),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "named pipe has no write half to shut down",
+            )),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "WebSocket has no write half to shut down",
+            )),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TLS stream has no write half to shut down, use shutdown to send close_notify",
+            )),
+        }
+    }
+
+    /// Enable or disable `TCP_CORK`. While corked partial segments are held back until the cork
+    /// is removed, so many small writes leave as few full segments. `TCP_CORK` overrides
+    /// `TCP_NODELAY`: they are meant to be used one at a time, uncork to get low latency back.
+    /// Serial port returns `Unsupported`, [RetryingTcpStream] not connected returns
+    /// `NotConnected`.
+    #[cfg(target_os = "linux")]
+    pub fn set_cork(&self, corked: bool) -> io::Result<()> {
+        match self {
+            UniConnect::TcpStream(inner) => sys::set_cork(inner, corked),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner.set_cork(corked),
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(_) | UniConnect::RetryingSerial(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TCP_CORK is not supported by serial port",
+            )),
+            #[cfg(unix)]
+            UniConnect::UnixStream(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TCP_CORK is not supported by Unix socket",
+            )),
+            UniConnect::UdpSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TCP_CORK is not supported by UDP socket",
+            )),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TCP_CORK is not supported by WebSocket",
+            )),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => sys::set_cork(inner.get_ref().0, corked),
+        }
+    }
+
+    /// Set `SO_SNDBUF` of TCP socket. Linux doubles the requested size to leave room for
+    /// bookkeeping, so `set_send_buffer_size(65536)` results in buffer of `131072` bytes as
+    /// reported by [send_buffer_size](UniConnect::send_buffer_size). Serial port returns
+    /// `Unsupported`, [RetryingTcpStream] not connected returns `NotConnected` and the size is
+    /// not kept after reconnect.
+    pub fn set_send_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.socket()?.set_send_buffer_size(size)
+    }
+
+    /// Actual `SO_SNDBUF` of TCP socket, see [set_send_buffer_size](UniConnect::set_send_buffer_size).
+    pub fn send_buffer_size(&self) -> io::Result<usize> {
+        self.socket()?.send_buffer_size()
+    }
+
+    /// Set `SO_RCVBUF` of TCP socket. Linux doubles the requested size like for
+    /// [set_send_buffer_size](UniConnect::set_send_buffer_size).
+    pub fn set_recv_buffer_size(&self, size: usize) -> io::Result<()> {
+        self.socket()?.set_recv_buffer_size(size)
+    }
+
+    /// Actual `SO_RCVBUF` of TCP socket, see [set_recv_buffer_size](UniConnect::set_recv_buffer_size).
+    pub fn recv_buffer_size(&self) -> io::Result<usize> {
+        self.socket()?.recv_buffer_size()
+    }
+
+    // TCP socket for socket options
+    fn socket(&self) -> io::Result<&TcpStream> {
+        match self {
+            UniConnect::TcpStream(inner) => Ok(inner),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner
+                .as_tcp_stream()
+                .ok_or_else(|| io::ErrorKind::NotConnected.into()),
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(_) | UniConnect::RetryingSerial(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "socket options are not supported by serial port",
+            )),
+            #[cfg(unix)]
+            UniConnect::UnixStream(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TCP socket options are not supported by Unix socket",
+            )),
+            UniConnect::UdpSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TCP socket options are not supported by UDP socket",
+            )),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "TCP socket options are not supported by named pipe",
+            )),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "socket options are not supported by WebSocket",
+            )),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => Ok(inner.get_ref().0),
+        }
+    }
+
+    /// Convert into connection implementing `futures::io` traits.
+    #[cfg(feature = "futures-io")]
+    pub fn into_futures_io(self) -> compat::FuturesIoConn {
+        compat::FuturesIoConn::new(self)
+    }
+
+    /// Peer displayed safe for logs, see [RedactedDisplay](address::RedactedDisplay).
+    pub fn redacted(&self) -> address::RedactedDisplay<'static> {
+        let address = match self {
+            UniConnect::TcpStream(inner) => {
+                inner.peer_addr().ok().map(address::ConnectionAddress::Tcp)
+            }
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => {
+                inner.peer_addr().ok().map(address::ConnectionAddress::Tcp)
+            }
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(inner) => tokio_serial::SerialPort::name(inner)
+                .map(|name| address::ConnectionAddress::Serial(name.into())),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => {
+                Some(address::ConnectionAddress::Serial(inner.path().into()))
+            }
+            #[cfg(unix)]
+            UniConnect::UnixStream(inner) => inner.peer_addr().ok().and_then(unix_address),
+            UniConnect::UdpSocket(inner) => Some(address::ConnectionAddress::Udp {
+                hostname: inner.peer_addr().ip().to_string(),
+                port: inner.peer_addr().port(),
+            }),
+            #[cfg(windows)]
+            UniConnect::NamedPipe(inner) => {
+                Some(address::ConnectionAddress::NamedPipe(inner.path().into()))
+            }
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => tokio_tungstenite::PeerAddr::peer_addr(inner.get_ref())
+                .ok()
+                .map(address::ConnectionAddress::Tcp),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner
+                .get_ref()
+                .0
+                .peer_addr()
+                .ok()
+                .map(address::ConnectionAddress::Tcp),
+        };
+        address::RedactedDisplay::owned(address)
+    }
+
+    /// Local address of TCP connection or UDP socket. [RetryingTcpStream] not connected returns `NotConnected`,
+    /// serial port, Unix socket and WebSocket return `Unsupported`.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            UniConnect::TcpStream(inner) => inner.local_addr(),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner.local_addr(),
+            UniConnect::UdpSocket(inner) => inner.local_addr(),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(_) => Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "local address of WebSocket is not available",
+            )),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.get_ref().0.local_addr(),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.no_socket_address()),
+        }
+    }
+
+    /// Remote address of TCP connection or UDP peer. [RetryingTcpStream] returns the address it
+    /// connects to also while reconnecting, serial port and Unix socket return `Unsupported`.
+    pub fn peer_addr(&self) -> io::Result<SocketAddr> {
+        match self {
+            UniConnect::TcpStream(inner) => inner.peer_addr(),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner.peer_addr(),
+            UniConnect::UdpSocket(inner) => Ok(inner.peer_addr()),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(inner) => tokio_tungstenite::PeerAddr::peer_addr(inner.get_ref()),
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => inner.get_ref().0.peer_addr(),
+            #[allow(unreachable_patterns)]
+            _ => Err(self.no_socket_address()),
+        }
+    }
+
+    // Error of variants without IP socket address
+    fn no_socket_address(&self) -> io::Error {
+        let msg = match self {
+            #[cfg(unix)]
+            UniConnect::UnixStream(_) => "Unix socket has no IP socket address",
+            #[cfg(windows)]
+            UniConnect::NamedPipe(_) => "named pipe has no IP socket address",
+            _ => "serial port has no socket address",
+        };
+        io::Error::new(io::ErrorKind::Unsupported, msg)
+    }
+
+    /// [connection_id](RetryingTcpStream::connection_id) of [RetryingTcpStream], `None` for other
+    /// variants.
+    #[cfg(feature = "retrying-tcp")]
+    pub fn connection_id(&self) -> Option<uuid::Uuid> {
+        match self {
+            UniConnect::RetringTcpStream(inner) => Some(inner.connection_id()),
+            _ => None,
+        }
+    }
+
+    /// [Diagnostics](RetryingTcpStream::diagnostics) of [RetryingTcpStream], `None` for other
+    /// variants.
+    #[cfg(feature = "retrying-tcp")]
+    pub fn diagnostics(&self) -> Option<retrying_tcp_stream::ConnDiagnostics> {
+        match self {
+            UniConnect::RetringTcpStream(inner) => Some(inner.diagnostics()),
+            _ => None,
+        }
+    }
+
+    /// Read and write frames of `codec`, shortcut for [Framed::new].
+    pub fn framed<C: Encoder + Decoder>(self, codec: C) -> Framed<UniConnect, C> {
+        Framed::new(self, codec)
+    }
+
+    /// Write frames encoded by `codec`.
+    pub fn into_sink<C: Encoder>(
+        self,
+        codec: C,
+    ) -> impl Sink<SinkItem = C::Item, SinkError = C::Error> {
+        FramedWrite::new(self, codec)
+    }
+
+    /// Write `Bytes` as they are.
+    pub fn into_byte_sink(self) -> impl Sink<SinkItem = Bytes, SinkError = io::Error> {
+        self.into_sink(BytesCodec::new())
+    }
+
+    /// Apply `filter` on every read and write.
+    pub fn with_filter<F: filter::ConnFilter>(self, filter: F) -> filter::Filtered<UniConnect, F> {
+        filter::Filtered::new(self, filter)
+    }
+
+    /// Buffer reads and writes, see [Buffered](buffered::Buffered).
+    pub fn buffered(self) -> buffered::Buffered<UniConnect> {
+        buffered::Buffered::new(self)
+    }
+
+    /// Count bytes read and written, see [Instrumented](stats::Instrumented).
+    pub fn with_stats(self) -> stats::Instrumented<UniConnect> {
+        stats::Instrumented::new(self)
+    }
+
+    /// Fail reads and writes waiting longer than `timeout` with `TimedOut`, see
+    /// [Timeout](timeout::Timeout).
+    pub fn with_timeout(self, timeout: Duration) -> timeout::Timeout<UniConnect> {
+        timeout::Timeout::new(self, timeout)
+    }
+
+    /// Limit writes to `bytes_per_sec`, see [TokenBucketConn](rate_limit::TokenBucketConn).
+    #[cfg(feature = "rate-limit")]
+    pub fn with_bandwidth_limit(
+        self,
+        bytes_per_sec: u64,
+    ) -> rate_limit::TokenBucketConn<UniConnect> {
+        rate_limit::TokenBucketConn::new(self, bytes_per_sec)
+    }
+
+    /// Wrap into [HexDumpConn](debug::HexDumpConn) tracing every read and write.
+    #[cfg(feature = "tracing")]
+    pub fn with_hex_dump(self) -> debug::HexDumpConn<UniConnect> {
+        debug::HexDumpConn::new(self)
+    }
+
+    /// Wrap into [InstrumentedConn](debug::InstrumentedConn) recording every poll as a span.
+    #[cfg(feature = "tokio-console")]
+    pub fn instrumented(self, name: &str) -> debug::InstrumentedConn<UniConnect> {
+        debug::InstrumentedConn::new(self, name)
+    }
+
+    /// Raw file descriptor of the underlying connection. `None` when
+    /// [RetryingTcpStream] or [RetryingSerial](retrying_serial::RetryingSerial) is not
+    /// connected and for WebSocket.
+    #[cfg(unix)]
+    pub fn try_as_raw_fd(&self) -> Option<std::os::unix::io::RawFd> {
+        use std::os::unix::io::AsRawFd;
+        match self {
+            UniConnect::TcpStream(inner) => Some(inner.as_raw_fd()),
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner.as_tcp_stream().map(AsRawFd::as_raw_fd),
+            #[cfg(feature = "serial")]
+            UniConnect::Serial(inner) => Some(inner.as_raw_fd()),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.as_serial().map(AsRawFd::as_raw_fd),
+            UniConnect::UnixStream(inner) => Some(inner.as_raw_fd()),
+            UniConnect::UdpSocket(inner) => Some(inner.get_ref().as_raw_fd()),
+            #[cfg(feature = "websocket")]
+            UniConnect::WebSocket(_) => None,
+            #[cfg(feature = "tls")]
+            UniConnect::TlsStream(inner) => Some(inner.get_ref().0.as_raw_fd()),
+        }
+    }
+
+    /// Raw handle of the underlying named pipe. tokio 0.1 doesn't expose sockets and serial
+    /// ports on Windows, other variants return `None`.
+    #[cfg(windows)]
+    pub fn try_as_raw_handle(&self) -> Option<std::os::windows::io::RawHandle> {
+        use std::os::windows::io::AsRawHandle;
+        match self {
+            UniConnect::NamedPipe(inner) => Some(inner.get_ref().as_raw_handle()),
+            _ => None,
+        }
+    }
+}
+
+// Duplicate socket of `stream` into blocking std stream. Original socket is closed when `stream`
+// is dropped.
+fn tcp_into_std(stream: &TcpStream) -> io::Result<std::net::TcpStream> {
+    let std_stream = dup_tcp(stream)?;
+    std_stream.set_nonblocking(false)?;
+    Ok(std_stream)
+}
+
+// Duplicate socket of `stream`, both share the same OS socket
+#[cfg(unix)]
+fn dup_tcp(stream: &TcpStream) -> io::Result<std::net::TcpStream> {
+    use std::os::unix::io::{AsRawFd, FromRawFd};
+    let borrowed = unsafe { std::net::TcpStream::from_raw_fd(stream.as_raw_fd()) };
+    let borrowed = std::mem::ManuallyDrop::new(borrowed);
+    borrowed.try_clone()
+}
+
+// tokio 0.1 doesn't expose the socket handle on Windows
+#[cfg(not(unix))]
+fn dup_tcp(_stream: &TcpStream) -> io::Result<std::net::TcpStream> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "socket can be duplicated only on Unix",
+    ))
+}
+
+#[cfg(unix)]
+fn unix_address(addr: std::os::unix::net::SocketAddr) -> Option<address::ConnectionAddress> {
+    if let Some(path) = addr.as_pathname() {
+        return Some(address::ConnectionAddress::UnixSocket(path.into()));
+    }
+    #[cfg(target_os = "linux")]
+    {
+        use std::os::linux::net::SocketAddrExt;
+        if let Some(name) = addr.as_abstract_name() {
+            return Some(address::ConnectionAddress::AbstractUnixSocket(name.into()));
         }
     }
+    None
 }
@@ -0,0 +1,141 @@
+//! Support for the [uniconnect!](crate::uniconnect) macro.
+
+/// Build UniConnect with [RetryingTcpOrSerial](crate::builder::RetryingTcpOrSerial) from an
+/// address and optional settings. Expands to `std::io::Result<UniConnect>`.
+///
+/// Supported settings: `baud`, `data_bits` (5-8), `parity` (`none`, `odd`, `even`),
+/// `stop_bits` (1, 2), `flow_control` (`none`, `software`, `hardware`), `timeout_ms` and
+/// `nodelay`. Literal addresses with unknown scheme are rejected at compile time.
+///
+/// `uniconnect!(env = "VAR")` reads the address from environment variable `VAR` instead.
+///
+/// ```compile_fail
+/// let conn = tokio_uniconnect::uniconnect!("http://127.0.0.1:5000");
+/// ```
+#[macro_export]
+macro_rules! uniconnect {
+    (env = $var:expr) => {
+        ::std::env::var($var)
+            .map_err(|err| {
+                ::std::io::Error::new(
+                    ::std::io::ErrorKind::InvalidInput,
+                    format!("{}: {}", $var, err),
+                )
+            })
+            .and_then(|address| {
+                address
+                    .parse::<$crate::address::ConnectionAddress>()
+                    .map_err(|err| ::std::io::Error::new(::std::io::ErrorKind::InvalidInput, err))
+            })
+            .and_then(|address| $crate::builder::RetryingTcpOrSerial::new(address).build())
+    };
+    ($address:literal $(, $key:ident = $value:tt)* $(,)?) => {{
+        const _: () = assert!(
+            $crate::macros::is_known_scheme($address),
+            "unknown scheme, expected tcp://, serial://, unix:// or udp://"
+        );
+        match $address.parse::<$crate::address::ConnectionAddress>() {
+            Ok(address) => {
+                #[allow(unused_mut)]
+                let mut serial = $crate::builder::SerialPortSettings::default();
+                #[allow(unused_mut)]
+                let mut tcp = $crate::builder::TcpStreamSettings::default();
+                $($crate::uniconnect!(@set serial, tcp, $key = $value);)*
+
+                let mut builder = $crate::builder::RetryingTcpOrSerial::new(address);
+                builder.set_serial_port_settings(Some(serial));
+                builder.set_tcp_settings(Some(tcp));
+                builder.build()
+            }
+            Err(err) => Err(::std::io::Error::new(::std::io::ErrorKind::InvalidInput, err)),
+        }
+    }};
+
+    (@set $serial:ident, $tcp:ident, baud = $value:expr) => {
+        $serial.baud_rate = $value;
+    };
+    (@set $serial:ident, $tcp:ident, data_bits = 5) => {
+        $serial.data_bits = $crate::builder::DataBits::Five;
+    };
+    (@set $serial:ident, $tcp:ident, data_bits = 6) => {
+        $serial.data_bits = $crate::builder::DataBits::Six;
+    };
+    (@set $serial:ident, $tcp:ident, data_bits = 7) => {
+        $serial.data_bits = $crate::builder::DataBits::Seven;
+    };
+    (@set $serial:ident, $tcp:ident, data_bits = 8) => {
+        $serial.data_bits = $crate::builder::DataBits::Eight;
+    };
+    (@set $serial:ident, $tcp:ident, parity = none) => {
+        $serial.parity = $crate::builder::Parity::None;
+    };
+    (@set $serial:ident, $tcp:ident, parity = odd) => {
+        $serial.parity = $crate::builder::Parity::Odd;
+    };
+    (@set $serial:ident, $tcp:ident, parity = even) => {
+        $serial.parity = $crate::builder::Parity::Even;
+    };
+    (@set $serial:ident, $tcp:ident, stop_bits = 1) => {
+        $serial.stop_bits = $crate::builder::StopBits::One;
+    };
+    (@set $serial:ident, $tcp:ident, stop_bits = 2) => {
+        $serial.stop_bits = $crate::builder::StopBits::Two;
+    };
+    (@set $serial:ident, $tcp:ident, flow_control = none) => {
+        $serial.flow_control = $crate::builder::FlowControl::None;
+    };
+    (@set $serial:ident, $tcp:ident, flow_control = software) => {
+        $serial.flow_control = $crate::builder::FlowControl::Software;
+    };
+    (@set $serial:ident, $tcp:ident, flow_control = hardware) => {
+        $serial.flow_control = $crate::builder::FlowControl::Hardware;
+    };
+    (@set $serial:ident, $tcp:ident, timeout_ms = $value:expr) => {
+        $serial.timeout = ::std::time::Duration::from_millis($value);
+    };
+    (@set $serial:ident, $tcp:ident, nodelay = $value:expr) => {
+        $tcp.set_nodelay($value);
+    };
+}
+
+/// `false` if `address` has a scheme other than `tcp://`, `serial://`, `unix://` or `udp://`.
+#[doc(hidden)]
+pub const fn is_known_scheme(address: &str) -> bool {
+    let bytes = address.as_bytes();
+    if !contains(bytes, b"://") {
+        return true;
+    }
+    starts_with(bytes, b"tcp://")
+        || starts_with(bytes, b"serial://")
+        || starts_with(bytes, b"unix://")
+        || starts_with(bytes, b"udp://")
+}
+
+const fn starts_with(bytes: &[u8], prefix: &[u8]) -> bool {
+    if bytes.len() < prefix.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < prefix.len() {
+        if bytes[i] != prefix[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+const fn contains(bytes: &[u8], needle: &[u8]) -> bool {
+    let mut start = 0;
+    while start + needle.len() <= bytes.len() {
+        let mut i = 0;
+        while i < needle.len() && bytes[start + i] == needle[i] {
+            i += 1;
+        }
+        if i == needle.len() {
+            return true;
+        }
+        start += 1;
+    }
+    false
+}
@@ -0,0 +1,87 @@
+//! Read from many UniConnects at once.
+
+use crate::UniConnect;
+
+use bytes::Bytes;
+use tokio::prelude::{stream, Async, AsyncRead, Stream};
+
+use std::io::{self, Read};
+
+/// Merge `conns` into one reader returning data of whichever connection has it first.
+pub fn merge_readers(conns: Vec<UniConnect>) -> MergedReader {
+    let finished = vec![false; conns.len()];
+    MergedReader {
+        conns,
+        finished,
+        source_index: 0,
+    }
+}
+
+/// Reader created by [merge_readers].
+///
+/// Connections are polled starting after the last source so that one busy connection doesn't
+/// starve the others. A connection that reached EOF is skipped, EOF is returned when all of them
+/// did.
+pub struct MergedReader {
+    conns: Vec<UniConnect>,
+    finished: Vec<bool>,
+    source_index: usize,
+}
+
+impl MergedReader {
+    /// Index of the connection that provided the last read
+    pub fn source_index(&self) -> usize {
+        self.source_index
+    }
+
+    /// Return back merged connections
+    pub fn into_inner(self) -> Vec<UniConnect> {
+        self.conns
+    }
+
+    /// Stream of `(source_index, data)` tuples. Ends when all connections reach EOF.
+    pub fn into_stream(mut self) -> impl Stream<Item = (usize, Bytes), Error = io::Error> {
+        let mut buf = [0u8; 4096];
+        stream::poll_fn(move || match self.poll_read(&mut buf)? {
+            Async::Ready(0) => Ok(Async::Ready(None)),
+            Async::Ready(n) => Ok(Async::Ready(Some((
+                self.source_index,
+                Bytes::from(&buf[..n]),
+            )))),
+            Async::NotReady => Ok(Async::NotReady),
+        })
+    }
+}
+
+impl Read for MergedReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.conns.len();
+        for offset in 1..=len {
+            let index = (self.source_index + offset) % len;
+            if self.finished[index] {
+                continue;
+            }
+
+            match self.conns[index].read(buf) {
+                Ok(0) if !buf.is_empty() => self.finished[index] = true,
+                Ok(n) => {
+                    self.source_index = index;
+                    return Ok(n);
+                }
+                Err(ref err) if err.kind() == io::ErrorKind::WouldBlock => {}
+                Err(err) => {
+                    self.source_index = index;
+                    return Err(err);
+                }
+            }
+        }
+
+        if self.finished.iter().all(|finished| *finished) {
+            Ok(0)
+        } else {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+}
+
+impl AsyncRead for MergedReader {}
@@ -0,0 +1,136 @@
+//! In-memory connection for unit tests, see [MockUniConnect].
+
+use bytes::Bytes;
+use futures::Stream;
+use tokio::prelude::{future, Async, AsyncRead, AsyncWrite, Future, Poll};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use std::io::{self, Read, Write};
+
+// One direction written through `tx`, the other read from `rx`
+struct Pipe {
+    tx: Option<UnboundedSender<Bytes>>,
+    rx: UnboundedReceiver<Bytes>,
+    // rest of the last received chunk
+    read_buf: Bytes,
+}
+
+impl Read for Pipe {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.read_buf.is_empty() {
+            match self.rx.poll().map_err(io::Error::other)? {
+                Async::Ready(Some(data)) => self.read_buf = data,
+                Async::Ready(None) => return Ok(0),
+                Async::NotReady => return Err(io::ErrorKind::WouldBlock.into()),
+            }
+        }
+        let n = buf.len().min(self.read_buf.len());
+        buf[..n].copy_from_slice(&self.read_buf[..n]);
+        self.read_buf.advance(n);
+        Ok(n)
+    }
+}
+
+impl AsyncRead for Pipe {}
+
+impl Write for Pipe {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let tx = self.tx.as_mut().ok_or(io::ErrorKind::BrokenPipe)?;
+        if !buf.is_empty() {
+            tx.try_send(Bytes::from(buf))
+                .map_err(|_| io::Error::from(io::ErrorKind::BrokenPipe))?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for Pipe {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.tx = None;
+        Ok(Async::Ready(()))
+    }
+}
+
+fn pipe_pair() -> (Pipe, Pipe) {
+    let (a_tx, b_rx) = unbounded_channel();
+    let (b_tx, a_rx) = unbounded_channel();
+    let pipe = |tx, rx| Pipe {
+        tx: Some(tx),
+        rx,
+        read_buf: Bytes::new(),
+    };
+    (pipe(a_tx, a_rx), pipe(b_tx, b_rx))
+}
+
+/// Connection backed by in-memory channels, the other end is [MockPeer].
+///
+/// Reads return what the peer wrote and EOF once the peer is dropped. Writes are received by the
+/// peer and fail with `BrokenPipe` once the peer is dropped or after `shutdown`. Code taking
+/// [UniConnect](crate::UniConnect) can be tested with it through
+/// [DynUniConnect](crate::dynamic::DynUniConnect).
+///
+/// ```
+/// use tokio::prelude::Future;
+/// use tokio_uniconnect::mock::MockUniConnect;
+///
+/// let (conn, peer) = MockUniConnect::channel();
+/// let test = tokio::io::write_all(conn, b"ping")
+///     .and_then(move |_| peer.read_exact(4))
+///     .map(|(_, data)| assert_eq!(data, b"ping"));
+/// tokio::runtime::current_thread::block_on_all(test).unwrap();
+/// ```
+pub struct MockUniConnect(Pipe);
+
+/// Test side of [MockUniConnect]. Dropping it closes the connection.
+pub struct MockPeer(Pipe);
+
+impl MockUniConnect {
+    pub fn channel() -> (MockUniConnect, MockPeer) {
+        let (conn, peer) = pipe_pair();
+        (MockUniConnect(conn), MockPeer(peer))
+    }
+}
+
+impl MockPeer {
+    /// Send `data` to [MockUniConnect]. Fails with `BrokenPipe` if it was dropped.
+    pub fn write(mut self, data: &[u8]) -> impl Future<Item = MockPeer, Error = io::Error> {
+        future::result(self.0.write(data).map(|_| self))
+    }
+
+    /// Receive exactly `n` bytes written by [MockUniConnect]. Fails with `UnexpectedEof` if it
+    /// is dropped or shut down before.
+    pub fn read_exact(
+        self,
+        n: usize,
+    ) -> impl Future<Item = (MockPeer, Vec<u8>), Error = io::Error> {
+        tokio::io::read_exact(self.0, vec![0; n]).map(|(pipe, data)| (MockPeer(pipe), data))
+    }
+}
+
+impl Read for MockUniConnect {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.0.read(buf)
+    }
+}
+
+impl AsyncRead for MockUniConnect {}
+
+impl Write for MockUniConnect {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+impl AsyncWrite for MockUniConnect {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.0.shutdown()
+    }
+}
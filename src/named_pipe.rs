@@ -0,0 +1,81 @@
+//! Windows named pipe client connections.
+
+use crate::UniConnect;
+
+use mio_named_pipes::NamedPipe;
+use tokio::prelude::{future, AsyncRead, AsyncWrite, Future, Poll};
+use tokio::reactor::{Handle, PollEvented2};
+
+use std::fs::OpenOptions;
+use std::io::{self, Read, Write};
+use std::os::windows::fs::OpenOptionsExt;
+use std::os::windows::io::{FromRawHandle, IntoRawHandle};
+use std::path::{Path, PathBuf};
+
+// Reactor drives only pipes opened for overlapped IO
+const FILE_FLAG_OVERLAPPED: u32 = 0x4000_0000;
+
+/// Client end of named pipe like `\\.\pipe\myapp`.
+pub struct NamedPipeClient {
+    io: PollEvented2<NamedPipe>,
+    path: PathBuf,
+}
+
+impl NamedPipeClient {
+    /// Open pipe at `path`. Fails with `NotFound` when no server created the pipe, all busy pipe
+    /// instances are reported as OS error `231` (`ERROR_PIPE_BUSY`).
+    pub fn connect(path: impl Into<PathBuf>) -> io::Result<Self> {
+        let path = path.into();
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .custom_flags(FILE_FLAG_OVERLAPPED)
+            .open(&path)
+            .map_err(|err| {
+                io::Error::new(
+                    err.kind(),
+                    format!("can't open named pipe {}: {}", path.display(), err),
+                )
+            })?;
+        let pipe = unsafe { NamedPipe::from_raw_handle(file.into_raw_handle()) };
+        let io = PollEvented2::new_with_handle(pipe, &Handle::default())?;
+        Ok(Self { io, path })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn get_ref(&self) -> &NamedPipe {
+        self.io.get_ref()
+    }
+}
+
+impl Read for NamedPipeClient {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.io.read(buf)
+    }
+}
+
+impl AsyncRead for NamedPipeClient {}
+
+impl Write for NamedPipeClient {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.io.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.io.flush()
+    }
+}
+
+impl AsyncWrite for NamedPipeClient {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.io.shutdown()
+    }
+}
+
+/// Connect to named pipe at `path`.
+pub fn connect_named_pipe(path: &Path) -> impl Future<Item = UniConnect, Error = io::Error> {
+    future::result(NamedPipeClient::connect(path).map(UniConnect::from))
+}
@@ -0,0 +1,167 @@
+//! Open UniConnect from connection string.
+
+use crate::address::ConnectionAddress;
+use crate::UniConnect;
+
+use std::fmt;
+use std::io;
+use std::net::SocketAddr;
+use std::str::FromStr;
+
+/// Why connection string couldn't be turned into [UniConnect].
+#[derive(Debug)]
+pub enum UniConnectParseError {
+    /// String is not a valid [ConnectionAddress]
+    InvalidAddress(String),
+    /// Serial port path is valid but the port can't be opened
+    SerialOpenError(io::Error),
+    /// Scheme is unknown, or not supported without a feature or by sync parsing (`tls`)
+    UnsupportedScheme(String),
+    /// TCP or Unix socket connection failed
+    ConnectError(io::Error),
+}
+
+impl fmt::Display for UniConnectParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            UniConnectParseError::InvalidAddress(reason) => write!(f, "{}", reason),
+            UniConnectParseError::SerialOpenError(err) => {
+                write!(f, "can't open serial port: {}", err)
+            }
+            UniConnectParseError::UnsupportedScheme(scheme) => {
+                write!(f, "unsupported connection scheme `{}`", scheme)
+            }
+            UniConnectParseError::ConnectError(err) => write!(f, "can't connect: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for UniConnectParseError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            UniConnectParseError::SerialOpenError(err)
+            | UniConnectParseError::ConnectError(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Open connection described by [ConnectionAddress] string, e.g. `"127.0.0.1:9000"`,
+/// `"unix:///run/app.sock"` or `"/dev/ttyUSB0"`.
+///
+/// Like [RetryingTcpOrSerial](crate::builder::RetryingTcpOrSerial) with default settings: TCP
+/// uses [RetryingTcpStream](crate::retrying_tcp_stream::RetryingTcpStream) connecting in
+/// background (plain blocking connect without `retrying-tcp` feature), hostname is resolved
+/// sync, serial port and Unix socket are opened sync. `tls://` needs handshake and returns
+/// `UnsupportedScheme`.
+impl FromStr for UniConnect {
+    type Err = UniConnectParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let address = ConnectionAddress::detect(s).map_err(|err| match s.find("://") {
+            Some(pos) if !is_known_scheme(&s[..pos]) => {
+                UniConnectParseError::UnsupportedScheme(s[..pos].to_owned())
+            }
+            _ => UniConnectParseError::InvalidAddress(err.to_string()),
+        })?;
+
+        match address {
+            ConnectionAddress::Tcp(addr) => connect_tcp(&addr),
+            ConnectionAddress::TcpHost { hostname, port } => {
+                let addr = crate::resolver::resolve_first_blocking(&hostname, port)
+                    .map_err(UniConnectParseError::ConnectError)?;
+                connect_tcp(&addr)
+            }
+            #[cfg(feature = "serial")]
+            ConnectionAddress::Serial(path) => {
+                let path = match path.to_str() {
+                    Some(input) => {
+                        let (path, warning) = crate::serial::normalize_serial_path_checked(input);
+                        if let Some(warning) = warning {
+                            log::warn!("{}", warning);
+                        }
+                        path
+                    }
+                    None => path,
+                };
+                tokio_serial::Serial::from_path(path, &Default::default())
+                    .map(UniConnect::from)
+                    .map_err(UniConnectParseError::SerialOpenError)
+            }
+            #[cfg(not(feature = "serial"))]
+            ConnectionAddress::Serial(_) => {
+                Err(UniConnectParseError::UnsupportedScheme("serial".to_owned()))
+            }
+            #[cfg(unix)]
+            ConnectionAddress::UnixSocket(path) => crate::unix::connect_unix_sync(&path)
+                .map(UniConnect::from)
+                .map_err(UniConnectParseError::ConnectError),
+            #[cfg(not(unix))]
+            ConnectionAddress::UnixSocket(_) => {
+                Err(UniConnectParseError::UnsupportedScheme("unix".to_owned()))
+            }
+            #[cfg(target_os = "linux")]
+            ConnectionAddress::AbstractUnixSocket(name) => {
+                crate::unix::connect_abstract_unix_sync(&name)
+                    .map(UniConnect::from)
+                    .map_err(UniConnectParseError::ConnectError)
+            }
+            #[cfg(not(target_os = "linux"))]
+            ConnectionAddress::AbstractUnixSocket(_) => Err(
+                UniConnectParseError::UnsupportedScheme("@abstract".to_owned()),
+            ),
+            ConnectionAddress::Tls { .. } => {
+                Err(UniConnectParseError::UnsupportedScheme("tls".to_owned()))
+            }
+            ConnectionAddress::Udp { hostname, port } => {
+                crate::resolver::resolve_first_blocking(&hostname, port)
+                    .and_then(|addr| crate::udp::connect_udp(&addr))
+                    .map_err(UniConnectParseError::ConnectError)
+            }
+            #[cfg(feature = "retrying-tcp")]
+            ConnectionAddress::RetryingTcp { addr, policy } => Ok(UniConnect::from(
+                crate::retrying_tcp_stream::RetryingTcpStream::connect_with_retry(
+                    &addr,
+                    Default::default(),
+                    policy,
+                ),
+            )),
+            #[cfg(not(feature = "retrying-tcp"))]
+            ConnectionAddress::RetryingTcp { .. } => Err(UniConnectParseError::UnsupportedScheme(
+                "retrying-tcp".to_owned(),
+            )),
+            #[cfg(windows)]
+            ConnectionAddress::NamedPipe(path) => crate::named_pipe::NamedPipeClient::connect(path)
+                .map(UniConnect::from)
+                .map_err(UniConnectParseError::ConnectError),
+            #[cfg(not(windows))]
+            ConnectionAddress::NamedPipe(_) => {
+                Err(UniConnectParseError::UnsupportedScheme("pipe".to_owned()))
+            }
+        }
+    }
+}
+
+fn is_known_scheme(scheme: &str) -> bool {
+    matches!(
+        scheme,
+        "tcp" | "serial" | "unix" | "tls" | "udp" | "retrying-tcp"
+    )
+}
+
+#[cfg(feature = "retrying-tcp")]
+fn connect_tcp(addr: &SocketAddr) -> Result<UniConnect, UniConnectParseError> {
+    Ok(UniConnect::from(
+        crate::retrying_tcp_stream::RetryingTcpStream::connect(addr),
+    ))
+}
+
+#[cfg(not(feature = "retrying-tcp"))]
+fn connect_tcp(addr: &SocketAddr) -> Result<UniConnect, UniConnectParseError> {
+    std::net::TcpStream::connect(addr)
+        .and_then(|stream| {
+            tokio::net::TcpStream::from_std(stream, &tokio::reactor::Handle::default())
+        })
+        .map(UniConnect::from)
+        .map_err(UniConnectParseError::ConnectError)
+}
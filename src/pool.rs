@@ -0,0 +1,358 @@
+//! Pool of connections shared between tasks.
+
+use crate::UniConnect;
+
+use futures::future::{self, join_all, Future};
+use futures::task::{self, Task};
+use futures::{try_ready, Async, Poll, Stream};
+use log::warn;
+use tokio::prelude::{AsyncRead, AsyncWrite, FutureExt};
+use tokio::timer::Interval;
+
+use std::io::{self, Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// How pool checks its connections
+#[derive(Clone, Debug)]
+pub struct HealthConfig {
+    /// Time between two checks
+    pub interval: Duration,
+    /// Bytes written to every connection on each check, pick something the peer ignores
+    pub probe: Vec<u8>,
+    /// Connection is unhealthy if probe isn't written and flushed within this time
+    pub timeout: Duration,
+}
+
+/// Order in which [acquire](UniConnectPool::acquire) tries connections
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SelectionStrategy {
+    /// Start from the connection after the one tried first by the previous `acquire`
+    #[default]
+    RoundRobin,
+    /// Start from the connection acquired longest ago
+    LeastRecentlyUsed,
+}
+
+struct Slot {
+    // `None` while a PoolGuard holds the connection
+    conn: Mutex<Option<UniConnect>>,
+    healthy: AtomicBool,
+    // value of `Slots::uses` when last acquired, 0 if never
+    last_used: AtomicU64,
+}
+
+// State shared by the pool, Acquire futures and guards
+struct Slots {
+    slots: Vec<Slot>,
+    // tasks waiting for a connection to be returned
+    waiters: Mutex<Vec<Task>>,
+    uses: AtomicU64,
+}
+
+impl Slots {
+    fn take(&self, index: usize) -> Option<UniConnect> {
+        let conn = self.slots[index].conn.lock().unwrap().take()?;
+        let use_id = self.uses.fetch_add(1, Ordering::Relaxed) + 1;
+        self.slots[index].last_used.store(use_id, Ordering::Relaxed);
+        Some(conn)
+    }
+
+    fn put_back(&self, index: usize, conn: UniConnect) {
+        *self.slots[index].conn.lock().unwrap() = Some(conn);
+        for waiter in self.waiters.lock().unwrap().drain(..) {
+            waiter.notify();
+        }
+    }
+}
+
+/// Fixed set of connections handed out in round-robin (default) or least recently used order,
+/// see [SelectionStrategy].
+///
+/// Connections that failed health check (see
+/// [start_health_checks](UniConnectPool::start_health_checks)) are skipped until a later check
+/// succeeds.
+pub struct UniConnectPool {
+    slots: Arc<Slots>,
+    health_cfg: Option<HealthConfig>,
+    strategy: SelectionStrategy,
+    next: AtomicUsize,
+}
+
+impl UniConnectPool {
+    pub fn new(conns: Vec<UniConnect>) -> Self {
+        let slots = conns
+            .into_iter()
+            .map(|conn| Slot {
+                conn: Mutex::new(Some(conn)),
+                healthy: AtomicBool::new(true),
+                last_used: AtomicU64::new(0),
+            })
+            .collect();
+        Self {
+            slots: Arc::new(Slots {
+                slots,
+                waiters: Mutex::new(Vec::new()),
+                uses: AtomicU64::new(0),
+            }),
+            health_cfg: None,
+            strategy: SelectionStrategy::default(),
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    /// Pool of `size` connections, each built like
+    /// [RetryingTcpOrSerial::build](crate::builder::RetryingTcpOrSerial::build) from a clone of
+    /// `builder`.
+    ///
+    /// # Note
+    /// Serial port can be opened only once, use pool of size `1` or
+    /// [RetryingSerial](crate::retrying_serial::RetryingSerial) for serial ports.
+    #[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+    pub fn from_builder(
+        builder: &crate::builder::RetryingTcpOrSerial,
+        size: usize,
+    ) -> Result<Self, io::Error> {
+        let conns = (0..size)
+            .map(|_| builder.clone().build())
+            .collect::<Result<_, _>>()?;
+        Ok(Self::new(conns))
+    }
+
+    /// Order of connections tried by [acquire](UniConnectPool::acquire)
+    pub fn with_strategy(mut self, strategy: SelectionStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Health check settings used by [start_health_checks](UniConnectPool::start_health_checks)
+    pub fn with_health_checks(mut self, health_cfg: HealthConfig) -> Self {
+        self.health_cfg = Some(health_cfg);
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.slots.slots.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.slots.slots.is_empty()
+    }
+
+    /// Result of the last health check of connection `index`
+    pub fn is_healthy(&self, index: usize) -> bool {
+        self.slots.slots[index].healthy.load(Ordering::Relaxed)
+    }
+
+    /// Wait for the next healthy connection that is not in use.
+    ///
+    /// Fails with `NotConnected` when no connection is healthy.
+    pub fn acquire(&self) -> Acquire {
+        Acquire {
+            slots: self.slots.clone(),
+            candidates: self.healthy_slots(),
+        }
+    }
+
+    /// Spawn task that periodically writes probe bytes to every connection and marks those that
+    /// fail as unhealthy. Does nothing if pool was created without
+    /// [with_health_checks](UniConnectPool::with_health_checks).
+    pub fn start_health_checks(self: &Arc<Self>, executor: &tokio::runtime::TaskExecutor) {
+        let health_cfg = match &self.health_cfg {
+            Some(health_cfg) => health_cfg.clone(),
+            None => return,
+        };
+
+        let slots = self.slots.clone();
+        let probe = Arc::new(health_cfg.probe);
+        let timeout = health_cfg.timeout;
+        let checks = Interval::new_interval(health_cfg.interval)
+            .map_err(|err| warn!("UniConnectPool => health check timer failed: {}", err))
+            .for_each(move |_| {
+                let slots = slots.clone();
+                let probe = probe.clone();
+                let probes = (0..slots.slots.len()).map(move |index| {
+                    let slots = slots.clone();
+                    let acquire = Acquire {
+                        slots: slots.clone(),
+                        candidates: vec![index],
+                    };
+                    probe_conn(acquire, probe.clone(), timeout).then(move |res| {
+                        if let Err(err) = &res {
+                            warn!("UniConnectPool => connection {} unhealthy: {}", index, err);
+                        }
+                        slots.slots[index]
+                            .healthy
+                            .store(res.is_ok(), Ordering::Relaxed);
+                        Ok::<_, ()>(())
+                    })
+                });
+                let probes: Vec<_> = probes.collect();
+                join_all(probes).map(drop)
+            });
+
+        executor.spawn(checks);
+    }
+
+    // Indexes of healthy slots in order of selection strategy
+    fn healthy_slots(&self) -> Vec<usize> {
+        let slots = &self.slots.slots;
+        let len = slots.len();
+        let mut indexes: Vec<usize> = match self.strategy {
+            SelectionStrategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::Relaxed);
+                (0..len).map(|offset| (start + offset) % len).collect()
+            }
+            SelectionStrategy::LeastRecentlyUsed => {
+                let mut indexes: Vec<usize> = (0..len).collect();
+                // stable sort, ties keep index order
+                indexes.sort_by_key(|&index| slots[index].last_used.load(Ordering::Relaxed));
+                indexes
+            }
+        };
+        indexes.retain(|&index| slots[index].healthy.load(Ordering::Relaxed));
+        indexes
+    }
+}
+
+fn probe_conn(
+    acquire: Acquire,
+    probe: Arc<Vec<u8>>,
+    timeout: Duration,
+) -> impl Future<Item = (), Error = io::Error> {
+    acquire
+        .and_then(move |mut guard| {
+            let mut written = 0;
+            future::poll_fn(move || {
+                while written < probe.len() {
+                    match try_ready!(guard.poll_write(&probe[written..])) {
+                        0 => return Err(io::ErrorKind::WriteZero.into()),
+                        n => written += n,
+                    }
+                }
+                guard.poll_flush()
+            })
+        })
+        .timeout(timeout)
+        .map_err(|err| {
+            if err.is_elapsed() {
+                io::Error::new(io::ErrorKind::TimedOut, "health check timed out")
+            } else {
+                err.into_inner()
+                    .unwrap_or_else(|| io::Error::other("timer error"))
+            }
+        })
+}
+
+/// Future returned by [UniConnectPool::acquire].
+///
+/// Candidates are picked when `acquire` is called, the future doesn't borrow the pool.
+pub struct Acquire {
+    slots: Arc<Slots>,
+    candidates: Vec<usize>,
+}
+
+impl Acquire {
+    fn try_take(&self) -> Option<PoolGuard> {
+        self.candidates.iter().find_map(|&index| {
+            let conn = self.slots.take(index)?;
+            Some(PoolGuard {
+                slots: self.slots.clone(),
+                index,
+                conn: Some(conn),
+            })
+        })
+    }
+}
+
+impl Future for Acquire {
+    type Item = PoolGuard;
+    type Error = io::Error;
+
+    fn poll(&mut self) -> Poll<Self::Item, Self::Error> {
+        if self.candidates.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "no healthy connection in pool",
+            ));
+        }
+
+        if let Some(guard) = self.try_take() {
+            return Ok(Async::Ready(guard));
+        }
+        {
+            let mut waiters = self.slots.waiters.lock().unwrap();
+            if !waiters.iter().any(Task::will_notify_current) {
+                waiters.push(task::current());
+            }
+        }
+        // connection may have been returned before the task was registered
+        match self.try_take() {
+            Some(guard) => Ok(Async::Ready(guard)),
+            None => Ok(Async::NotReady),
+        }
+    }
+}
+
+/// Exclusive access to one connection of the pool. Connection is returned to the pool on drop.
+pub struct PoolGuard {
+    slots: Arc<Slots>,
+    index: usize,
+    // `None` only while dropping
+    conn: Option<UniConnect>,
+}
+
+impl PoolGuard {
+    /// Index of the connection in the pool
+    pub fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl Drop for PoolGuard {
+    fn drop(&mut self) {
+        if let Some(conn) = self.conn.take() {
+            self.slots.put_back(self.index, conn);
+        }
+    }
+}
+
+impl Deref for PoolGuard {
+    type Target = UniConnect;
+
+    fn deref(&self) -> &UniConnect {
+        self.conn.as_ref().unwrap()
+    }
+}
+
+impl DerefMut for PoolGuard {
+    fn deref_mut(&mut self) -> &mut UniConnect {
+        self.conn.as_mut().unwrap()
+    }
+}
+
+impl Read for PoolGuard {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+}
+
+impl AsyncRead for PoolGuard {}
+
+impl Write for PoolGuard {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+}
+
+impl AsyncWrite for PoolGuard {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        (**self).shutdown()
+    }
+}
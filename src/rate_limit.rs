@@ -0,0 +1,158 @@
+//! Bandwidth shaping of connections.
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::{Async, Future, Poll};
+use tokio::timer::Delay;
+
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+const NANOS_PER_SEC: u128 = 1_000_000_000;
+
+/// Bytes per second limit with burst of one second of traffic.
+#[derive(Debug)]
+struct TokenBucket {
+    rate: u64,
+    tokens: u64,
+    last_refill: Instant,
+    // wakes the task once enough tokens are refilled
+    delay: Option<Delay>,
+}
+
+impl TokenBucket {
+    fn new(bytes_per_sec: u64) -> Self {
+        assert!(bytes_per_sec > 0, "bandwidth limit must be positive");
+        Self {
+            rate: bytes_per_sec,
+            tokens: bytes_per_sec,
+            last_refill: Instant::now(),
+            delay: None,
+        }
+    }
+
+    fn refill(&mut self) {
+        let elapsed = self.last_refill.elapsed().as_nanos();
+        let refilled = elapsed * u128::from(self.rate) / NANOS_PER_SEC;
+        if refilled == 0 {
+            return;
+        }
+        if self.tokens + refilled as u64 >= self.rate {
+            self.tokens = self.rate;
+            self.last_refill = Instant::now();
+        } else {
+            self.tokens += refilled as u64;
+            // keep fraction of token not refilled yet
+            let used = refilled * NANOS_PER_SEC / u128::from(self.rate);
+            self.last_refill += Duration::from_nanos(used as u64);
+        }
+    }
+
+    // Number of bytes that can go now, at most `wanted`. Returns `WouldBlock` and schedules
+    // wake-up when there are no tokens.
+    fn poll_available(&mut self, wanted: usize) -> io::Result<usize> {
+        self.refill();
+        if self.tokens == 0 {
+            // wait for the whole `wanted` rather than waking up for every single byte
+            let missing = (wanted as u64).min(self.rate);
+            let wait = u128::from(missing) * NANOS_PER_SEC / u128::from(self.rate);
+            let deadline = self.last_refill + Duration::from_nanos(wait as u64);
+            let delay = self.delay.get_or_insert_with(|| Delay::new(deadline));
+            delay.reset(deadline);
+            match delay.poll().map_err(io::Error::other)? {
+                Async::Ready(()) => self.refill(),
+                Async::NotReady => return Err(io::ErrorKind::WouldBlock.into()),
+            }
+        }
+        Ok(wanted.min(self.tokens as usize))
+    }
+
+    fn consume(&mut self, n: usize) {
+        self.tokens = self.tokens.saturating_sub(n as u64);
+    }
+}
+
+/// Connection with writes (and optionally reads) limited to given number of bytes per second.
+///
+/// Up to one second of traffic can be sent in a burst after idle time. Writes take as much of
+/// the buffer as the limit allows at the moment, a write without any budget left returns
+/// `WouldBlock` until enough budget is refilled.
+pub struct TokenBucketConn<T> {
+    inner: T,
+    write_bucket: TokenBucket,
+    read_bucket: Option<TokenBucket>,
+}
+
+impl<T> TokenBucketConn<T> {
+    /// Limit writes to `bytes_per_sec`.
+    ///
+    /// # Panics
+    /// When `bytes_per_sec` is `0`.
+    pub fn new(inner: T, bytes_per_sec: u64) -> Self {
+        Self {
+            inner,
+            write_bucket: TokenBucket::new(bytes_per_sec),
+            read_bucket: None,
+        }
+    }
+
+    /// Limit also reads to `bytes_per_sec`, independent on the write limit.
+    ///
+    /// # Panics
+    /// When `bytes_per_sec` is `0`.
+    pub fn with_read_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.read_bucket = Some(TokenBucket::new(bytes_per_sec));
+        self
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Read> Read for TokenBucketConn<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let bucket = match &mut self.read_bucket {
+            Some(bucket) => bucket,
+            None => return self.inner.read(buf),
+        };
+        if buf.is_empty() {
+            return Ok(0);
+        }
+        let allowed = bucket.poll_available(buf.len())?;
+        let n = self.inner.read(&mut buf[..allowed])?;
+        bucket.consume(n);
+        Ok(n)
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for TokenBucketConn<T> {}
+
+impl<T: Write> Write for TokenBucketConn<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return self.inner.write(buf);
+        }
+        let allowed = self.write_bucket.poll_available(buf.len())?;
+        let n = self.inner.write(&buf[..allowed])?;
+        self.write_bucket.consume(n);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for TokenBucketConn<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
@@ -0,0 +1,150 @@
+//! Apply changed [UniConnectConfig] without restarting the application.
+
+use crate::builder::RetryingTcpOrSerial;
+use crate::config::UniConnectConfig;
+use crate::UniConnect;
+
+use log::{info, warn};
+use tokio::executor::{DefaultExecutor, Executor};
+use tokio::prelude::{Async, AsyncRead, AsyncWrite, Future, Poll};
+use tokio::sync::watch;
+
+use std::io::{self, Read, Write};
+use std::time::Duration;
+
+const DEFAULT_DRAIN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// UniConnect rebuilt whenever a new config is sent to the watch channel.
+///
+/// Channel is checked on every read and write. Changed config builds new connection first, if
+/// that fails the old connection and config are kept. The old connection is then
+/// [shut down gracefully](UniConnect::shutdown_gracefully) on a spawned task, bytes not flushed to
+/// it are lost. Sending the current config again does nothing.
+pub struct WatchedConnector {
+    conn: UniConnect,
+    config: UniConnectConfig,
+    rx: Option<watch::Receiver<UniConnectConfig>>,
+    drain_timeout: Duration,
+}
+
+impl WatchedConnector {
+    pub(crate) fn new(
+        builder: RetryingTcpOrSerial,
+        rx: watch::Receiver<UniConnectConfig>,
+    ) -> io::Result<Self> {
+        let config = builder.to_config();
+        Ok(Self {
+            conn: builder.build()?,
+            config,
+            rx: Some(rx),
+            drain_timeout: DEFAULT_DRAIN_TIMEOUT,
+        })
+    }
+
+    /// Time given to the replaced connection to flush and shutdown, 5 seconds by default.
+    pub fn set_drain_timeout(&mut self, drain_timeout: Duration) {
+        self.drain_timeout = drain_timeout;
+    }
+
+    /// Config of current connection
+    pub fn current_config(&self) -> UniConnectConfig {
+        self.config.clone()
+    }
+
+    pub fn get_ref(&self) -> &UniConnect {
+        &self.conn
+    }
+
+    pub fn get_mut(&mut self) -> &mut UniConnect {
+        &mut self.conn
+    }
+
+    pub fn into_inner(self) -> UniConnect {
+        self.conn
+    }
+
+    fn poll_config(&mut self) {
+        let rx = match &mut self.rx {
+            Some(rx) => rx,
+            None => return,
+        };
+        // poll until NotReady so this task is notified about the next change
+        let mut latest = None;
+        loop {
+            let polled = rx
+                .poll_ref()
+                .map(|ready| ready.map(|config| config.map(|c| c.clone())));
+            match polled {
+                Ok(Async::Ready(Some(config))) => latest = Some(config),
+                Ok(Async::NotReady) => break,
+                Ok(Async::Ready(None)) | Err(_) => {
+                    info!("WatchedConnector => config channel closed, keeping current config");
+                    self.rx = None;
+                    break;
+                }
+            }
+        }
+        let config = match latest {
+            Some(config) => config,
+            None => return,
+        };
+        if config == self.config {
+            return;
+        }
+
+        let conn = match config.clone().build() {
+            Ok(conn) => conn,
+            Err(err) => {
+                warn!(
+                    "WatchedConnector => new config for {} not applied: {}",
+                    config.address.redacted(),
+                    err
+                );
+                return;
+            }
+        };
+        let old = std::mem::replace(&mut self.conn, conn);
+        info!(
+            "WatchedConnector => switched from {} to {}",
+            self.config.address.redacted(),
+            config.address.redacted()
+        );
+        self.config = config;
+
+        let shutdown = old
+            .shutdown_gracefully(self.drain_timeout)
+            .map_err(|err| warn!("WatchedConnector => old connection shutdown: {}", err));
+        if DefaultExecutor::current()
+            .spawn(Box::new(shutdown))
+            .is_err()
+        {
+            warn!("WatchedConnector => no executor, old connection dropped");
+        }
+    }
+}
+
+impl Read for WatchedConnector {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.poll_config();
+        self.conn.read(buf)
+    }
+}
+
+impl AsyncRead for WatchedConnector {}
+
+impl Write for WatchedConnector {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.poll_config();
+        self.conn.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.conn.flush()
+    }
+}
+
+impl AsyncWrite for WatchedConnector {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.conn.shutdown()
+    }
+}
@@ -0,0 +1,121 @@
+//! Hostname resolution used when reconnecting by name.
+
+use futures::sync::oneshot;
+use tokio::prelude::{future, Future};
+
+use std::collections::HashMap;
+use std::io;
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::sync::Arc;
+
+/// Future returned by [AddressResolver::resolve]
+pub type ResolveFuture = Box<dyn Future<Item = Vec<SocketAddr>, Error = io::Error> + Send>;
+
+/// Resolve `hostname` and `port` into socket addresses.
+pub trait AddressResolver: Send + Sync {
+    fn resolve(&self, hostname: &str, port: u16) -> ResolveFuture;
+}
+
+/// Resolver of the operating system (`getaddrinfo`).
+///
+/// Lookup is blocking so every resolution runs on its own thread.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemResolver;
+
+impl AddressResolver for SystemResolver {
+    fn resolve(&self, hostname: &str, port: u16) -> ResolveFuture {
+        let hostname = hostname.to_string();
+        let (tx, rx) = oneshot::channel();
+        let spawned = std::thread::Builder::new()
+            .name("uniconnect-resolver".into())
+            .spawn(move || {
+                let addrs = (hostname.as_str(), port)
+                    .to_socket_addrs()
+                    .map(|addrs| addrs.collect());
+                let _ = tx.send(addrs);
+            });
+
+        match spawned {
+            Ok(_) => Box::new(rx.then(|res| match res {
+                Ok(addrs) => addrs,
+                Err(oneshot::Canceled) => Err(io::Error::other("resolver thread panicked")),
+            })),
+            Err(err) => Box::new(future::err(err)),
+        }
+    }
+}
+
+/// Resolver answering from fixed `hostname -> addresses` map, useful for tests.
+///
+/// Stored addresses are returned as they are, requested port is ignored.
+#[derive(Clone, Debug, Default)]
+pub struct StaticResolver(pub HashMap<String, Vec<SocketAddr>>);
+
+impl AddressResolver for StaticResolver {
+    fn resolve(&self, hostname: &str, _port: u16) -> ResolveFuture {
+        let res = match self.0.get(hostname) {
+            Some(addrs) => Ok(addrs.clone()),
+            None => Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{} is not in StaticResolver", hostname),
+            )),
+        };
+        Box::new(future::result(res))
+    }
+}
+
+/// Hostname resolved to no addresses.
+///
+/// Returned inside `io::Error` of kind `NotFound`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolveError {
+    pub hostname: String,
+    pub port: u16,
+}
+
+impl std::fmt::Display for ResolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}:{} resolved to no addresses",
+            self.hostname, self.port
+        )
+    }
+}
+
+impl std::error::Error for ResolveError {}
+
+impl From<ResolveError> for io::Error {
+    fn from(err: ResolveError) -> Self {
+        io::Error::new(io::ErrorKind::NotFound, err)
+    }
+}
+
+/// Resolve `hostname` with blocking `getaddrinfo` and return the first address.
+pub fn resolve_first_blocking(hostname: &str, port: u16) -> io::Result<SocketAddr> {
+    (hostname, port).to_socket_addrs()?.next().ok_or_else(|| {
+        ResolveError {
+            hostname: hostname.to_owned(),
+            port,
+        }
+        .into()
+    })
+}
+
+/// [AddressResolver] shared between settings, compared by identity.
+#[derive(Clone)]
+pub struct SharedResolver(pub Arc<dyn AddressResolver>);
+
+impl PartialEq for SharedResolver {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedResolver {}
+
+impl std::fmt::Debug for SharedResolver {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str("SharedResolver(..)")
+    }
+}
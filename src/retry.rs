@@ -0,0 +1,573 @@
+//! Reconnect policies.
+
+use rand::rngs::SmallRng;
+use rand::{Rng, SeedableRng};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Token bucket limiting reconnects of many streams sharing the same backend.
+///
+/// Clones share the same bucket. Every reconnect takes one token, a reconnect without token is
+/// delayed by `penalty`. Tokens are refilled one per `refill_rate` up to `capacity`.
+#[derive(Clone, Debug)]
+pub struct RetryBudget {
+    tokens: Arc<AtomicU32>,
+    last_refill: Arc<Mutex<Instant>>,
+    refill_rate: Duration,
+    capacity: u32,
+    penalty: Duration,
+}
+
+impl RetryBudget {
+    pub fn new(capacity: u32, refill_rate: Duration, penalty: Duration) -> Self {
+        Self {
+            tokens: Arc::new(AtomicU32::new(capacity)),
+            last_refill: Arc::new(Mutex::new(Instant::now())),
+            refill_rate,
+            capacity,
+            penalty,
+        }
+    }
+
+    /// Take one token, `false` if budget is exhausted
+    pub fn try_acquire(&self) -> bool {
+        self.refill();
+        self.tokens
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |tokens| {
+                tokens.checked_sub(1)
+            })
+            .is_ok()
+    }
+
+    /// Tokens left
+    pub fn available(&self) -> u32 {
+        self.refill();
+        self.tokens.load(Ordering::SeqCst)
+    }
+
+    /// Extra delay of reconnect without token
+    pub fn penalty(&self) -> Duration {
+        self.penalty
+    }
+
+    fn refill(&self) {
+        let mut last_refill = self
+            .last_refill
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let elapsed = last_refill.elapsed();
+        let refills = if self.refill_rate == Duration::from_secs(0) {
+            self.capacity as u128
+        } else {
+            elapsed.as_nanos() / self.refill_rate.as_nanos()
+        };
+        if refills == 0 {
+            return;
+        }
+
+        let refills = if refills >= self.capacity as u128 {
+            // bucket is full, time past that must not pay for later tokens
+            *last_refill = Instant::now();
+            self.capacity
+        } else {
+            *last_refill += self.refill_rate * refills as u32;
+            refills as u32
+        };
+        let capacity = self.capacity;
+        let _ = self
+            .tokens
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |tokens| {
+                Some(tokens.saturating_add(refills).min(capacity))
+            });
+    }
+}
+
+/// Exponential backoff between reconnect attempts of
+/// [RetryingTcpStream](crate::retrying_tcp_stream::RetryingTcpStream).
+///
+/// First reconnect waits `initial_delay`, every next failed attempt multiplies the delay by
+/// `multiplier` up to `max_delay`. Delay starts again from `initial_delay` once connected.
+/// Default `initial_delay` is zero, i.e. reconnect immediately.
+///
+/// With `max_attempts` the stream gives up after that many reconnect attempts in a row fail,
+/// reads and writes then return [RetryExhausted]. Default `None` reconnects forever.
+///
+/// With `jitter` every delay is multiplied by a random factor from `[0.5, 1.5]`, so clients that
+/// lost connection at the same time don't reconnect at the same time. Random numbers come from
+/// `shared_rng`, seeded by `jitter_seed` if set. Clones of one config share it, so streams
+/// configured by the same seeded config still wait different delays.
+///
+/// With `serde` feature delays are (de)serialized as seconds, missing fields take default
+/// values.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct RetryConfig {
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_secs"))]
+    pub initial_delay: Duration,
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_secs"))]
+    pub max_delay: Duration,
+    pub multiplier: f64,
+    pub max_attempts: Option<u32>,
+    pub jitter: bool,
+    pub jitter_seed: Option<u64>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub shared_rng: SharedJitterRng,
+}
+
+impl Default for RetryConfig {
+    fn default() -> Self {
+        Self {
+            initial_delay: Duration::ZERO,
+            max_delay: Duration::from_secs(30),
+            multiplier: 2.0,
+            max_attempts: None,
+            jitter: false,
+            jitter_seed: None,
+            shared_rng: SharedJitterRng::default(),
+        }
+    }
+}
+
+// valid config has no NaN multiplier
+impl Eq for RetryConfig {}
+
+impl Hash for RetryConfig {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.initial_delay.hash(state);
+        self.max_delay.hash(state);
+        // `-0.0 + 0.0` is `0.0`, equal multipliers hash the same
+        (self.multiplier + 0.0).to_bits().hash(state);
+        self.max_attempts.hash(state);
+        self.jitter.hash(state);
+        self.jitter_seed.hash(state);
+    }
+}
+
+impl RetryConfig {
+    /// Delay before reconnect after `attempt` consecutive failed attempts.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tokio_uniconnect::retry::RetryConfig;
+    ///
+    /// let config = RetryConfig {
+    ///     initial_delay: Duration::from_millis(100),
+    ///     max_delay: Duration::from_millis(500),
+    ///     multiplier: 2.0,
+    ///     ..RetryConfig::default()
+    /// };
+    /// let delays: Vec<_> = (0..4).map(|attempt| config.delay_for_attempt(attempt)).collect();
+    /// assert_eq!(delays, [100, 200, 400, 500].map(Duration::from_millis));
+    /// ```
+    pub fn delay_for_attempt(&self, attempt: u32) -> Duration {
+        // zero times infinite growth is NaN
+        if self.initial_delay.is_zero() {
+            return Duration::ZERO;
+        }
+        let exponent = attempt.min(i32::MAX as u32) as i32;
+        let delay = self.initial_delay.as_secs_f64() * self.multiplier.powi(exponent);
+        match Duration::try_from_secs_f64(delay) {
+            Ok(delay) => delay.min(self.max_delay),
+            // grew past what Duration holds
+            Err(_) if delay > 0.0 => self.max_delay,
+            // negative or NaN multiplier
+            Err(_) => self.initial_delay.min(self.max_delay),
+        }
+    }
+
+    /// Enable `jitter` with random numbers seeded by `seed`, reconnect delays are then the same
+    /// on every run. The config gets new `shared_rng`, not shared with earlier clones.
+    pub fn with_jitter_seed(mut self, seed: u64) -> Self {
+        self.jitter = true;
+        self.jitter_seed = Some(seed);
+        self.shared_rng = SharedJitterRng::default();
+        self
+    }
+
+    /// [delay_for_attempt](RetryConfig::delay_for_attempt) multiplied by a random factor from
+    /// `[0.5, 1.5]` if `jitter` is enabled, so the delay may exceed `max_delay` by half.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use tokio_uniconnect::retry::RetryConfig;
+    ///
+    /// let config = RetryConfig {
+    ///     initial_delay: Duration::from_secs(1),
+    ///     ..RetryConfig::default()
+    /// }
+    /// .with_jitter_seed(7);
+    /// let delay = config.jittered_delay_for_attempt(0, &mut config.jitter_rng());
+    /// assert!(delay >= Duration::from_millis(500) && delay <= Duration::from_millis(1500));
+    /// ```
+    pub fn jittered_delay_for_attempt<R: Rng + ?Sized>(
+        &self,
+        attempt: u32,
+        rng: &mut R,
+    ) -> Duration {
+        let delay = self.delay_for_attempt(attempt);
+        if !self.jitter {
+            return delay;
+        }
+        delay.mul_f64(rng.random_range(0.5..=1.5))
+    }
+
+    /// Random number generator for jitter, seeded by `jitter_seed` or from OS randomness.
+    pub fn jitter_rng(&self) -> SmallRng {
+        match self.jitter_seed {
+            Some(seed) => SmallRng::seed_from_u64(seed),
+            None => SmallRng::from_rng(&mut rand::rng()),
+        }
+    }
+
+    /// Delay streams using this config wait after `attempt` consecutive failed attempts. If
+    /// `jitter` is enabled every call takes next random factor from `shared_rng`, so clones of
+    /// seeded config give different delays.
+    pub fn next_delay(&self, attempt: u32) -> Duration {
+        if !self.jitter {
+            return self.delay_for_attempt(attempt);
+        }
+        let mut rng = self
+            .shared_rng
+            .0
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        let rng = rng.get_or_insert_with(|| self.jitter_rng());
+        self.jittered_delay_for_attempt(attempt, rng)
+    }
+}
+
+/// Random numbers for jitter shared by clones of [RetryConfig], created from
+/// [jitter_rng](RetryConfig::jitter_rng) on first use.
+///
+/// Holds state, not configuration: all instances compare equal and it isn't (de)serialized.
+#[derive(Clone, Default)]
+pub struct SharedJitterRng(Arc<Mutex<Option<SmallRng>>>);
+
+impl fmt::Debug for SharedJitterRng {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("SharedJitterRng")
+    }
+}
+
+impl PartialEq for SharedJitterRng {
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+/// Error of stream that stopped reconnecting after
+/// [max_attempts](RetryConfig::max_attempts) failed attempts.
+///
+/// Returned inside `io::Error` of kind `NotConnected`, see [is_retry_exhausted].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryExhausted {
+    pub attempts: u32,
+}
+
+impl From<RetryExhausted> for io::Error {
+    fn from(err: RetryExhausted) -> Self {
+        io::Error::new(io::ErrorKind::NotConnected, err)
+    }
+}
+
+impl fmt::Display for RetryExhausted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "gave up reconnecting after {} attempts", self.attempts)
+    }
+}
+
+impl std::error::Error for RetryExhausted {}
+
+/// `err` was caused by [RetryExhausted].
+pub fn is_retry_exhausted(err: &io::Error) -> bool {
+    err.get_ref()
+        .is_some_and(|inner| inner.is::<RetryExhausted>())
+}
+
+/// State of [CircuitBreaker].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Reconnects are attempted as usual
+    Closed,
+    /// Too many failures, no reconnect until cool-down elapses
+    Open,
+    /// Cool-down elapsed, next reconnect is a probe deciding whether circuit closes or reopens
+    HalfOpen,
+}
+
+/// Stop reconnecting for `cool_down` after `open_threshold` failures within `failure_window`.
+///
+/// Failed connection attempts and lost connections count as failures. After cool-down the
+/// circuit is half-open and allows one probe: success closes the circuit and forgets failures,
+/// failure opens it again for another cool-down.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    open_threshold: u32,
+    failure_window: Duration,
+    cool_down: Duration,
+    // failures inside window, oldest first
+    failures: VecDeque<Instant>,
+    opened_at: Option<Instant>,
+}
+
+impl CircuitBreaker {
+    /// # Panics
+    /// When `open_threshold` is `0`.
+    pub fn new(open_threshold: u32, failure_window: Duration, cool_down: Duration) -> Self {
+        assert!(
+            open_threshold > 0,
+            "circuit breaker needs positive threshold"
+        );
+        Self {
+            open_threshold,
+            failure_window,
+            cool_down,
+            failures: VecDeque::new(),
+            opened_at: None,
+        }
+    }
+
+    pub fn open_threshold(&self) -> u32 {
+        self.open_threshold
+    }
+
+    pub fn failure_window(&self) -> Duration {
+        self.failure_window
+    }
+
+    pub fn cool_down(&self) -> Duration {
+        self.cool_down
+    }
+
+    pub fn state(&self) -> CircuitState {
+        match self.opened_at {
+            None => CircuitState::Closed,
+            Some(opened_at) if opened_at.elapsed() < self.cool_down => CircuitState::Open,
+            Some(_) => CircuitState::HalfOpen,
+        }
+    }
+
+    /// End of cool-down, `None` while closed.
+    pub fn open_until(&self) -> Option<Instant> {
+        self.opened_at.map(|opened_at| opened_at + self.cool_down)
+    }
+
+    /// Count failure, returns `true` if circuit is open now.
+    pub fn record_failure(&mut self) -> bool {
+        let now = Instant::now();
+        if self.opened_at.is_some() {
+            // failed probe
+            self.opened_at = Some(now);
+            return true;
+        }
+        self.failures.push_back(now);
+        while let Some(first) = self.failures.front() {
+            if now.duration_since(*first) <= self.failure_window {
+                break;
+            }
+            self.failures.pop_front();
+        }
+        if self.failures.len() >= self.open_threshold as usize {
+            self.failures.clear();
+            self.opened_at = Some(now);
+        }
+        self.opened_at.is_some()
+    }
+
+    /// Close circuit and forget failures.
+    pub fn record_success(&mut self) {
+        self.failures.clear();
+        self.opened_at = None;
+    }
+}
+
+/// Error returned while [CircuitBreaker] is open.
+///
+/// Returned inside `io::Error` of kind `NotConnected`, see [is_circuit_open].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CircuitOpen {
+    /// End of cool-down
+    pub until: Instant,
+}
+
+impl From<CircuitOpen> for io::Error {
+    fn from(err: CircuitOpen) -> Self {
+        io::Error::new(io::ErrorKind::NotConnected, err)
+    }
+}
+
+impl fmt::Display for CircuitOpen {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "circuit breaker is open for {:?}",
+            self.until.saturating_duration_since(Instant::now())
+        )
+    }
+}
+
+impl std::error::Error for CircuitOpen {}
+
+/// `err` was caused by [CircuitOpen].
+pub fn is_circuit_open(err: &io::Error) -> bool {
+    err.get_ref().is_some_and(|inner| inner.is::<CircuitOpen>())
+}
+
+/// Delays between reconnect attempts, `None` once no more attempts should be made.
+///
+/// Implemented for every `Send` iterator of durations, so schedules compose with iterator
+/// adapters and wrappers below.
+///
+/// ```
+/// use std::time::Duration;
+/// use tokio_uniconnect::retry::{ImmediateFirstRetrySchedule, LimitedRetrySchedule};
+///
+/// let backoff = std::iter::successors(Some(Duration::from_millis(100)), |d| Some(*d * 2));
+/// let schedule = LimitedRetrySchedule::new(
+///     Box::new(ImmediateFirstRetrySchedule::new(Box::new(backoff))),
+///     3,
+/// );
+/// let delays: Vec<_> = schedule.collect();
+/// assert_eq!(
+///     delays,
+///     [Duration::ZERO, Duration::from_millis(100), Duration::from_millis(200)]
+/// );
+/// ```
+pub trait RetrySchedule: Iterator<Item = Duration> + Send {}
+
+impl<T: Iterator<Item = Duration> + Send> RetrySchedule for T {}
+
+/// Retry once after `first_delay` (immediately by default), then follow `subsequent`.
+pub struct ImmediateFirstRetrySchedule {
+    first_delay: Option<Duration>,
+    subsequent: Box<dyn RetrySchedule>,
+}
+
+impl ImmediateFirstRetrySchedule {
+    pub fn new(subsequent: Box<dyn RetrySchedule>) -> Self {
+        Self::with_first_delay(Duration::ZERO, subsequent)
+    }
+
+    pub fn with_first_delay(first_delay: Duration, subsequent: Box<dyn RetrySchedule>) -> Self {
+        Self {
+            first_delay: Some(first_delay),
+            subsequent,
+        }
+    }
+}
+
+impl Iterator for ImmediateFirstRetrySchedule {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        self.first_delay.take().or_else(|| self.subsequent.next())
+    }
+}
+
+/// Stop `inner` schedule after `remaining` attempts.
+pub struct LimitedRetrySchedule {
+    inner: Box<dyn RetrySchedule>,
+    remaining: u32,
+}
+
+impl LimitedRetrySchedule {
+    pub fn new(inner: Box<dyn RetrySchedule>, attempts: u32) -> Self {
+        Self {
+            inner,
+            remaining: attempts,
+        }
+    }
+
+    /// Attempts left, fewer if `inner` ends earlier
+    pub fn remaining(&self) -> u32 {
+        self.remaining
+    }
+}
+
+impl Iterator for LimitedRetrySchedule {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+        self.inner.next()
+    }
+}
+
+/// Limit of reconnects started per time window, shared by many streams.
+///
+/// Unlike [RetryBudget] reconnects over the limit are not penalized but spread out, each one is
+/// scheduled to the first window with a free slot. Prevents thundering herd when hundreds of
+/// streams lose connection to the same server at once.
+#[derive(Debug)]
+pub struct ReconnectGovernor {
+    permits_per_window: u32,
+    window: Duration,
+    slots: Mutex<Slots>,
+}
+
+#[derive(Debug)]
+struct Slots {
+    // start of window of the first reserved slot
+    window_start: Instant,
+    reserved: u64,
+}
+
+impl ReconnectGovernor {
+    /// Allow `permits_per_window` reconnects every `window`.
+    ///
+    /// # Panics
+    /// When `permits_per_window` is `0`.
+    pub fn new(permits_per_window: u32, window: Duration) -> Self {
+        assert!(permits_per_window > 0, "governor needs at least one permit");
+        Self {
+            permits_per_window,
+            window,
+            slots: Mutex::new(Slots {
+                window_start: Instant::now(),
+                reserved: 0,
+            }),
+        }
+    }
+
+    /// Governor shared by the whole process, 50 reconnects per second.
+    pub fn global() -> Arc<ReconnectGovernor> {
+        static GLOBAL: std::sync::OnceLock<Arc<ReconnectGovernor>> = std::sync::OnceLock::new();
+        GLOBAL
+            .get_or_init(|| Arc::new(ReconnectGovernor::new(50, Duration::from_secs(1))))
+            .clone()
+    }
+
+    pub fn permits_per_window(&self) -> u32 {
+        self.permits_per_window
+    }
+
+    pub fn window(&self) -> Duration {
+        self.window
+    }
+
+    /// Reserve a slot for one reconnect, returns when it may start. Reserved slot can't be
+    /// given back.
+    pub fn reserve(&self) -> Instant {
+        let now = Instant::now();
+        let mut slots = self.slots.lock().unwrap_or_else(|err| err.into_inner());
+        let permits = u64::from(self.permits_per_window);
+        let windows_used = slots.reserved.div_ceil(permits);
+        if now >= slots.window_start + self.window * windows_used as u32 {
+            slots.window_start = now;
+            slots.reserved = 0;
+        }
+        let window_index = slots.reserved / permits;
+        slots.reserved += 1;
+        (slots.window_start + self.window * window_index as u32).max(now)
+    }
+}
@@ -0,0 +1,223 @@
+//! Serial port that reopens on error, for USB adapters that come and go.
+
+use crate::retry::{RetryConfig, RetryExhausted};
+
+use log::{debug, warn};
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::{Async, Future, Poll};
+use tokio::timer::Delay;
+use tokio_serial::{Serial, SerialPortSettings};
+
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Shortest delay between reopen attempts. Opening is sync, without the floor a zero
+/// [RetryConfig::initial_delay] would retry in a busy loop.
+pub const MIN_REOPEN_DELAY: Duration = Duration::from_millis(100);
+
+enum State {
+    Open(Serial),
+    // Delay before next open attempt
+    Waiting(Delay),
+    // Gave up after `RetryConfig::max_attempts`
+    Failed,
+}
+
+/// Serial port that is opened again with the same settings after any IO error.
+///
+/// Reopen attempts are delayed according to [RetryConfig], at least by [MIN_REOPEN_DELAY].
+/// Reads and writes return `WouldBlock` while the port is closed, the error that closed the port
+/// is returned once.
+pub struct RetryingSerial {
+    path: PathBuf,
+    settings: SerialPortSettings,
+    retry_config: RetryConfig,
+    state: State,
+    // failed attempts since port was last opened
+    failed_attempts: u32,
+    // reopens since the port was created
+    reconnect_count: u32,
+}
+
+impl RetryingSerial {
+    /// Open port at `path`. If it can't be opened now it's retried later, the error is only
+    /// logged.
+    pub fn open(path: impl Into<PathBuf>, settings: SerialPortSettings) -> Self {
+        let mut serial = Self {
+            path: path.into(),
+            settings,
+            retry_config: RetryConfig::default(),
+            state: State::Failed,
+            failed_attempts: 0,
+            reconnect_count: 0,
+        };
+        if let Err(err) = serial.try_open() {
+            warn!(
+                "RetryingSerial[{}] => open failed: {}",
+                serial.path.display(),
+                err
+            );
+            serial.reset();
+        }
+        serial
+    }
+
+    /// Delay reopen attempts according to `retry_config`.
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn settings(&self) -> &SerialPortSettings {
+        &self.settings
+    }
+
+    /// Port while it's open
+    pub fn as_serial(&self) -> Option<&Serial> {
+        match &self.state {
+            State::Open(serial) => Some(serial),
+            _ => None,
+        }
+    }
+
+    /// Mutable port while it's open
+    pub fn as_serial_mut(&mut self) -> Option<&mut Serial> {
+        match &mut self.state {
+            State::Open(serial) => Some(serial),
+            _ => None,
+        }
+    }
+
+    /// Reopen attempts since the port was created, including failed ones.
+    pub fn reconnect_count(&self) -> u32 {
+        self.reconnect_count
+    }
+
+    fn try_open(&mut self) -> io::Result<()> {
+        let serial = Serial::from_path(&self.path, &self.settings)?;
+        debug!("RetryingSerial[{}] => opened", self.path.display());
+        self.state = State::Open(serial);
+        self.failed_attempts = 0;
+        Ok(())
+    }
+
+    // Return NotReady until port is open
+    fn poll_serial(&mut self) -> Poll<&mut Serial, io::Error> {
+        loop {
+            match &mut self.state {
+                State::Open(_) => break,
+                State::Waiting(delay) => {
+                    if let Async::NotReady = delay.poll().map_err(io::Error::other)? {
+                        return Ok(Async::NotReady);
+                    }
+                    if let Err(err) = self.try_open() {
+                        warn!(
+                            "RetryingSerial[{}] => reopen failed: {}",
+                            self.path.display(),
+                            err
+                        );
+                        self.reset();
+                    }
+                }
+                State::Failed => {
+                    return Err(RetryExhausted {
+                        attempts: self.failed_attempts,
+                    }
+                    .into())
+                }
+            }
+        }
+
+        match &mut self.state {
+            State::Open(serial) => Ok(Async::Ready(serial)),
+            _ => unreachable!(),
+        }
+    }
+
+    fn reset(&mut self) {
+        if let Some(max_attempts) = self.retry_config.max_attempts {
+            if self.failed_attempts >= max_attempts {
+                warn!(
+                    "RetryingSerial[{}] => giving up after {} reopen attempts",
+                    self.path.display(),
+                    self.failed_attempts
+                );
+                self.state = State::Failed;
+                return;
+            }
+        }
+        let delay = self
+            .retry_config
+            .next_delay(self.failed_attempts)
+            .max(MIN_REOPEN_DELAY);
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+        self.reconnect_count = self.reconnect_count.saturating_add(1);
+        debug!(
+            "RetryingSerial[{}] => reopen in {:?}",
+            self.path.display(),
+            delay
+        );
+        self.state = State::Waiting(Delay::new(Instant::now() + delay));
+    }
+
+    // Close port on error other than `WouldBlock`
+    fn reset_on_error<T>(&mut self, res: io::Result<T>) -> io::Result<T> {
+        match res {
+            Err(err) if err.kind() != io::ErrorKind::WouldBlock => {
+                warn!(
+                    "RetryingSerial[{}] => closing after error: {}",
+                    self.path.display(),
+                    err
+                );
+                self.reset();
+                Err(err)
+            }
+            res => res,
+        }
+    }
+}
+
+impl Read for RetryingSerial {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let res = match self.poll_serial()? {
+            Async::Ready(serial) => serial.read(buf),
+            Async::NotReady => return Err(io::ErrorKind::WouldBlock.into()),
+        };
+        self.reset_on_error(res)
+    }
+}
+
+impl AsyncRead for RetryingSerial {}
+
+impl Write for RetryingSerial {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let res = match self.poll_serial()? {
+            Async::Ready(serial) => serial.write(buf),
+            Async::NotReady => return Err(io::ErrorKind::WouldBlock.into()),
+        };
+        self.reset_on_error(res)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let res = match self.poll_serial()? {
+            Async::Ready(serial) => serial.flush(),
+            Async::NotReady => return Err(io::ErrorKind::WouldBlock.into()),
+        };
+        self.reset_on_error(res)
+    }
+}
+
+impl AsyncWrite for RetryingSerial {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match &mut self.state {
+            State::Open(serial) => serial.shutdown(),
+            // nothing to shut down while closed
+            State::Waiting(_) | State::Failed => Ok(Async::Ready(())),
+        }
+    }
+}
@@ -4,29 +4,545 @@ use std::convert::TryFrom;
 use std::io::Read;
 use std::io::Write;
 
+#[cfg(feature = "history")]
+use crate::history::{ConnectionHistory, HistoryEventKind};
+use crate::resolver::{AddressResolver, ResolveFuture, SharedResolver, SystemResolver};
+use crate::retry::{
+    CircuitBreaker, CircuitOpen, CircuitState, ReconnectGovernor, RetryBudget, RetryConfig,
+    RetryExhausted,
+};
+
+use bytes::BytesMut;
+use futures::task::AtomicTask;
 use futures::try_ready;
 
 use log::{debug, trace, warn};
-use mio;
 use tokio::io::{AsyncRead, AsyncWrite, Error};
-use tokio::prelude::{Async, Future, Poll};
+use tokio::prelude::{future, Async, Future, Poll};
+use tokio::sync::watch;
+use tokio::timer::Delay;
+use uuid::Uuid;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Record event in stream history, no-op without `history` feature
+macro_rules! record {
+    ($stream:expr, $kind:ident, $($detail:tt)*) => {
+        #[cfg(feature = "history")]
+        $stream.history.push(HistoryEventKind::$kind, format!($($detail)*));
+    };
+}
 
 /// Holding settings state between reconnection
-#[derive(Default, Clone)]
+#[derive(Default, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct TcpStreamSettings {
     nodelay: bool,
+    write_buffer_capacity: Option<usize>,
+    write_overflow_policy: OverflowPolicy,
+    warmup_probe: bool,
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_secs::option"))]
+    first_byte_timeout: Option<Duration>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_secs::option"))]
+    connect_timeout: Option<Duration>,
+    #[cfg(target_os = "linux")]
+    tcp_cork_on_connect: bool,
+    keepalive: Option<TcpKeepalive>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_secs::option"))]
+    linger: Option<Duration>,
+    recv_buffer_size: Option<usize>,
+    send_buffer_size: Option<usize>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    resolver: Option<SharedResolver>,
+}
+
+/// `SO_KEEPALIVE` parameters, converted to [socket2::TcpKeepalive] when applied.
+///
+/// `interval` and `retries` are ignored on platforms where socket2 can't set them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TcpKeepalive {
+    /// Idle time before the first probe
+    #[cfg_attr(feature = "serde", serde(with = "crate::duration_secs"))]
+    pub time: Duration,
+    /// Time between probes, system default if `None`
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::duration_secs::option")
+    )]
+    pub interval: Option<Duration>,
+    /// Unanswered probes before connection is dropped, system default if `None`
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub retries: Option<u32>,
+}
+
+impl TcpKeepalive {
+    pub fn new(time: Duration) -> Self {
+        Self {
+            time,
+            interval: None,
+            retries: None,
+        }
+    }
+}
+
+impl From<TcpKeepalive> for socket2::TcpKeepalive {
+    fn from(params: TcpKeepalive) -> Self {
+        #[allow(unused_mut)]
+        let mut keepalive = socket2::TcpKeepalive::new().with_time(params.time);
+        #[cfg(any(
+            windows,
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd"
+        ))]
+        if let Some(interval) = params.interval {
+            keepalive = keepalive.with_interval(interval);
+        }
+        #[cfg(any(
+            target_os = "linux",
+            target_os = "android",
+            target_os = "macos",
+            target_os = "ios",
+            target_os = "freebsd",
+            target_os = "netbsd"
+        ))]
+        if let Some(retries) = params.retries {
+            keepalive = keepalive.with_retries(retries);
+        }
+        keepalive
+    }
+}
+
+impl TcpStreamSettings {
+    /// Set `TCP_NODELAY` applied on every (re)connect.
+    pub fn set_nodelay(&mut self, nodelay: bool) {
+        self.nodelay = nodelay;
+    }
+
+    /// Resolver used by [RetryingTcpStream::connect_host]. `None` means [SystemResolver].
+    pub fn set_resolver(&mut self, resolver: Option<Arc<dyn AddressResolver>>) {
+        self.resolver = resolver.map(SharedResolver);
+    }
+
+    /// Reconnect with `TimedOut` error when no data arrives within `timeout` after (re)connect.
+    pub fn set_first_byte_timeout(&mut self, timeout: Option<Duration>) {
+        self.first_byte_timeout = timeout;
+    }
+
+    /// Fail connect attempt with `TimedOut` when it doesn't finish within `timeout`, the attempt
+    /// counts as failed and stream reconnects. `None` waits until system gives up.
+    pub fn set_connect_timeout(&mut self, timeout: Option<Duration>) {
+        self.connect_timeout = timeout;
+    }
+
+    /// Set `TCP_CORK` on every (re)connect, see [UniConnect::set_cork](crate::UniConnect::set_cork)
+    /// for its interaction with `TCP_NODELAY`.
+    #[cfg(target_os = "linux")]
+    pub fn set_tcp_cork_on_connect(&mut self, corked: bool) {
+        self.tcp_cork_on_connect = corked;
+    }
+
+    /// Enable `SO_KEEPALIVE` with `keepalive` parameters on every (re)connect, `None` leaves
+    /// keep-alive disabled. Only [time](TcpKeepalive::time) is used outside Unix.
+    pub fn set_keepalive(&mut self, keepalive: Option<TcpKeepalive>) {
+        self.keepalive = keepalive;
+    }
+
+    /// Set `SO_LINGER` on every (re)connect, `None` keeps the system default.
+    pub fn set_linger(&mut self, linger: Option<Duration>) {
+        self.linger = linger;
+    }
+
+    /// Set `SO_RCVBUF` on every (re)connect, `None` keeps the system default.
+    pub fn set_recv_buffer_size(&mut self, size: Option<usize>) {
+        self.recv_buffer_size = size;
+    }
+
+    /// Set `SO_SNDBUF` on every (re)connect, `None` keeps the system default.
+    pub fn set_send_buffer_size(&mut self, size: Option<usize>) {
+        self.send_buffer_size = size;
+    }
+
+    /// Probe every (re)connected stream before it is reported ready: peek without consuming data
+    /// to catch a pending socket error (e.g. reset right after accept) or the peer already
+    /// closing the connection. A failing probe triggers an immediate reconnect.
+    pub fn set_warmup_probe(&mut self, warmup_probe: bool) {
+        self.warmup_probe = warmup_probe;
+    }
+
+    /// Queue up to `capacity` bytes written while the stream is (re)connecting instead of
+    /// returning `WouldBlock`. Queued bytes are sent before any new write once connected.
+    /// `None` disables queuing.
+    pub fn set_write_buffer(&mut self, capacity: Option<usize>, policy: OverflowPolicy) {
+        self.write_buffer_capacity = capacity;
+        self.write_overflow_policy = policy;
+    }
+}
+
+impl std::fmt::Display for TcpStreamSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "nodelay={}", self.nodelay)?;
+        match self.write_buffer_capacity {
+            Some(capacity) => write!(
+                f,
+                ", write_buffer={} ({:?})",
+                capacity, self.write_overflow_policy
+            ),
+            None => write!(f, ", write_buffer=off"),
+        }?;
+        if self.warmup_probe {
+            write!(f, ", warmup_probe")?;
+        }
+        if let Some(timeout) = self.first_byte_timeout {
+            write!(f, ", first_byte_timeout={}ms", timeout.as_millis())?;
+        }
+        if let Some(timeout) = self.connect_timeout {
+            write!(f, ", connect_timeout={}ms", timeout.as_millis())?;
+        }
+        #[cfg(target_os = "linux")]
+        if self.tcp_cork_on_connect {
+            write!(f, ", tcp_cork")?;
+        }
+        if let Some(keepalive) = &self.keepalive {
+            write!(f, ", keepalive={}s", keepalive.time.as_secs())?;
+        }
+        if let Some(linger) = self.linger {
+            write!(f, ", linger={}ms", linger.as_millis())?;
+        }
+        if let Some(size) = self.recv_buffer_size {
+            write!(f, ", recv_buffer={}", size)?;
+        }
+        if let Some(size) = self.send_buffer_size {
+            write!(f, ", send_buffer={}", size)?;
+        }
+        Ok(())
+    }
+}
+
+impl TcpStreamSettings {
+    // Set socket options on connected stream
+    pub(crate) fn apply(&self, ts: &tokio::net::TcpStream) -> Result<(), Error> {
+        ts.set_nodelay(self.nodelay)?;
+        #[cfg(target_os = "linux")]
+        crate::sys::set_cork(ts, self.tcp_cork_on_connect)?;
+        apply_socket_options(ts, self)
+    }
+}
+
+// Connect plain TcpStream with connect timeout and socket options of `settings`
+pub(crate) fn connect_with_settings(
+    addr: &SocketAddr,
+    settings: &TcpStreamSettings,
+) -> impl Future<Item = tokio::net::TcpStream, Error = Error> {
+    let settings = settings.clone();
+    connect(addr, None, settings.connect_timeout).and_then(move |ts| {
+        settings.apply(&ts)?;
+        Ok(ts)
+    })
+}
+
+// Socket of `ts` for calls tokio doesn't expose
+#[cfg(unix)]
+fn borrow_fd(ts: &tokio::net::TcpStream) -> std::os::unix::io::BorrowedFd<'_> {
+    // the borrow doesn't outlive `ts`, which owns the socket
+    unsafe { std::os::unix::io::BorrowedFd::borrow_raw(std::os::unix::io::AsRawFd::as_raw_fd(ts)) }
+}
+
+// Read bytes already received by `ts`, regardless of readiness seen by tokio
+#[cfg(unix)]
+fn read_received(ts: &mut tokio::net::TcpStream, buf: &mut [u8]) -> Result<usize, Error> {
+    let fd = borrow_fd(ts);
+    let socket = socket2::SockRef::from(&fd);
+    (&*socket).read(buf)
+}
+
+#[cfg(not(unix))]
+fn read_received(ts: &mut tokio::net::TcpStream, buf: &mut [u8]) -> Result<usize, Error> {
+    ts.read(buf)
+}
+
+// Set socket options of `settings` not exposed by tokio, options left `None` are not touched
+#[cfg(unix)]
+fn apply_socket_options(
+    ts: &tokio::net::TcpStream,
+    settings: &TcpStreamSettings,
+) -> Result<(), Error> {
+    let fd = borrow_fd(ts);
+    let socket = socket2::SockRef::from(&fd);
+
+    if let Some(keepalive) = settings.keepalive {
+        socket.set_tcp_keepalive(&keepalive.into())?;
+    }
+    if let Some(linger) = settings.linger {
+        socket.set_linger(Some(linger))?;
+    }
+    if let Some(size) = settings.recv_buffer_size {
+        socket.set_recv_buffer_size(size)?;
+    }
+    if let Some(size) = settings.send_buffer_size {
+        socket.set_send_buffer_size(size)?;
+    }
+    Ok(())
+}
+
+// tokio 0.1 doesn't expose the socket handle outside Unix, use its own setters. Keep-alive
+// interval and retries can't be set there.
+#[cfg(not(unix))]
+fn apply_socket_options(
+    ts: &tokio::net::TcpStream,
+    settings: &TcpStreamSettings,
+) -> Result<(), Error> {
+    if let Some(keepalive) = settings.keepalive {
+        ts.set_keepalive(Some(keepalive.time))?;
+    }
+    if let Some(linger) = settings.linger {
+        ts.set_linger(Some(linger))?;
+    }
+    if let Some(size) = settings.recv_buffer_size {
+        ts.set_recv_buffer_size(size)?;
+    }
+    if let Some(size) = settings.send_buffer_size {
+        ts.set_send_buffer_size(size)?;
+    }
+    Ok(())
+}
+
+/// What to do with a write that doesn't fit into the write buffer
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum OverflowPolicy {
+    /// Accept only what fits and return `WouldBlock` when the buffer is full
+    #[default]
+    Block,
+    /// Discard the oldest queued bytes to make room
+    Drop,
+}
+
+type ValidateFuture = Box<dyn Future<Item = (tokio::net::TcpStream, bool), Error = Error> + Send>;
+type Validator = Box<dyn Fn(tokio::net::TcpStream) -> ValidateFuture + Send>;
+
+/// Where [RetryingTcpStream] connects to.
+#[derive(Clone, Debug)]
+pub enum PeerAddress {
+    Static(SocketAddr),
+    /// Resolved again on every reconnect
+    Dynamic(DnsRotatingAddr),
+}
+
+impl From<SocketAddr> for PeerAddress {
+    fn from(addr: SocketAddr) -> Self {
+        PeerAddress::Static(addr)
+    }
+}
+
+impl From<DnsRotatingAddr> for PeerAddress {
+    fn from(addr: DnsRotatingAddr) -> Self {
+        PeerAddress::Dynamic(addr)
+    }
+}
+
+/// Hostname resolved on every reconnect.
+///
+/// When the hostname resolves to many addresses, each reconnect uses the one after the address
+/// used previously. When resolution fails the last successfully connected address is used.
+#[derive(Clone)]
+pub struct DnsRotatingAddr {
+    pub hostname: String,
+    pub port: u16,
+    pub resolver: Arc<dyn AddressResolver>,
+}
+
+impl std::fmt::Debug for DnsRotatingAddr {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("DnsRotatingAddr")
+            .field("hostname", &self.hostname)
+            .field("port", &self.port)
+            .finish_non_exhaustive()
+    }
+}
+
+// Address following `previous` in `addrs`, first one if `previous` is not there
+fn next_addr(addrs: &[SocketAddr], previous: Option<SocketAddr>) -> Option<SocketAddr> {
+    let next = previous
+        .and_then(|previous| addrs.iter().position(|addr| *addr == previous))
+        .map_or(0, |index| (index + 1) % addrs.len());
+    addrs.get(next).copied()
+}
+
+#[derive(Default)]
+struct TriggerShared {
+    requested: AtomicBool,
+    task: AtomicTask,
+}
+
+/// Forces [RetryingTcpStream] to drop its connection and reconnect, see
+/// [reconnect_trigger](RetryingTcpStream::reconnect_trigger).
+#[derive(Clone, Default)]
+pub struct ReconnectTrigger {
+    shared: Arc<TriggerShared>,
+}
+
+impl ReconnectTrigger {
+    /// Reconnect on next poll of the stream. Ignored if the stream is not connected.
+    pub fn trigger(&self) {
+        self.shared.requested.store(true, Ordering::SeqCst);
+        self.shared.task.notify();
+    }
+
+    // Return `true` once for every `trigger` call. Must be called inside a task.
+    fn poll_triggered(&self) -> bool {
+        self.shared.task.register();
+        self.shared.requested.swap(false, Ordering::SeqCst)
+    }
+}
+
+impl std::fmt::Debug for ReconnectTrigger {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("ReconnectTrigger")
+            .field("requested", &self.shared.requested.load(Ordering::SeqCst))
+            .finish()
+    }
+}
+
+type SocketFactory = Box<dyn Fn(&SocketAddr) -> Result<socket2::Socket, Error> + Send>;
+type ConnectFuture = Box<dyn Future<Item = tokio::net::TcpStream, Error = Error> + Send>;
+
+// Connect to `addr` using socket created by `factory` if any, fails immediately when socket
+// factory fails
+fn connect(
+    addr: &SocketAddr,
+    factory: Option<&SocketFactory>,
+    timeout: Option<Duration>,
+) -> ConnectFuture {
+    let connect = match factory.map(|factory| factory(addr)) {
+        None => future::Either::A(tokio::net::TcpStream::connect(addr)),
+        Some(Ok(socket)) => future::Either::A(tokio::net::TcpStream::connect_std(
+            socket.into(),
+            addr,
+            &tokio::reactor::Handle::default(),
+        )),
+        Some(Err(err)) => future::Either::B(future::err(err)),
+    };
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return Box::new(connect),
+    };
+    Box::new(
+        tokio::prelude::FutureExt::timeout(connect, timeout).map_err(|err| {
+            if err.is_elapsed() {
+                Error::new(tokio::io::ErrorKind::TimedOut, "connect timed out")
+            } else if err.is_inner() {
+                err.into_inner().expect("inner error")
+            } else {
+                Error::other(err)
+            }
+        }),
+    )
 }
 
 // Handle connection state
 enum ConnectionState {
-    ConnectFuture(tokio::net::tcp::ConnectFuture),
+    // Delay before next connect attempt
+    Waiting(Delay),
+    // Like `Waiting`, but reads and writes fail until cool-down of `CircuitBreaker` elapses
+    CircuitOpen(Delay),
+    Resolving(ResolveFuture),
+    ConnectFuture(ConnectFuture),
+    // Validator owns the stream until it hands it back
+    Validating(ValidateFuture),
     TcpStream(tokio::net::TcpStream),
+    // Gave up after `RetryConfig::max_attempts`
+    Failed,
+    // Shut down by `AsyncWrite::shutdown`, never reconnects
+    Shutdown,
+}
+
+impl ConnectionState {
+    // Resolve peer address if needed and connect
+    fn connecting(
+        peer: &PeerAddress,
+        factory: Option<&SocketFactory>,
+        timeout: Option<Duration>,
+    ) -> Self {
+        match peer {
+            PeerAddress::Static(addr) => {
+                ConnectionState::ConnectFuture(connect(addr, factory, timeout))
+            }
+            PeerAddress::Dynamic(dns) => {
+                ConnectionState::Resolving(dns.resolver.resolve(&dns.hostname, dns.port))
+            }
+        }
+    }
+}
+
+/// Reconnect started by [RetryingTcpStream], see
+/// [with_reconnect_notifier](RetryingTcpStream::with_reconnect_notifier).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReconnectEvent {
+    /// Reconnect attempt since last successful connection, starting at `1`
+    pub attempt: u32,
+    pub at: Instant,
 }
 
+/// State of [RetryingTcpStream] returned by [diagnostics](RetryingTcpStream::diagnostics)
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConnDiagnostics {
+    pub connection_id: Uuid,
+    pub label: Option<String>,
+    /// See [state_description](RetryingTcpStream::state_description)
+    pub state: &'static str,
+    pub state_changed_at: Instant,
+    /// Current peer or the one being connected to
+    pub peer: Option<SocketAddr>,
+    pub queued_write_len: usize,
+}
+
+/// tokio TcpStream that reconnect on error
 pub struct RetryingTcpStream {
-    addr: std::net::SocketAddr,
+    peer: PeerAddress,
+    // address of current or last connection attempt
+    addr: Option<SocketAddr>,
+    // fallback when `PeerAddress::Dynamic` fails to resolve
+    last_connected: Option<SocketAddr>,
     settings: TcpStreamSettings,
     state: ConnectionState,
+    // when `state_description` last changed
+    state_changed_at: Instant,
+    validator: Option<Validator>,
+    socket_factory: Option<SocketFactory>,
+    retry_budget: Option<RetryBudget>,
+    governor: Option<Arc<ReconnectGovernor>>,
+    circuit_breaker: Option<CircuitBreaker>,
+    retry_config: RetryConfig,
+    // failed attempts since last connection, see `RetryConfig`
+    failed_attempts: u32,
+    // reconnects since the stream was created
+    reconnect_count: u32,
+    reconnect_notifier: Option<watch::Sender<ReconnectEvent>>,
+    // bytes written while connecting, see `TcpStreamSettings::set_write_buffer`
+    write_buf: BytesMut,
+    // bytes received by connection left by `migrate_to`, read before new data
+    read_buf: BytesMut,
+    // running until first byte is read, see `TcpStreamSettings::set_first_byte_timeout`
+    first_byte_deadline: Option<Delay>,
+    trigger: ReconnectTrigger,
+    // generated once, kept on reconnect
+    connection_id: Uuid,
+    // set by application, never touched on reconnect
+    label: Option<String>,
+    metadata: HashMap<String, String>,
+    #[cfg(feature = "history")]
+    history: ConnectionHistory,
 }
 
 impl TryFrom<tokio::net::TcpStream> for RetryingTcpStream {
@@ -34,23 +550,90 @@ impl TryFrom<tokio::net::TcpStream> for RetryingTcpStream {
     fn try_from(tcp_stream: tokio::net::TcpStream) -> Result<Self, Self::Error> {
         let settings = TcpStreamSettings {
             nodelay: tcp_stream.nodelay()?,
+            ..Default::default()
         };
 
-        Ok(RetryingTcpStream {
-            addr: tcp_stream.peer_addr()?,
-            state: ConnectionState::TcpStream(tcp_stream),
+        let addr = tcp_stream.peer_addr()?;
+        Ok(Self::new_with_state(
+            PeerAddress::Static(addr),
             settings,
-        })
+            ConnectionState::TcpStream(tcp_stream),
+        ))
     }
 }
 
 /// Implement creators
 impl RetryingTcpStream {
     pub fn connect_with_settings(addr: &std::net::SocketAddr, settings: TcpStreamSettings) -> Self {
+        Self::connect_to(PeerAddress::Static(*addr), settings)
+    }
+
+    /// Connect to `hostname` resolved with [resolver](TcpStreamSettings::set_resolver) on every
+    /// reconnect.
+    pub fn connect_host(
+        hostname: impl Into<String>,
+        port: u16,
+        settings: TcpStreamSettings,
+    ) -> Self {
+        let resolver = match &settings.resolver {
+            Some(resolver) => resolver.0.clone(),
+            None => Arc::new(SystemResolver),
+        };
+        let peer = DnsRotatingAddr {
+            hostname: hostname.into(),
+            port,
+            resolver,
+        };
+        Self::connect_to(peer, settings)
+    }
+
+    /// Connect to static address or hostname resolved again on every reconnect.
+    pub fn connect_to(peer: impl Into<PeerAddress>, settings: TcpStreamSettings) -> Self {
+        let peer = peer.into();
+        let state = ConnectionState::connecting(&peer, None, settings.connect_timeout);
+        Self::new_with_state(peer, settings, state)
+    }
+
+    // Stream in `state` with everything else at defaults. Peer address counts as connected
+    // when `state` is already connected.
+    fn new_with_state(
+        peer: PeerAddress,
+        settings: TcpStreamSettings,
+        state: ConnectionState,
+    ) -> Self {
+        let addr = match &peer {
+            PeerAddress::Static(addr) => Some(*addr),
+            PeerAddress::Dynamic(_) => None,
+        };
+        let last_connected = match state {
+            ConnectionState::TcpStream(_) => addr,
+            _ => None,
+        };
         Self {
-            addr: addr.clone(),
-            state: ConnectionState::ConnectFuture(tokio::net::TcpStream::connect(addr)),
+            state,
+            state_changed_at: Instant::now(),
+            peer,
+            addr,
+            last_connected,
             settings,
+            validator: None,
+            socket_factory: None,
+            retry_budget: None,
+            governor: None,
+            circuit_breaker: None,
+            retry_config: RetryConfig::default(),
+            failed_attempts: 0,
+            reconnect_count: 0,
+            reconnect_notifier: None,
+            write_buf: BytesMut::new(),
+            read_buf: BytesMut::new(),
+            first_byte_deadline: None,
+            trigger: ReconnectTrigger::default(),
+            connection_id: Uuid::new_v4(),
+            label: None,
+            metadata: HashMap::new(),
+            #[cfg(feature = "history")]
+            history: ConnectionHistory::default(),
         }
     }
 
@@ -58,19 +641,127 @@ impl RetryingTcpStream {
         Self::connect_with_settings(addr, Default::default())
     }
 
+    /// Connect to `addr` and send [ReconnectEvent] to `notifier` every time a reconnect starts.
+    /// Watch channel keeps only the latest event, so a slow receiver never blocks reconnecting.
+    pub fn with_reconnect_notifier(
+        addr: &std::net::SocketAddr,
+        notifier: watch::Sender<ReconnectEvent>,
+    ) -> Self {
+        let mut stream = Self::connect(addr);
+        stream.reconnect_notifier = Some(notifier);
+        stream
+    }
+
+    /// Connect to `addr`, reconnects are delayed according to `retry_config`.
+    pub fn connect_with_retry(
+        addr: &std::net::SocketAddr,
+        settings: TcpStreamSettings,
+        retry_config: RetryConfig,
+    ) -> Self {
+        Self::connect_with_settings(addr, settings).with_retry_config(retry_config)
+    }
+
     pub fn from_std(
         stream: std::net::TcpStream,
         handle: &tokio::reactor::Handle,
     ) -> Result<Self, Error> {
         let settings = TcpStreamSettings {
             nodelay: stream.nodelay()?,
+            ..Default::default()
         };
 
-        Ok(Self {
-            addr: stream.peer_addr()?,
-            state: ConnectionState::TcpStream(tokio::net::TcpStream::from_std(stream, handle)?),
+        let addr = stream.peer_addr()?;
+        let stream = tokio::net::TcpStream::from_std(stream, handle)?;
+        Ok(Self::new_with_state(
+            PeerAddress::Static(addr),
             settings,
-        })
+            ConnectionState::TcpStream(stream),
+        ))
+    }
+
+    /// Validate every (re)connected stream before it is used.
+    ///
+    /// `f` takes each newly connected stream, the future it returns is polled until it hands
+    /// the stream back with the verdict. If it is `false` the connection is dropped and a
+    /// reconnect is started immediately. Useful for verifying server identity or checking a
+    /// version banner, e.g. with [tokio::io::read_exact].
+    ///
+    /// ```no_run
+    /// use tokio::prelude::Future;
+    /// use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+    ///
+    /// let addr = "127.0.0.1:2000".parse().unwrap();
+    /// let stream = RetryingTcpStream::connect(&addr).with_validator(|ts| {
+    ///     tokio::io::read_exact(ts, [0u8; 4]).map(|(ts, banner)| (ts, &banner == b"OK\r\n"))
+    /// });
+    /// ```
+    pub fn with_validator<F, Fut>(mut self, f: F) -> Self
+    where
+        F: Fn(tokio::net::TcpStream) -> Fut + Send + 'static,
+        Fut: Future<Item = (tokio::net::TcpStream, bool), Error = Error> + Send + 'static,
+    {
+        self.validator = Some(Box::new(move |ts| Box::new(f(ts))));
+        self
+    }
+
+    /// Connect using `socket` configured by caller, e.g. with `SO_BINDTODEVICE` or
+    /// `SO_REUSEADDR`. Resolves once connected. Reconnects use plain sockets unless
+    /// [with_socket_factory](Self::with_socket_factory) is set.
+    pub fn connect_from_socket(
+        socket: socket2::Socket,
+        addr: SocketAddr,
+    ) -> impl Future<Item = Self, Error = Error> {
+        tokio::net::TcpStream::connect_std(socket.into(), &addr, &tokio::reactor::Handle::default())
+            .and_then(Self::try_from)
+    }
+
+    /// Create socket for every (re)connect with `f`, so options that must be set before
+    /// `connect()` are kept. `f` gets the address the socket will connect to. Connection attempt
+    /// in progress is restarted with socket from `f`.
+    pub fn with_socket_factory<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&SocketAddr) -> Result<socket2::Socket, Error> + Send + 'static,
+    {
+        self.socket_factory = Some(Box::new(f));
+        if let ConnectionState::ConnectFuture(_) = self.state {
+            self.start_connect();
+        }
+        self
+    }
+
+    /// Take a token from `budget` on every reconnect. Reconnect is delayed by
+    /// [penalty](RetryBudget::penalty) when budget is exhausted. Share one budget between
+    /// streams connecting to the same backend to prevent reconnect storms.
+    pub fn with_retry_budget(mut self, budget: RetryBudget) -> Self {
+        self.retry_budget = Some(budget);
+        self
+    }
+
+    /// Schedule every reconnect through `governor`, e.g. [ReconnectGovernor::global], so that
+    /// streams sharing it don't reconnect all at once. Applied after retry budget.
+    pub fn with_governor(mut self, governor: Arc<ReconnectGovernor>) -> Self {
+        self.governor = Some(governor);
+        self
+    }
+
+    /// Stop reconnecting for a cool-down after too many failures, see [CircuitBreaker]. While
+    /// the circuit is open reads and writes fail immediately with [CircuitOpen].
+    pub fn with_circuit_breaker(mut self, circuit_breaker: CircuitBreaker) -> Self {
+        self.circuit_breaker = Some(circuit_breaker);
+        self
+    }
+
+    /// State of [CircuitBreaker], always `Closed` without one.
+    pub fn circuit_state(&self) -> CircuitState {
+        self.circuit_breaker
+            .as_ref()
+            .map_or(CircuitState::Closed, CircuitBreaker::state)
+    }
+
+    /// Delay reconnects with exponential backoff, see [RetryConfig].
+    pub fn with_retry_config(mut self, retry_config: RetryConfig) -> Self {
+        self.retry_config = retry_config;
+        self
     }
 }
 
@@ -90,23 +781,41 @@ impl RetryingTcpStream {
 
     pub fn local_addr(&self) -> Result<std::net::SocketAddr, Error> {
         match &self.state {
-            ConnectionState::ConnectFuture(_) => {
-                Err(Error::from(tokio::io::ErrorKind::NotConnected))
-            }
+            ConnectionState::Waiting(_)
+            | ConnectionState::Resolving(_)
+            | ConnectionState::ConnectFuture(_)
+            | ConnectionState::Validating(..)
+            | ConnectionState::CircuitOpen(_)
+            | ConnectionState::Failed
+            | ConnectionState::Shutdown => Err(Error::from(tokio::io::ErrorKind::NotConnected)),
             ConnectionState::TcpStream(ts) => ts.local_addr(),
         }
     }
 
     pub fn peer_addr(&self) -> Result<std::net::SocketAddr, Error> {
         match &self.state {
-            ConnectionState::ConnectFuture(_) => Ok(self.addr),
+            ConnectionState::Waiting(_)
+            | ConnectionState::Resolving(_)
+            | ConnectionState::ConnectFuture(_)
+            | ConnectionState::Validating(..)
+            | ConnectionState::CircuitOpen(_)
+            | ConnectionState::Failed
+            | ConnectionState::Shutdown => self
+                .addr
+                .ok_or_else(|| Error::from(tokio::io::ErrorKind::NotConnected)),
             ConnectionState::TcpStream(ts) => ts.peer_addr(),
         }
     }
 
     pub fn set_nodelay(&mut self, nodelay: bool) -> Result<(), Error> {
         match &self.state {
-            ConnectionState::ConnectFuture(_) => {
+            ConnectionState::Waiting(_)
+            | ConnectionState::Resolving(_)
+            | ConnectionState::ConnectFuture(_)
+            | ConnectionState::Validating(..)
+            | ConnectionState::CircuitOpen(_)
+            | ConnectionState::Failed
+            | ConnectionState::Shutdown => {
                 self.settings.nodelay = nodelay;
                 Ok(())
             }
@@ -122,8 +831,196 @@ impl RetryingTcpStream {
 }
 
 impl RetryingTcpStream {
+    /// Underlying stream if connected, `None` while (re)connecting.
+    pub fn as_tcp_stream(&self) -> Option<&tokio::net::TcpStream> {
+        match &self.state {
+            ConnectionState::TcpStream(ts) => Some(ts),
+            _ => None,
+        }
+    }
+
+    /// Mutable underlying stream if connected, `None` while (re)connecting.
+    ///
+    /// # Note
+    /// Options changed directly on the stream may conflict with [TcpStreamSettings] and are lost
+    /// on reconnect, while settings are applied again to every new connection.
+    pub fn as_tcp_stream_mut(&mut self) -> Option<&mut tokio::net::TcpStream> {
+        match &mut self.state {
+            ConnectionState::TcpStream(ts) => Some(ts),
+            _ => None,
+        }
+    }
+
+    /// Set `TCP_CORK` on current connection, see
+    /// [UniConnect::set_cork](crate::UniConnect::set_cork). Use
+    /// [set_tcp_cork_on_connect](TcpStreamSettings::set_tcp_cork_on_connect) to keep it after
+    /// reconnect.
+    #[cfg(target_os = "linux")]
+    pub fn set_cork(&self, corked: bool) -> Result<(), Error> {
+        match self.as_tcp_stream() {
+            Some(ts) => crate::sys::set_cork(ts, corked),
+            None => Err(Error::from(tokio::io::ErrorKind::NotConnected)),
+        }
+    }
+
+    /// Shut down the write half of current connection without reconnecting, so the rest of data
+    /// can still be read. Returns `NotConnected` while (re)connecting.
+    pub fn shutdown_write(&mut self) -> Result<(), Error> {
+        match self.as_tcp_stream() {
+            Some(ts) => tokio::net::TcpStream::shutdown(ts, std::net::Shutdown::Write),
+            None => Err(Error::from(tokio::io::ErrorKind::NotConnected)),
+        }
+    }
+
+    /// Handle forcing a reconnect from other task, e.g. after configuration change.
+    pub fn reconnect_trigger(&self) -> ReconnectTrigger {
+        self.trigger.clone()
+    }
+
+    /// Drop current connection and reconnect right away. Does nothing while (re)connecting.
+    pub fn force_reconnect(&mut self) {
+        if let ConnectionState::TcpStream(_) = self.state {
+            debug!(
+                "RetryingTcpStream[{}] => forced reconnect",
+                self.connection_id
+            );
+            self.reset();
+        }
+    }
+
+    /// Random ID generated when the stream is created, included in every log line of the stream.
+    /// Stays the same on reconnect.
+    pub fn connection_id(&self) -> Uuid {
+        self.connection_id
+    }
+
+    /// Current state for monitoring:
+    /// * `"Connecting"` -- first connection attempt
+    /// * `"Connected"`
+    /// * `"Reconnecting"` -- connecting again after connection was lost
+    /// * `"WaitingToRetry"` -- reconnect delayed by [RetryConfig], [RetryBudget] or
+    ///   [ReconnectGovernor]
+    /// * `"CircuitOpen"` -- reconnects stopped by [CircuitBreaker]
+    /// * `"Shutdown"` -- shut down by `AsyncWrite::shutdown`, doesn't reconnect
+    /// * `"Failed"` -- gave up after [max_attempts](RetryConfig::max_attempts)
+    pub fn state_description(&self) -> &'static str {
+        match &self.state {
+            ConnectionState::TcpStream(_) => "Connected",
+            ConnectionState::Waiting(_) => "WaitingToRetry",
+            ConnectionState::CircuitOpen(_) => "CircuitOpen",
+            ConnectionState::Failed => "Failed",
+            ConnectionState::Shutdown => "Shutdown",
+            ConnectionState::Resolving(_)
+            | ConnectionState::ConnectFuture(_)
+            | ConnectionState::Validating(..) => {
+                if self.last_connected.is_some() {
+                    "Reconnecting"
+                } else {
+                    "Connecting"
+                }
+            }
+        }
+    }
+
+    /// `true` only while connected, without polling the connection.
+    pub fn is_connected(&self) -> bool {
+        matches!(self.state, ConnectionState::TcpStream(_))
+    }
+
+    /// Reconnects started since the stream was created, including failed ones.
+    pub fn reconnect_count(&self) -> u32 {
+        self.reconnect_count
+    }
+
+    /// When [state_description](Self::state_description) last changed
+    pub fn state_changed_at(&self) -> Instant {
+        self.state_changed_at
+    }
+
+    /// Snapshot of the stream for monitoring
+    pub fn diagnostics(&self) -> ConnDiagnostics {
+        ConnDiagnostics {
+            connection_id: self.connection_id,
+            label: self.label.clone(),
+            state: self.state_description(),
+            state_changed_at: self.state_changed_at,
+            peer: self.peer_addr().ok(),
+            queued_write_len: self.write_buf.len(),
+        }
+    }
+
+    /// Application defined name of the stream, e.g. device ID. Label and
+    /// [metadata](Self::metadata) belong to the stream rather than to a connection, they are kept
+    /// on every reconnect.
+    pub fn set_label<L: Into<String>>(&mut self, label: L) {
+        self.label = Some(label.into());
+    }
+
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Attach `value` under `key`, returns previous value. Kept on reconnect like
+    /// [label](Self::set_label).
+    pub fn set_metadata<K: Into<String>, V: Into<String>>(
+        &mut self,
+        key: K,
+        value: V,
+    ) -> Option<String> {
+        self.metadata.insert(key.into(), value.into())
+    }
+
+    pub fn remove_metadata(&mut self, key: &str) -> Option<String> {
+        self.metadata.remove(key)
+    }
+
+    pub fn metadata(&self) -> &HashMap<String, String> {
+        &self.metadata
+    }
+
+    /// Recent connection events
+    #[cfg(feature = "history")]
+    pub fn history(&self) -> &ConnectionHistory {
+        &self.history
+    }
+
+    #[cfg(feature = "history")]
+    pub fn clear_history(&mut self) {
+        self.history.clear()
+    }
+
+    /// Keep up to `max_len` recent events, see [history](Self::history).
+    #[cfg(feature = "history")]
+    pub fn with_history_len(mut self, max_len: usize) -> Self {
+        self.history = ConnectionHistory::new(max_len);
+        self
+    }
+
+    /// Number of bytes written while connecting that are not sent yet, see
+    /// [set_write_buffer](TcpStreamSettings::set_write_buffer).
+    pub fn queued_write_len(&self) -> usize {
+        self.write_buf.len()
+    }
+
+    /// Move stream to `new_addr`.
+    ///
+    /// Writes pending on the current connection are flushed for up to `grace_period`, then the
+    /// stream reconnects to `new_addr`. Bytes queued while not connected are sent to the new
+    /// peer. Reads and writes continue on the new connection once the returned future completes.
+    /// Data the old connection received up to the reconnect is kept and read first, data its
+    /// peer sends later is lost.
+    pub fn migrate_to(&mut self, new_addr: SocketAddr, grace_period: Duration) -> Migrate<'_> {
+        Migrate {
+            stream: self,
+            new_addr,
+            grace: Delay::new(Instant::now() + grace_period),
+        }
+    }
+
     pub fn set_tcp_settings(&mut self, tcp_settings: TcpStreamSettings) -> Result<(), Error> {
-        self.set_nodelay(tcp_settings.nodelay)?;
+        if let Some(ts) = self.as_tcp_stream() {
+            tcp_settings.apply(ts)?;
+        }
 
         self.settings = tcp_settings;
         Ok(())
@@ -131,45 +1028,430 @@ impl RetryingTcpStream {
 
     // Return NotReady until ConnectionState is diffrent than TcpStream
     fn poll_into_tcp_stream(&mut self) -> Poll<&mut tokio::net::TcpStream, Error> {
-        match &mut self.state {
-            ConnectionState::ConnectFuture(cf) => {
-                let tcp_s = match cf.poll() {
-                    Ok(Async::Ready(tcp_s)) => tcp_s,
+        if self.trigger.poll_triggered() {
+            if let ConnectionState::TcpStream(_) = self.state {
+                debug!(
+                    "RetryingTcpStream[{}] => reconnect triggered",
+                    self.connection_id
+                );
+                self.reset();
+            }
+        }
+
+        loop {
+            match &mut self.state {
+                ConnectionState::Waiting(delay) => match delay.poll() {
+                    Ok(Async::Ready(())) => {
+                        self.start_connect();
+                        debug!(
+                            "RetryingTcpStream[{}] => leave state Waiting",
+                            self.connection_id
+                        )
+                    }
                     Ok(Async::NotReady) => return Ok(Async::NotReady),
-                    Err(err) => {
+                    Err(err) => return Err(Error::other(err)),
+                },
+                ConnectionState::CircuitOpen(delay) => {
+                    // timer may fire a bit after the cool-down ends
+                    let cooled_down = self
+                        .circuit_breaker
+                        .as_ref()
+                        .is_some_and(|breaker| breaker.state() != CircuitState::Open);
+                    match delay.poll() {
+                        Ok(Async::NotReady) if !cooled_down => {
+                            return Err(CircuitOpen {
+                                until: delay.deadline(),
+                            }
+                            .into())
+                        }
+                        Err(err) if !cooled_down => return Err(Error::other(err)),
+                        _ => {
+                            debug!(
+                                "RetryingTcpStream[{}] => circuit half-open, probing",
+                                self.connection_id
+                            );
+                            self.start_connect();
+                        }
+                    }
+                }
+                ConnectionState::Resolving(resolving) => {
+                    let resolved = match resolving.poll() {
+                        Ok(Async::Ready(addrs)) => next_addr(&addrs, self.addr).ok_or_else(|| {
+                            Error::new(tokio::io::ErrorKind::NotFound, "hostname has no addresses")
+                        }),
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                        Err(err) => Err(err),
+                    };
+                    let addr = match (resolved, self.last_connected) {
+                        (Ok(addr), _) => addr,
+                        (Err(err), Some(fallback)) => {
+                            warn!(
+                                "RetryingTcpStream[{}] => resolving failed: {}, using {}",
+                                self.connection_id, err, fallback
+                            );
+                            fallback
+                        }
+                        (Err(err), None) => {
+                            return Err(self.reset_on_error(err));
+                        }
+                    };
+                    self.addr = Some(addr);
+                    self.set_state(ConnectionState::ConnectFuture(connect(
+                        &addr,
+                        self.socket_factory.as_ref(),
+                        self.settings.connect_timeout,
+                    )));
+                    debug!(
+                        "RetryingTcpStream[{}] => change state Resolving -> ConnectFuture",
+                        self.connection_id
+                    )
+                }
+                ConnectionState::ConnectFuture(cf) => {
+                    let tcp_s = match cf.poll() {
+                        Ok(Async::Ready(tcp_s)) => tcp_s,
+                        Ok(Async::NotReady) => return Ok(Async::NotReady),
+                        Err(err) => {
+                            return Err(self.reset_on_error(err));
+                        }
+                    };
+                    match &self.validator {
+                        Some(validator) => {
+                            let validation = validator(tcp_s);
+                            self.set_state(ConnectionState::Validating(validation));
+                            debug!(
+                                "RetryingTcpStream[{}] => change state ConnectFuture -> Validating",
+                                self.connection_id
+                            )
+                        }
+                        None => {
+                            self.set_connected(tcp_s)?;
+                            debug!(
+                                "RetryingTcpStream[{}] => change state ConnectFuture -> TcpStream",
+                                self.connection_id
+                            )
+                        }
+                    }
+                }
+                ConnectionState::Validating(validation) => match validation.poll() {
+                    Ok(Async::Ready((tcp_s, true))) => {
+                        self.set_connected(tcp_s)?;
+                        debug!(
+                            "RetryingTcpStream[{}] => change state Validating -> TcpStream",
+                            self.connection_id
+                        )
+                    }
+                    Ok(Async::Ready((_, false))) => {
+                        warn!(
+                            "RetryingTcpStream[{}] => connection rejected by validator",
+                            self.connection_id
+                        );
+                        record!(self, Error, "connection rejected by validator");
                         self.reset();
-                        return Err(err);
                     }
-                };
-                self.state = ConnectionState::TcpStream(tcp_s);
-                self.set_tcp_settings(self.settings.clone())?;
-                debug!("RetryingTcpStream => change state ConnectFuture -> TcpStream")
-            }
-            ConnectionState::TcpStream(_) => (),
-        };
+                    Ok(Async::NotReady) => return Ok(Async::NotReady),
+                    Err(err) => {
+                        return Err(self.reset_on_error(err));
+                    }
+                },
+                ConnectionState::TcpStream(_) => break,
+                ConnectionState::Failed => {
+                    return Err(RetryExhausted {
+                        attempts: self.failed_attempts,
+                    }
+                    .into())
+                }
+                ConnectionState::Shutdown => {
+                    return Err(Error::new(
+                        tokio::io::ErrorKind::BrokenPipe,
+                        "stream was shut down",
+                    ))
+                }
+            };
+        }
 
         match self.state {
-            ConnectionState::ConnectFuture(_) => unreachable!(),
             ConnectionState::TcpStream(ref mut ts) => Ok(Async::Ready(ts)),
+            _ => unreachable!(),
+        }
+    }
+
+    // Move into `TcpStream` state, apply settings and send warm-up probe
+    fn set_connected(&mut self, mut tcp_s: tokio::net::TcpStream) -> Result<(), Error> {
+        if self.settings.warmup_probe {
+            if let Err(err) = Self::warmup_probe(&mut tcp_s) {
+                // like rejected validation, reconnect before the caller sees the error
+                warn!(
+                    "RetryingTcpStream[{}] => warm-up probe failed: {}",
+                    self.connection_id, err
+                );
+                record!(self, Error, "warm-up probe failed: {}", err);
+                self.reset();
+                return Ok(());
+            }
+        }
+        self.set_state(ConnectionState::TcpStream(tcp_s));
+        self.last_connected = self.addr;
+        self.failed_attempts = 0;
+        if let Some(circuit_breaker) = &mut self.circuit_breaker {
+            circuit_breaker.record_success();
+        }
+        self.first_byte_deadline = self
+            .settings
+            .first_byte_timeout
+            .map(|timeout| Delay::new(Instant::now() + timeout));
+        record!(self, Connected, "{}", self.peer_name());
+        self.set_tcp_settings(self.settings.clone())
+    }
+
+    // Peek the socket itself, tokio reports no readiness before the reactor polled it
+    #[cfg(unix)]
+    fn warmup_probe(tcp_s: &mut tokio::net::TcpStream) -> Result<(), Error> {
+        let fd = borrow_fd(tcp_s);
+        let socket = socket2::SockRef::from(&fd);
+        if let Some(err) = socket.take_error()? {
+            return Err(err);
+        }
+        match socket.peek(&mut [std::mem::MaybeUninit::uninit(); 1]) {
+            Ok(0) => Err(Error::new(
+                tokio::io::ErrorKind::ConnectionAborted,
+                "peer closed connection during warm-up",
+            )),
+            Ok(_) => Ok(()),
+            // nothing to read yet is healthy
+            Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => Ok(()),
+            Err(err) => Err(err),
         }
     }
 
+    // tokio 0.1 doesn't expose the socket handle outside Unix
+    #[cfg(not(unix))]
+    fn warmup_probe(tcp_s: &mut tokio::net::TcpStream) -> Result<(), Error> {
+        match tcp_s.poll_peek(&mut [0u8; 1])? {
+            Async::Ready(0) => Err(Error::new(
+                tokio::io::ErrorKind::ConnectionAborted,
+                "peer closed connection during warm-up",
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Move bytes current connection already received into `read_buf`
+    fn keep_received(&mut self) {
+        let ts = match &mut self.state {
+            ConnectionState::TcpStream(ts) => ts,
+            _ => return,
+        };
+        let mut chunk = [0u8; 4096];
+        loop {
+            match read_received(ts, &mut chunk) {
+                Ok(0) => break,
+                Ok(n) => self.read_buf.extend_from_slice(&chunk[..n]),
+                Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => break,
+                Err(err) => {
+                    debug!(
+                        "RetryingTcpStream[{}] => read before migration failed: {}",
+                        self.connection_id, err
+                    );
+                    break;
+                }
+            }
+        }
+    }
+
+    // Return NotReady until all bytes queued while connecting are written
+    fn poll_drain_write_buf(&mut self) -> Poll<(), Error> {
+        try_ready!(self.poll_into_tcp_stream());
+        let ts = match &mut self.state {
+            ConnectionState::TcpStream(ts) => ts,
+            _ => unreachable!(),
+        };
+
+        while !self.write_buf.is_empty() {
+            match ts.write(&self.write_buf) {
+                Ok(0) => return Err(std::io::ErrorKind::WriteZero.into()),
+                Ok(n) => {
+                    self.write_buf.split_to(n);
+                }
+                Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                    return Ok(Async::NotReady)
+                }
+                Err(err) => {
+                    return Err(self.reset_on_error(err));
+                }
+            }
+        }
+        Ok(Async::Ready(()))
+    }
+
+    // Queue `buf` according to write buffer settings
+    fn queue_write(&mut self, buf: &[u8]) -> Result<usize, Error> {
+        let capacity = match self.settings.write_buffer_capacity {
+            Some(capacity) => capacity,
+            None => return Err(std::io::ErrorKind::WouldBlock.into()),
+        };
+
+        match self.settings.write_overflow_policy {
+            OverflowPolicy::Block => {
+                let free = capacity.saturating_sub(self.write_buf.len());
+                if free == 0 {
+                    return Err(std::io::ErrorKind::WouldBlock.into());
+                }
+                let n = free.min(buf.len());
+                self.write_buf.extend_from_slice(&buf[..n]);
+                Ok(n)
+            }
+            OverflowPolicy::Drop => {
+                let kept = &buf[buf.len().saturating_sub(capacity)..];
+                let overflow = (self.write_buf.len() + kept.len()).saturating_sub(capacity);
+                self.write_buf.split_to(overflow);
+                self.write_buf.extend_from_slice(kept);
+
+                let dropped = overflow + buf.len() - kept.len();
+                if dropped > 0 {
+                    warn!(
+                        "RetryingTcpStream[{}] => write buffer full, dropped {} bytes",
+                        self.connection_id, dropped
+                    );
+                }
+                Ok(buf.len())
+            }
+        }
+    }
+
+    // Reset after `err` and return it back
+    fn reset_on_error(&mut self, err: Error) -> Error {
+        record!(self, Error, "{}", err);
+        self.reset();
+        err
+    }
+
     fn reset(&mut self) {
-        warn!("RetryinTcpStream => reset was called!");
-        self.state = ConnectionState::ConnectFuture(tokio::net::TcpStream::connect(&self.addr))
+        warn!(
+            "RetryingTcpStream[{}] => reset was called!",
+            self.connection_id
+        );
+        self.first_byte_deadline = None;
+        if let ConnectionState::TcpStream(_) = self.state {
+            record!(self, Disconnected, "{}", self.peer_name());
+        }
+        if let Some(max_attempts) = self.retry_config.max_attempts {
+            if self.failed_attempts >= max_attempts {
+                warn!(
+                    "RetryingTcpStream[{}] => giving up after {} reconnect attempts",
+                    self.connection_id, self.failed_attempts
+                );
+                record!(
+                    self,
+                    Error,
+                    "gave up after {} attempts",
+                    self.failed_attempts
+                );
+                self.set_state(ConnectionState::Failed);
+                return;
+            }
+        }
+        record!(self, Reconnecting, "{}", self.peer_name());
+        self.reconnect_count = self.reconnect_count.saturating_add(1);
+        let mut delay = self.retry_config.next_delay(self.failed_attempts);
+        self.failed_attempts = self.failed_attempts.saturating_add(1);
+        if let Some(notifier) = &mut self.reconnect_notifier {
+            // nobody listening is fine
+            let _ = notifier.broadcast(ReconnectEvent {
+                attempt: self.failed_attempts,
+                at: Instant::now(),
+            });
+        }
+        match &self.retry_budget {
+            Some(budget) if !budget.try_acquire() => {
+                let penalty = budget.penalty();
+                warn!(
+                    "RetryingTcpStream[{}] => retry budget exhausted, reconnect in {:?}",
+                    self.connection_id, penalty
+                );
+                delay = delay.max(penalty);
+            }
+            _ => {}
+        }
+
+        let mut start = Instant::now() + delay;
+        if let Some(circuit_breaker) = &mut self.circuit_breaker {
+            if circuit_breaker.record_failure() {
+                let until = circuit_breaker.open_until().expect("circuit is open");
+                warn!(
+                    "RetryingTcpStream[{}] => circuit breaker open for {:?}",
+                    self.connection_id,
+                    until.saturating_duration_since(Instant::now())
+                );
+                record!(self, Error, "circuit breaker open");
+                self.set_state(ConnectionState::CircuitOpen(Delay::new(start.max(until))));
+                return;
+            }
+        }
+        if let Some(governor) = &self.governor {
+            start = start.max(governor.reserve());
+        }
+        let now = Instant::now();
+        if start > now {
+            debug!(
+                "RetryingTcpStream[{}] => reconnect in {:?}",
+                self.connection_id,
+                start - now
+            );
+            self.set_state(ConnectionState::Waiting(Delay::new(start)))
+        } else {
+            self.start_connect()
+        }
+    }
+
+    // Peer for history entries, resolved address included when known
+    #[cfg(feature = "history")]
+    fn peer_name(&self) -> String {
+        match (&self.peer, self.addr) {
+            (PeerAddress::Static(addr), _) => addr.to_string(),
+            (PeerAddress::Dynamic(dns), Some(addr)) => {
+                format!("{}:{} ({})", dns.hostname, dns.port, addr)
+            }
+            (PeerAddress::Dynamic(dns), None) => format!("{}:{}", dns.hostname, dns.port),
+        }
+    }
+
+    // Connect to `new_addr` without taking retry budget
+    fn reconnect_to(&mut self, new_addr: SocketAddr) {
+        if let ConnectionState::TcpStream(_) = self.state {
+            record!(self, Disconnected, "{}", self.peer_name());
+        }
+        self.peer = PeerAddress::Static(new_addr);
+        self.addr = Some(new_addr);
+        self.first_byte_deadline = None;
+        record!(self, Reconnecting, "{}", self.peer_name());
+        self.start_connect();
+    }
+
+    fn set_state(&mut self, state: ConnectionState) {
+        let before = self.state_description();
+        self.state = state;
+        if self.state_description() != before {
+            self.state_changed_at = Instant::now();
+        }
+    }
+
+    fn start_connect(&mut self) {
+        let state = ConnectionState::connecting(
+            &self.peer,
+            self.socket_factory.as_ref(),
+            self.settings.connect_timeout,
+        );
+        self.set_state(state)
     }
 
     fn call_reset_if_io_is_closed2<T>(&mut self, res: Result<T, Error>) -> Result<T, Error> {
         use tokio::io::ErrorKind;
         match res {
             Ok(ok) => Ok(ok),
-            Err(err) => {
-                match err.kind() {
-                    ErrorKind::WouldBlock => (),
-                    _ => self.reset(),
-                };
-                Err(err)
-            }
+            Err(err) => match err.kind() {
+                ErrorKind::WouldBlock => Err(err),
+                _ => Err(self.reset_on_error(err)),
+            },
         }
     }
 }
@@ -177,37 +1459,74 @@ impl RetryingTcpStream {
 impl Read for RetryingTcpStream {
     /// # Note
     /// This is Async version of Read. It will panic outside of tash
+    ///
+    /// Bytes taken from the socket are always copied to `buf` before returning, so dropping
+    /// the future that polls this read (e.g. on timeout) never loses data. Data kept by
+    /// [migrate_to](RetryingTcpStream::migrate_to) is returned first.
     fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
-        trace!("RetryingTcpStream::read called");
+        trace!("RetryingTcpStream[{}]::read called", self.connection_id);
+        if !self.read_buf.is_empty() {
+            let n = buf.len().min(self.read_buf.len());
+            buf[..n].copy_from_slice(&self.read_buf.split_to(n));
+            return Ok(n);
+        }
 
         let ts = self.poll_into_tcp_stream()?;
         let r = match ts {
             Async::Ready(ts) => ts.read(buf),
-            Async::NotReady => Err(std::io::ErrorKind::WouldBlock.into()),
+            Async::NotReady => return Err(std::io::ErrorKind::WouldBlock.into()),
         };
 
+        let timed_out = match (&r, &mut self.first_byte_deadline) {
+            (Ok(n), Some(_)) if *n > 0 => {
+                self.first_byte_deadline = None;
+                false
+            }
+            (Err(err), Some(deadline)) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                deadline.poll().map_err(Error::other)?.is_ready()
+            }
+            _ => false,
+        };
+        if timed_out {
+            warn!(
+                "RetryingTcpStream[{}] => no data received before first byte timeout",
+                self.connection_id
+            );
+            let err = Error::new(std::io::ErrorKind::TimedOut, "first byte timeout");
+            return Err(self.reset_on_error(err));
+        }
+
         self.call_reset_if_io_is_closed2(r)
     }
 }
 
 impl Write for RetryingTcpStream {
     fn write(&mut self, buf: &[u8]) -> Result<usize, Error> {
-        trace!("RetryingTcpStream::write called");
-        let ts = self.poll_into_tcp_stream()?;
-        let r = match ts {
-            Async::Ready(ts) => ts.write(buf),
-            Async::NotReady => Err(std::io::ErrorKind::WouldBlock.into()),
+        trace!("RetryingTcpStream[{}]::write called", self.connection_id);
+        let r = match self.poll_drain_write_buf() {
+            Ok(Async::Ready(())) => match &mut self.state {
+                ConnectionState::TcpStream(ts) => ts.write(buf),
+                _ => unreachable!(),
+            },
+            Ok(Async::NotReady) => match self.state {
+                ConnectionState::TcpStream(_) => Err(std::io::ErrorKind::WouldBlock.into()),
+                _ => return self.queue_write(buf),
+            },
+            Err(err) => return Err(err),
         };
 
         self.call_reset_if_io_is_closed2(r)
     }
 
     fn flush(&mut self) -> Result<(), Error> {
-        trace!("RetryingTcpStream::flush called");
-        let ts = self.poll_into_tcp_stream()?;
-        let r = match ts {
-            Async::Ready(ts) => ts.flush(),
-            Async::NotReady => Err(std::io::ErrorKind::WouldBlock.into()),
+        trace!("RetryingTcpStream[{}]::flush called", self.connection_id);
+        let r = match self.poll_drain_write_buf() {
+            Ok(Async::Ready(())) => match &mut self.state {
+                ConnectionState::TcpStream(ts) => ts.flush(),
+                _ => unreachable!(),
+            },
+            Ok(Async::NotReady) => Err(std::io::ErrorKind::WouldBlock.into()),
+            Err(err) => return Err(err),
         };
 
         self.call_reset_if_io_is_closed2(r)
@@ -219,15 +1538,84 @@ impl Write for RetryingTcpStream {
 // source: https://docs.rs/tokio-io/0.1.12/src/tokio_io/async_write.rs.html#149
 impl AsyncRead for RetryingTcpStream {}
 
+/// Shutdown is final: once done the stream doesn't reconnect and reads and writes return
+/// `BrokenPipe`, including reads of data the peer sends after the shutdown. Use
+/// [shutdown_write](RetryingTcpStream::shutdown_write) to keep reading.
+///
+/// Shutdown of connected stream first sends bytes queued by
+/// [set_write_buffer](TcpStreamSettings::set_write_buffer) and flushes. Shutdown while
+/// (re)connecting cancels the attempt and drops queued bytes.
 impl AsyncWrite for RetryingTcpStream {
     fn shutdown(&mut self) -> Poll<(), Error> {
         match &mut self.state {
-            ConnectionState::ConnectFuture(_cf) => {
-                // there is a chance when we call poll conection will resolve to TcpStream
-                // we probably need add a Shutdowned state.
-                unimplemented!();
+            ConnectionState::Waiting(_)
+            | ConnectionState::CircuitOpen(_)
+            | ConnectionState::Resolving(_)
+            | ConnectionState::ConnectFuture(_)
+            | ConnectionState::Validating(..) => {
+                debug!(
+                    "RetryingTcpStream[{}] => shut down while connecting",
+                    self.connection_id
+                );
+                self.write_buf.clear();
+                self.set_state(ConnectionState::Shutdown);
+                record!(self, Shutdown, "{}", self.peer_name());
+                Ok(Async::Ready(()))
+            }
+            // nothing left to shut down
+            ConnectionState::Failed | ConnectionState::Shutdown => Ok(Async::Ready(())),
+            ConnectionState::TcpStream(_) => {
+                // queued bytes go out before FIN
+                try_ready!(self.poll_flush());
+                let ts = match &mut self.state {
+                    ConnectionState::TcpStream(ts) => ts,
+                    _ => unreachable!(),
+                };
+                try_ready!(ts.shutdown());
+                self.first_byte_deadline = None;
+                self.set_state(ConnectionState::Shutdown);
+                record!(self, Shutdown, "{}", self.peer_name());
+                Ok(Async::Ready(()))
+            }
+        }
+    }
+}
+
+/// Future returned by [RetryingTcpStream::migrate_to]
+pub struct Migrate<'a> {
+    stream: &'a mut RetryingTcpStream,
+    new_addr: SocketAddr,
+    grace: Delay,
+}
+
+impl<'a> Future for Migrate<'a> {
+    type Item = ();
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<(), Error> {
+        if self.stream.as_tcp_stream().is_some() {
+            let flushed = match self.stream.poll_flush() {
+                Ok(Async::Ready(())) => true,
+                Ok(Async::NotReady) => false,
+                Err(err) => {
+                    warn!(
+                        "RetryingTcpStream[{}] => flush before migration failed: {}",
+                        self.stream.connection_id, err
+                    );
+                    true
+                }
+            };
+            if !flushed && !self.grace.poll().map_err(Error::other)?.is_ready() {
+                return Ok(Async::NotReady);
             }
-            ConnectionState::TcpStream(ts) => ts.shutdown(),
         }
+
+        debug!(
+            "RetryingTcpStream[{}] => migrating to {}",
+            self.stream.connection_id, self.new_addr
+        );
+        self.stream.keep_received();
+        self.stream.reconnect_to(self.new_addr);
+        Ok(Async::Ready(()))
     }
 }
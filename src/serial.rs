@@ -0,0 +1,534 @@
+//! Serial port helpers.
+
+#[cfg(feature = "serde")]
+pub use self::config::SerialConfig;
+#[cfg(all(target_os = "linux", feature = "serial-redetect"))]
+pub use self::redetect::InotifySerialWatcher;
+
+use futures::sync::oneshot;
+use log::warn;
+use tokio::prelude::{future, Future, FutureExt};
+use tokio_serial::{Serial, SerialPort, SerialPortSettings};
+
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Standard rates of termios `cfsetospeed` (`B50` .. `B4000000`)
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SUPPORTED_BAUD_RATES: &[u32] = &[
+    50, 75, 110, 134, 150, 200, 300, 600, 1200, 1800, 2400, 4800, 9600, 19200, 38400, 57600,
+    115200, 230400, 460800, 500000, 576000, 921600, 1000000, 1152000, 1500000, 2000000, 2500000,
+    3000000, 3500000, 4000000,
+];
+
+/// Rates defined by POSIX termios plus common BSD extensions
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "android"))))]
+const SUPPORTED_BAUD_RATES: &[u32] = &[
+    50, 75, 110, 134, 150, 200, 300, 600, 1200, 1800, 2400, 4800, 7200, 9600, 14400, 19200, 28800,
+    38400, 57600, 76800, 115200, 230400,
+];
+
+/// `CBR_*` rates of Win32 `DCB`
+#[cfg(not(unix))]
+const SUPPORTED_BAUD_RATES: &[u32] = &[
+    110, 300, 600, 1200, 2400, 4800, 9600, 14400, 19200, 38400, 57600, 115200, 128000, 256000,
+];
+
+/// Baud rate not supported on current platform, see [validate_baud_rate]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UnsupportedBaudRate {
+    pub baud: u32,
+}
+
+impl UnsupportedBaudRate {
+    /// Standard baud rates of current platform
+    pub fn supported() -> &'static [u32] {
+        SUPPORTED_BAUD_RATES
+    }
+}
+
+impl fmt::Display for UnsupportedBaudRate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "baud rate {} is not supported, supported rates:",
+            self.baud
+        )?;
+        for (i, baud) in SUPPORTED_BAUD_RATES.iter().enumerate() {
+            let separator = if i == 0 { " " } else { ", " };
+            write!(f, "{}{}", separator, baud)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for UnsupportedBaudRate {}
+
+impl From<UnsupportedBaudRate> for io::Error {
+    fn from(err: UnsupportedBaudRate) -> Self {
+        io::Error::new(io::ErrorKind::InvalidInput, err)
+    }
+}
+
+/// Check `baud` is one of standard rates of current platform before the OS rejects it with
+/// unclear error.
+pub fn validate_baud_rate(baud: u32) -> Result<(), UnsupportedBaudRate> {
+    if SUPPORTED_BAUD_RATES.contains(&baud) {
+        Ok(())
+    } else {
+        Err(UnsupportedBaudRate { baud })
+    }
+}
+
+/// Serial port path was adjusted by [normalize_serial_path_checked]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NormalizationWarning {
+    pub input: String,
+    pub normalized: PathBuf,
+}
+
+impl fmt::Display for NormalizationWarning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "serial port `{}` was normalized to `{}`, use the full path in configuration",
+            self.input,
+            self.normalized.display()
+        )
+    }
+}
+
+/// Turn short serial port name into a path that can be opened.
+///
+/// On Windows bare `COMn` becomes `\\.\COMn` (required for `n > 9`). On other platforms bare
+/// `tty*` and `cu.*` names get `/dev/` prefix. Everything else is returned unchanged.
+pub fn normalize_serial_path(input: &str) -> PathBuf {
+    normalize_serial_path_checked(input).0
+}
+
+/// Like [normalize_serial_path] but also returns warning when `input` was modified.
+pub fn normalize_serial_path_checked(input: &str) -> (PathBuf, Option<NormalizationWarning>) {
+    match normalized(input) {
+        Some(normalized) => {
+            let warning = NormalizationWarning {
+                input: input.to_string(),
+                normalized: normalized.clone(),
+            };
+            (normalized, Some(warning))
+        }
+        None => (PathBuf::from(input), None),
+    }
+}
+
+#[cfg(windows)]
+fn normalized(input: &str) -> Option<PathBuf> {
+    let is_com = input.len() > 3
+        && input[..3].eq_ignore_ascii_case("COM")
+        && input[3..].bytes().all(|b| b.is_ascii_digit());
+    if is_com {
+        Some(PathBuf::from(format!(r"\\.\{}", input)))
+    } else {
+        None
+    }
+}
+
+#[cfg(not(windows))]
+fn normalized(input: &str) -> Option<PathBuf> {
+    let is_bare_name =
+        !input.contains('/') && (input.starts_with("tty") || input.starts_with("cu."));
+    if is_bare_name {
+        Some(PathBuf::from("/dev").join(input))
+    } else {
+        None
+    }
+}
+
+/// Open serial port on a helper thread so slow enumerating USB devices don't block the runtime.
+///
+/// Fails with `TimedOut` if the port is not open within `timeout`. The helper thread can't be
+/// interrupted, a port it opens after the timeout is closed right away.
+pub fn open_serial_async(
+    path: &str,
+    settings: &SerialPortSettings,
+    timeout: Duration,
+) -> impl Future<Item = Serial, Error = io::Error> {
+    let path = PathBuf::from(path);
+    let settings = *settings;
+    let (tx, rx) = oneshot::channel();
+    let spawned = std::thread::Builder::new()
+        .name("uniconnect-serial-open".into())
+        .spawn(move || {
+            if tx.send(Serial::from_path(&path, &settings)).is_err() {
+                warn!("serial port {} opened after timeout", path.display());
+            }
+        });
+
+    let opening = rx
+        .then(|res| match res {
+            Ok(serial) => serial,
+            Err(oneshot::Canceled) => Err(io::Error::other("serial open thread panicked")),
+        })
+        .timeout(timeout)
+        .map_err(|err| {
+            if err.is_elapsed() {
+                io::Error::new(io::ErrorKind::TimedOut, "serial port open timed out")
+            } else {
+                err.into_inner()
+                    .unwrap_or_else(|| io::Error::other("timer error"))
+            }
+        });
+
+    match spawned {
+        Ok(_) => future::Either::A(opening),
+        Err(err) => future::Either::B(future::err(err)),
+    }
+}
+
+/// Line control not covered by [SerialPort].
+pub trait SerialExtensions {
+    /// Hold the line dominant for 13 + `extra_bits` bit times at current baud rate, e.g. as LIN
+    /// break field before [LinFrame](crate::codec::LinFrame) header.
+    ///
+    /// # Note
+    /// Blocks the calling thread for the break duration, about 1.4 ms at 9600 baud. Returns
+    /// `Unsupported` on Windows.
+    fn send_lin_break(&mut self, extra_bits: u8) -> io::Result<()>;
+}
+
+impl SerialExtensions for Serial {
+    fn send_lin_break(&mut self, extra_bits: u8) -> io::Result<()> {
+        let baud = SerialPort::baud_rate(self)?;
+        if baud == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "break duration is undefined for baud rate 0",
+            ));
+        }
+        let bits = 13 + u64::from(extra_bits);
+        let duration = Duration::from_micros((bits * 1_000_000).div_ceil(u64::from(baud)));
+
+        sys_break::set(self)?;
+        std::thread::sleep(duration);
+        sys_break::clear(self)
+    }
+}
+
+/// RS-232 modem control lines of [UniConnect](crate::UniConnect) using serial port. Other
+/// connections return `Unsupported`, closed
+/// [RetryingSerial](crate::retrying_serial::RetryingSerial) returns `NotConnected`.
+pub trait ModemControl {
+    fn write_data_terminal_ready(&mut self, level: bool) -> io::Result<()>;
+    fn write_request_to_send(&mut self, level: bool) -> io::Result<()>;
+    fn read_clear_to_send(&mut self) -> io::Result<bool>;
+    fn read_data_set_ready(&mut self) -> io::Result<bool>;
+}
+
+impl ModemControl for crate::UniConnect {
+    fn write_data_terminal_ready(&mut self, level: bool) -> io::Result<()> {
+        Ok(serial_port(self)?.write_data_terminal_ready(level)?)
+    }
+
+    fn write_request_to_send(&mut self, level: bool) -> io::Result<()> {
+        Ok(serial_port(self)?.write_request_to_send(level)?)
+    }
+
+    fn read_clear_to_send(&mut self) -> io::Result<bool> {
+        Ok(serial_port(self)?.read_clear_to_send()?)
+    }
+
+    fn read_data_set_ready(&mut self) -> io::Result<bool> {
+        Ok(serial_port(self)?.read_data_set_ready()?)
+    }
+}
+
+fn serial_port(conn: &mut crate::UniConnect) -> io::Result<&mut Serial> {
+    match conn {
+        crate::UniConnect::Serial(serial) => Ok(serial),
+        crate::UniConnect::RetryingSerial(serial) => serial
+            .as_serial_mut()
+            .ok_or_else(|| io::ErrorKind::NotConnected.into()),
+        _ => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "modem control lines are available only on serial port",
+        )),
+    }
+}
+
+#[cfg(unix)]
+mod sys_break {
+    use std::io;
+    use std::os::unix::io::AsRawFd;
+
+    pub fn set(port: &impl AsRawFd) -> io::Result<()> {
+        ioctl(port, libc::TIOCSBRK)
+    }
+
+    pub fn clear(port: &impl AsRawFd) -> io::Result<()> {
+        ioctl(port, libc::TIOCCBRK)
+    }
+
+    fn ioctl(port: &impl AsRawFd, request: libc::c_ulong) -> io::Result<()> {
+        // TIOCSBRK and TIOCCBRK take no argument
+        if unsafe { libc::ioctl(port.as_raw_fd(), request as _) } == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+// tokio-serial doesn't expose the port handle on Windows
+#[cfg(windows)]
+mod sys_break {
+    use std::io;
+
+    pub fn set<T>(_port: &T) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    pub fn clear<T>(_port: &T) -> io::Result<()> {
+        Err(unsupported())
+    }
+
+    fn unsupported() -> io::Error {
+        io::Error::new(
+            io::ErrorKind::Unsupported,
+            "serial break is not supported on Windows",
+        )
+    }
+}
+
+#[cfg(feature = "serde")]
+mod config {
+    use serde::{Deserialize, Serialize};
+    use tokio_serial::{DataBits, FlowControl, Parity, SerialPortSettings, StopBits};
+
+    use std::fmt;
+    use std::time::Duration;
+
+    /// Serializable mirror of [SerialPortSettings].
+    ///
+    /// Missing fields take their values from `SerialPortSettings::default()`. Displayed in the
+    /// usual `9600 8N1` notation followed by flow control and timeout.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct SerialConfig {
+        pub baud_rate: u32,
+        #[serde(with = "data_bits")]
+        pub data_bits: DataBits,
+        #[serde(with = "ParityDef")]
+        pub parity: Parity,
+        #[serde(with = "stop_bits")]
+        pub stop_bits: StopBits,
+        #[serde(with = "FlowControlDef")]
+        pub flow_control: FlowControl,
+        pub timeout_ms: u64,
+    }
+
+    impl Default for SerialConfig {
+        fn default() -> Self {
+            SerialPortSettings::default().into()
+        }
+    }
+
+    impl fmt::Display for SerialConfig {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            let data_bits = match self.data_bits {
+                DataBits::Five => 5,
+                DataBits::Six => 6,
+                DataBits::Seven => 7,
+                DataBits::Eight => 8,
+            };
+            let parity = match self.parity {
+                Parity::None => 'N',
+                Parity::Odd => 'O',
+                Parity::Even => 'E',
+            };
+            let stop_bits = match self.stop_bits {
+                StopBits::One => 1,
+                StopBits::Two => 2,
+            };
+            let flow_control = match self.flow_control {
+                FlowControl::None => "none",
+                FlowControl::Software => "software",
+                FlowControl::Hardware => "hardware",
+            };
+            write!(
+                f,
+                "{} {}{}{}, flow_control={}, timeout={}ms",
+                self.baud_rate, data_bits, parity, stop_bits, flow_control, self.timeout_ms
+            )
+        }
+    }
+
+    impl From<SerialConfig> for SerialPortSettings {
+        fn from(config: SerialConfig) -> Self {
+            SerialPortSettings {
+                baud_rate: config.baud_rate,
+                data_bits: config.data_bits,
+                flow_control: config.flow_control,
+                parity: config.parity,
+                stop_bits: config.stop_bits,
+                timeout: Duration::from_millis(config.timeout_ms),
+            }
+        }
+    }
+
+    impl From<SerialPortSettings> for SerialConfig {
+        fn from(settings: SerialPortSettings) -> Self {
+            SerialConfig {
+                baud_rate: settings.baud_rate,
+                data_bits: settings.data_bits,
+                parity: settings.parity,
+                stop_bits: settings.stop_bits,
+                flow_control: settings.flow_control,
+                timeout_ms: settings.timeout.as_millis() as u64,
+            }
+        }
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "Parity", rename_all = "lowercase")]
+    enum ParityDef {
+        None,
+        Odd,
+        Even,
+    }
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(remote = "FlowControl", rename_all = "lowercase")]
+    enum FlowControlDef {
+        None,
+        Software,
+        Hardware,
+    }
+
+    // data bits are written as number of bits
+    mod data_bits {
+        use serde::de::{Deserialize, Deserializer, Error};
+        use serde::Serializer;
+        use tokio_serial::DataBits;
+
+        pub fn serialize<S: Serializer>(bits: &DataBits, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_u8(match bits {
+                DataBits::Five => 5,
+                DataBits::Six => 6,
+                DataBits::Seven => 7,
+                DataBits::Eight => 8,
+            })
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<DataBits, D::Error> {
+            match u8::deserialize(d)? {
+                5 => Ok(DataBits::Five),
+                6 => Ok(DataBits::Six),
+                7 => Ok(DataBits::Seven),
+                8 => Ok(DataBits::Eight),
+                n => Err(D::Error::custom(format!(
+                    "invalid data bits {}, expected 5 to 8",
+                    n
+                ))),
+            }
+        }
+    }
+
+    // stop bits are written as number of bits
+    mod stop_bits {
+        use serde::de::{Deserialize, Deserializer, Error};
+        use serde::Serializer;
+        use tokio_serial::StopBits;
+
+        pub fn serialize<S: Serializer>(bits: &StopBits, s: S) -> Result<S::Ok, S::Error> {
+            s.serialize_u8(match bits {
+                StopBits::One => 1,
+                StopBits::Two => 2,
+            })
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<StopBits, D::Error> {
+            match u8::deserialize(d)? {
+                1 => Ok(StopBits::One),
+                2 => Ok(StopBits::Two),
+                n => Err(D::Error::custom(format!(
+                    "invalid stop bits {}, expected 1 or 2",
+                    n
+                ))),
+            }
+        }
+    }
+}
+
+#[cfg(all(target_os = "linux", feature = "serial-redetect"))]
+mod redetect {
+    use futures::try_ready;
+    use inotify::{EventStream, Inotify, WatchMask};
+    use tokio::prelude::{Async, Poll, Stream};
+
+    use std::ffi::OsStr;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    /// Stream of serial devices that appeared in directory of `path` and look like replugged
+    /// `path`.
+    ///
+    /// Trailing digits of the device name are ignored, so watching `/dev/ttyUSB0` yields
+    /// `/dev/ttyUSB1` when adapter comes back under a different number. Names without trailing
+    /// number (like `/dev/serial/by-id/...`) match only themselves. Open the yielded path, or send
+    /// it to [WatchedConnector](crate::reload::WatchedConnector) to reconnect.
+    pub struct InotifySerialWatcher {
+        path: PathBuf,
+        events: EventStream<Vec<u8>>,
+    }
+
+    impl InotifySerialWatcher {
+        /// Watch directory of `path` for `IN_CREATE` events.
+        pub fn new(path: impl Into<PathBuf>) -> io::Result<Self> {
+            let path = path.into();
+            let dir = match path.parent() {
+                Some(dir) if !dir.as_os_str().is_empty() => dir,
+                _ => Path::new("."),
+            };
+            let mut inotify = Inotify::init()?;
+            inotify.add_watch(dir, WatchMask::CREATE)?;
+            let events = inotify.event_stream(vec![0; 4096]);
+            Ok(Self { path, events })
+        }
+
+        /// Watched serial port path
+        pub fn path(&self) -> &Path {
+            &self.path
+        }
+
+        fn matches(&self, name: &OsStr) -> bool {
+            let (watched, name) =
+                match (self.path.file_name().and_then(OsStr::to_str), name.to_str()) {
+                    (Some(watched), Some(name)) => (watched, name),
+                    _ => return false,
+                };
+            let prefix = watched.trim_end_matches(|c: char| c.is_ascii_digit());
+            name.strip_prefix(prefix)
+                .is_some_and(|rest| rest.bytes().all(|b| b.is_ascii_digit()))
+        }
+    }
+
+    impl Stream for InotifySerialWatcher {
+        type Item = PathBuf;
+        type Error = io::Error;
+
+        fn poll(&mut self) -> Poll<Option<PathBuf>, io::Error> {
+            loop {
+                let event = match try_ready!(self.events.poll()) {
+                    Some(event) => event,
+                    None => return Ok(Async::Ready(None)),
+                };
+                if let Some(name) = event.name {
+                    if self.matches(&name) {
+                        return Ok(Async::Ready(Some(self.path.with_file_name(name))));
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,72 @@
+//! Coordinated shutdown of many connections.
+
+use crate::UniConnect;
+
+use futures::future::{join_all, Future};
+
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// Connection that failed to shutdown gracefully
+#[derive(Debug)]
+pub struct ShutdownError {
+    /// Label given at registration
+    pub label: String,
+    pub error: io::Error,
+}
+
+impl fmt::Display for ShutdownError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "connection {} shutdown failed: {}",
+            self.label, self.error
+        )
+    }
+}
+
+impl std::error::Error for ShutdownError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Shutdown all registered connections at once, e.g. on application exit.
+#[derive(Default)]
+pub struct ShutdownCoordinator {
+    connections: Vec<(String, UniConnect)>,
+}
+
+impl ShutdownCoordinator {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Register connection labeled by registration order (`#0`, `#1`, ...)
+    pub fn register(&mut self, conn: UniConnect) {
+        let label = format!("#{}", self.connections.len());
+        self.register_labeled(label, conn);
+    }
+
+    pub fn register_labeled<L: Into<String>>(&mut self, label: L, conn: UniConnect) {
+        self.connections.push((label.into(), conn));
+    }
+
+    /// Gracefully shutdown all connections concurrently, see
+    /// [shutdown_gracefully](UniConnect::shutdown_gracefully).
+    ///
+    /// Resolves after all connections are closed or `timeout` fired, with errors of connections
+    /// that failed. Never fails itself.
+    pub fn shutdown_all(
+        self,
+        timeout: Duration,
+    ) -> impl Future<Item = Vec<ShutdownError>, Error = ()> {
+        let shutdowns = self.connections.into_iter().map(move |(label, conn)| {
+            conn.shutdown_gracefully(timeout)
+                .then(move |res| Ok(res.err().map(|error| ShutdownError { label, error })))
+        });
+
+        join_all(shutdowns).map(|results| results.into_iter().flatten().collect())
+    }
+}
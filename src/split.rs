@@ -0,0 +1,119 @@
+//! Owned read and write halves of [UniConnect].
+
+use crate::UniConnect;
+
+use futures::sync::BiLock;
+use tokio::prelude::{Async, AsyncRead, AsyncWrite, Poll};
+
+use std::fmt;
+use std::io::{self, Read, Write};
+
+/// Read half of [UniConnect], created by [UniConnect::split].
+pub struct UniConnectReadHalf {
+    handle: BiLock<UniConnect>,
+}
+
+/// Write half of [UniConnect], created by [UniConnect::split].
+pub struct UniConnectWriteHalf {
+    handle: BiLock<UniConnect>,
+}
+
+/// Halves passed to [UniConnect::reunite] come from different connections. Both halves are
+/// given back.
+pub struct ReuniteError(pub UniConnectReadHalf, pub UniConnectWriteHalf);
+
+impl UniConnect {
+    /// Split into halves that can be moved into separate reader and writer tasks.
+    ///
+    /// Halves share the connection through [BiLock], a half only waits while the other one is
+    /// inside its own read or write call.
+    pub fn split(self) -> (UniConnectReadHalf, UniConnectWriteHalf) {
+        let (read, write) = BiLock::new(self);
+        (
+            UniConnectReadHalf { handle: read },
+            UniConnectWriteHalf { handle: write },
+        )
+    }
+
+    /// Join halves created by [split](UniConnect::split) back together.
+    pub fn reunite(
+        read: UniConnectReadHalf,
+        write: UniConnectWriteHalf,
+    ) -> Result<UniConnect, ReuniteError> {
+        read.reunite(write)
+    }
+}
+
+impl UniConnectReadHalf {
+    /// See [UniConnect::reunite].
+    pub fn reunite(self, write: UniConnectWriteHalf) -> Result<UniConnect, ReuniteError> {
+        self.handle.reunite(write.handle).map_err(|err| {
+            ReuniteError(
+                UniConnectReadHalf { handle: err.0 },
+                UniConnectWriteHalf { handle: err.1 },
+            )
+        })
+    }
+}
+
+impl Read for UniConnectReadHalf {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self.handle.poll_lock() {
+            Async::Ready(mut conn) => conn.read(buf),
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl AsyncRead for UniConnectReadHalf {}
+
+impl Write for UniConnectWriteHalf {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.handle.poll_lock() {
+            Async::Ready(mut conn) => conn.write(buf),
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.handle.poll_lock() {
+            Async::Ready(mut conn) => conn.flush(),
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl AsyncWrite for UniConnectWriteHalf {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self.handle.poll_lock() {
+            Async::Ready(mut conn) => conn.shutdown(),
+            Async::NotReady => Ok(Async::NotReady),
+        }
+    }
+}
+
+impl fmt::Debug for UniConnectReadHalf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("UniConnectReadHalf")
+    }
+}
+
+impl fmt::Debug for UniConnectWriteHalf {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("UniConnectWriteHalf")
+    }
+}
+
+impl fmt::Debug for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("ReuniteError")
+    }
+}
+
+impl fmt::Display for ReuniteError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("tried to reunite halves of different connections")
+    }
+}
+
+impl std::error::Error for ReuniteError {}
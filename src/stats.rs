@@ -0,0 +1,123 @@
+//! Traffic statistics of a connection.
+
+use crate::UniConnect;
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::Poll;
+
+use std::io::{self, Read, Write};
+
+/// Counters collected by [Instrumented].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct UniConnectStats {
+    pub bytes_read: u64,
+    pub bytes_written: u64,
+    /// Reconnects of the inner connection, see [ReconnectCount]
+    pub reconnect_count: u32,
+}
+
+/// Connection that may reconnect on its own.
+pub trait ReconnectCount {
+    /// Reconnects since the connection was created
+    fn reconnect_count(&self) -> u32;
+}
+
+impl ReconnectCount for UniConnect {
+    /// Reconnects of [RetryingTcpStream](crate::retrying_tcp_stream::RetryingTcpStream) and
+    /// [RetryingSerial](crate::retrying_serial::RetryingSerial), `0` for other variants.
+    fn reconnect_count(&self) -> u32 {
+        match self {
+            #[cfg(feature = "retrying-tcp")]
+            UniConnect::RetringTcpStream(inner) => inner.reconnect_count(),
+            #[cfg(feature = "serial")]
+            UniConnect::RetryingSerial(inner) => inner.reconnect_count(),
+            _ => 0,
+        }
+    }
+}
+
+#[cfg(feature = "serial")]
+impl ReconnectCount for crate::retrying_serial::RetryingSerial {
+    fn reconnect_count(&self) -> u32 {
+        crate::retrying_serial::RetryingSerial::reconnect_count(self)
+    }
+}
+
+#[cfg(feature = "retrying-tcp")]
+impl ReconnectCount for crate::retrying_tcp_stream::RetryingTcpStream {
+    fn reconnect_count(&self) -> u32 {
+        crate::retrying_tcp_stream::RetryingTcpStream::reconnect_count(self)
+    }
+}
+
+/// Connection counting bytes read and written, see [UniConnectStats].
+///
+/// `reconnect_count` is updated on every read and write.
+pub struct Instrumented<T> {
+    inner: T,
+    stats: UniConnectStats,
+}
+
+impl<T: ReconnectCount> Instrumented<T> {
+    pub fn new(inner: T) -> Self {
+        let stats = UniConnectStats {
+            reconnect_count: inner.reconnect_count(),
+            ..UniConnectStats::default()
+        };
+        Self { inner, stats }
+    }
+
+    pub fn stats(&self) -> &UniConnectStats {
+        &self.stats
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+
+    fn update_reconnect_count(&mut self) {
+        self.stats.reconnect_count = self.inner.reconnect_count();
+    }
+}
+
+impl<T: Read + ReconnectCount> Read for Instrumented<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let res = self.inner.read(buf);
+        self.update_reconnect_count();
+        let n = res?;
+        self.stats.bytes_read += n as u64;
+        Ok(n)
+    }
+}
+
+impl<T: AsyncRead + ReconnectCount> AsyncRead for Instrumented<T> {}
+
+impl<T: Write + ReconnectCount> Write for Instrumented<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let res = self.inner.write(buf);
+        self.update_reconnect_count();
+        let n = res?;
+        self.stats.bytes_written += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let res = self.inner.flush();
+        self.update_reconnect_count();
+        res
+    }
+}
+
+impl<T: AsyncWrite + ReconnectCount> AsyncWrite for Instrumented<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.inner.shutdown()
+    }
+}
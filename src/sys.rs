@@ -0,0 +1,10 @@
+//! Socket options not exposed by tokio.
+
+use std::io;
+use std::os::unix::io::{AsRawFd, BorrowedFd};
+
+pub(crate) fn set_cork(socket: &impl AsRawFd, corked: bool) -> io::Result<()> {
+    // `socket` outlives the borrow and stays owned by the caller
+    let fd = unsafe { BorrowedFd::borrow_raw(socket.as_raw_fd()) };
+    socket2::SockRef::from(&fd).set_cork(corked)
+}
@@ -0,0 +1,61 @@
+//! Plain TCP connections without reconnects.
+
+use crate::retrying_tcp_stream::{connect_with_settings, TcpStreamSettings};
+use crate::UniConnect;
+
+use futures::Future;
+
+use std::io;
+use std::net::SocketAddr;
+
+/// Builder of plain [TcpStream](tokio::net::TcpStream) connection without reconnects, for
+/// applications handling reconnection on their own.
+///
+/// Connect timeout, `TCP_NODELAY`, `TCP_CORK`, keep-alive, linger and buffer sizes of
+/// [TcpStreamSettings] are applied, options specific to
+/// [RetryingTcpStream](crate::retrying_tcp_stream::RetryingTcpStream) (write buffer, warm-up probe,
+/// first byte timeout, resolver) are ignored.
+///
+/// ```no_run
+/// use tokio::prelude::Future;
+/// use tokio_uniconnect::retrying_tcp_stream::TcpStreamSettings;
+/// use tokio_uniconnect::tcp_only::TcpOnly;
+///
+/// let mut settings = TcpStreamSettings::default();
+/// settings.set_nodelay(true);
+/// let connect = TcpOnly::new("127.0.0.1:5000".parse().unwrap())
+///     .with_tcp_settings(settings)
+///     .build()
+///     .map(|conn| println!("connected to {}", conn.redacted()));
+/// tokio::run(connect.map_err(|err| eprintln!("{}", err)));
+/// ```
+#[derive(Clone, Debug)]
+pub struct TcpOnly {
+    addr: SocketAddr,
+    tcp_settings: TcpStreamSettings,
+}
+
+impl TcpOnly {
+    pub fn new(addr: SocketAddr) -> Self {
+        Self {
+            addr,
+            tcp_settings: TcpStreamSettings::default(),
+        }
+    }
+
+    pub fn set_tcp_settings(&mut self, tcp_settings: TcpStreamSettings) -> &mut Self {
+        self.tcp_settings = tcp_settings;
+        self
+    }
+
+    /// Owned variant of [set_tcp_settings](TcpOnly::set_tcp_settings).
+    pub fn with_tcp_settings(mut self, tcp_settings: TcpStreamSettings) -> Self {
+        self.tcp_settings = tcp_settings;
+        self
+    }
+
+    /// Connect and apply settings, resolves to [UniConnect::TcpStream].
+    pub fn build(self) -> impl Future<Item = UniConnect, Error = io::Error> {
+        connect_with_settings(&self.addr, &self.tcp_settings).map(UniConnect::from)
+    }
+}
@@ -0,0 +1,178 @@
+//! Test doubles.
+
+use crate::factory::{ConnectionFactory, CreateFuture};
+use crate::UniConnect;
+
+use bytes::Bytes;
+use futures::task::{self, Task};
+use tokio::prelude::{future, Async, AsyncRead, AsyncWrite, Future, Poll};
+use tokio::timer::Delay;
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// [ConnectionFactory] returning pre-configured results in order.
+///
+/// Once all results are handed out `create` fails with `NotConnected`.
+///
+/// ```no_run
+/// use std::io;
+/// use std::sync::Arc;
+/// use tokio_uniconnect::factory::ConnectionFactory;
+/// use tokio_uniconnect::testing::MockFactory;
+///
+/// let factory = MockFactory::new();
+/// factory.push_err(io::Error::from(io::ErrorKind::ConnectionRefused));
+/// let factory: Arc<dyn ConnectionFactory> = Arc::new(factory);
+/// ```
+#[derive(Default)]
+pub struct MockFactory {
+    results: Mutex<VecDeque<io::Result<UniConnect>>>,
+}
+
+impl MockFactory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue connection for next `create`
+    pub fn push(&self, conn: UniConnect) {
+        self.push_result(Ok(conn))
+    }
+
+    /// Queue error for next `create`
+    pub fn push_err(&self, err: io::Error) {
+        self.push_result(Err(err))
+    }
+
+    /// Number of results not handed out yet
+    pub fn remaining(&self) -> usize {
+        self.lock().len()
+    }
+
+    fn push_result(&self, result: io::Result<UniConnect>) {
+        self.lock().push_back(result)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, VecDeque<io::Result<UniConnect>>> {
+        self.results.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl ConnectionFactory for MockFactory {
+    fn create(&self) -> CreateFuture {
+        let result = self.lock().pop_front().unwrap_or_else(|| {
+            Err(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "MockFactory has no more connections",
+            ))
+        });
+        Box::new(future::result(result))
+    }
+}
+
+/// Connection reading back everything written to it.
+///
+/// Every write becomes readable after [delay](EchoConn::with_delay), right away by default.
+/// Reads without data return `WouldBlock` and wake the task on next write, so reader and writer
+/// can be the same task. After [close_write](EchoConn::close_write) reads return EOF once written
+/// data is consumed.
+#[derive(Default)]
+pub struct EchoConn {
+    pending: VecDeque<(Instant, Bytes)>,
+    delay: Duration,
+    timer: Option<Delay>,
+    reader: Option<Task>,
+    write_closed: bool,
+}
+
+impl EchoConn {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Make written data readable only after `delay`, e.g. to simulate network latency.
+    pub fn with_delay(delay: Duration) -> Self {
+        Self {
+            delay,
+            ..Self::default()
+        }
+    }
+
+    /// Number of bytes written and not read yet
+    pub fn pending_len(&self) -> usize {
+        self.pending.iter().map(|(_, data)| data.len()).sum()
+    }
+
+    /// Reads return EOF once data written so far is read. Writes fail with `BrokenPipe`.
+    pub fn close_write(&mut self) {
+        self.write_closed = true;
+        self.notify_reader();
+    }
+
+    fn notify_reader(&mut self) {
+        if let Some(task) = self.reader.take() {
+            task.notify();
+        }
+    }
+
+    fn would_block(&mut self) -> io::Result<usize> {
+        self.reader = Some(task::current());
+        Err(io::ErrorKind::WouldBlock.into())
+    }
+}
+
+impl Read for EchoConn {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let ready_at = match self.pending.front() {
+            Some((ready_at, _)) => *ready_at,
+            None if self.write_closed => return Ok(0),
+            None => return self.would_block(),
+        };
+        if ready_at > Instant::now() {
+            let timer = self.timer.get_or_insert_with(|| Delay::new(ready_at));
+            timer.reset(ready_at);
+            if let Async::NotReady = timer.poll().map_err(io::Error::other)? {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+        }
+
+        let (_, data) = self.pending.front_mut().expect("checked above");
+        let n = buf.len().min(data.len());
+        buf[..n].copy_from_slice(&data[..n]);
+        data.advance(n);
+        if data.is_empty() {
+            self.pending.pop_front();
+        }
+        Ok(n)
+    }
+}
+
+impl AsyncRead for EchoConn {}
+
+impl Write for EchoConn {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.write_closed {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        if !buf.is_empty() {
+            let ready_at = Instant::now() + self.delay;
+            self.pending.push_back((ready_at, Bytes::from(buf)));
+            self.notify_reader();
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for EchoConn {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        self.close_write();
+        Ok(Async::Ready(()))
+    }
+}
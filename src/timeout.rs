@@ -0,0 +1,165 @@
+//! Deadlines of reads and writes.
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::prelude::{Async, Future, Poll};
+use tokio::timer::Delay;
+
+use std::io::{self, Read, Write};
+use std::time::{Duration, Instant};
+
+/// Longest time a read may wait for data, see [Timeout::set_read_timeout].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReadTimeout(pub Duration);
+
+/// Longest time a write, flush or shutdown may wait, see [Timeout::set_write_timeout].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WriteTimeout(pub Duration);
+
+#[derive(Debug)]
+struct Deadline {
+    timeout: Duration,
+    // running while operation waits, reset on progress
+    delay: Option<Delay>,
+}
+
+impl Deadline {
+    fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            delay: None,
+        }
+    }
+
+    // Turn `WouldBlock` of `res` into `TimedOut` once the wait is longer than timeout
+    fn check<R>(&mut self, res: io::Result<R>) -> io::Result<R> {
+        match res {
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => {
+                let timeout = self.timeout;
+                let delay = self
+                    .delay
+                    .get_or_insert_with(|| Delay::new(Instant::now() + timeout));
+                match delay.poll().map_err(io::Error::other)? {
+                    Async::Ready(()) => {
+                        self.delay = None;
+                        Err(io::Error::new(
+                            io::ErrorKind::TimedOut,
+                            "operation timed out",
+                        ))
+                    }
+                    Async::NotReady => Err(err),
+                }
+            }
+            res => {
+                self.delay = None;
+                res
+            }
+        }
+    }
+}
+
+/// Connection failing reads and writes with `TimedOut` when they wait longer than their
+/// timeout.
+///
+/// Timeout is measured from the first poll that would block until the operation makes progress,
+/// so an idle connection nobody reads from never times out. The connection itself stays open
+/// after timeout, the operation can be retried.
+#[derive(Debug)]
+pub struct Timeout<T> {
+    inner: T,
+    read: Option<Deadline>,
+    write: Option<Deadline>,
+}
+
+impl<T> Timeout<T> {
+    /// Use `timeout` for both reads and writes.
+    pub fn new(inner: T, timeout: Duration) -> Self {
+        Self {
+            inner,
+            read: Some(Deadline::new(timeout)),
+            write: Some(Deadline::new(timeout)),
+        }
+    }
+
+    /// Change read timeout, `None` waits forever.
+    pub fn set_read_timeout(&mut self, timeout: Option<ReadTimeout>) {
+        self.read = timeout.map(|ReadTimeout(timeout)| Deadline::new(timeout));
+    }
+
+    /// Change timeout of writes, flushes and shutdown, `None` waits forever.
+    pub fn set_write_timeout(&mut self, timeout: Option<WriteTimeout>) {
+        self.write = timeout.map(|WriteTimeout(timeout)| Deadline::new(timeout));
+    }
+
+    pub fn read_timeout(&self) -> Option<ReadTimeout> {
+        self.read
+            .as_ref()
+            .map(|deadline| ReadTimeout(deadline.timeout))
+    }
+
+    pub fn write_timeout(&self) -> Option<WriteTimeout> {
+        self.write
+            .as_ref()
+            .map(|deadline| WriteTimeout(deadline.timeout))
+    }
+
+    pub fn get_ref(&self) -> &T {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> T {
+        self.inner
+    }
+}
+
+impl<T: Read> Read for Timeout<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let res = self.inner.read(buf);
+        match &mut self.read {
+            Some(deadline) => deadline.check(res),
+            None => res,
+        }
+    }
+}
+
+impl<T: AsyncRead> AsyncRead for Timeout<T> {}
+
+impl<T: Write> Write for Timeout<T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let res = self.inner.write(buf);
+        match &mut self.write {
+            Some(deadline) => deadline.check(res),
+            None => res,
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let res = self.inner.flush();
+        match &mut self.write {
+            Some(deadline) => deadline.check(res),
+            None => res,
+        }
+    }
+}
+
+impl<T: AsyncWrite> AsyncWrite for Timeout<T> {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        let res = self.inner.shutdown();
+        let deadline = match &mut self.write {
+            Some(deadline) => deadline,
+            None => return res,
+        };
+        let res = res.and_then(|ready| match ready {
+            Async::Ready(()) => Ok(()),
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        });
+        match deadline.check(res) {
+            Ok(()) => Ok(Async::Ready(())),
+            Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(Async::NotReady),
+            Err(err) => Err(err),
+        }
+    }
+}
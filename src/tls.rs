@@ -0,0 +1,82 @@
+//! TLS over TCP with [rustls](tokio_rustls::rustls).
+
+use crate::resolver::{AddressResolver, SystemResolver};
+use crate::UniConnect;
+
+use tokio::net::TcpStream;
+use tokio::prelude::{future, Future};
+use tokio_rustls::rustls::ClientConfig;
+use tokio_rustls::webpki::DNSNameRef;
+
+use std::io::{self, BufReader};
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+pub use tokio_rustls::TlsConnector;
+
+/// Client TLS stream over tokio TcpStream
+pub type TlsStream = tokio_rustls::client::TlsStream<TcpStream>;
+
+/// Connect to `addr` and perform TLS handshake, server certificate is verified for `domain`.
+pub fn tls_connect(
+    addr: &SocketAddr,
+    domain: &str,
+    connector: TlsConnector,
+) -> impl Future<Item = UniConnect, Error = io::Error> {
+    let domain = domain.to_owned();
+    future::result(dns_name(&domain).map(drop))
+        .and_then({
+            let addr = *addr;
+            move |()| TcpStream::connect(&addr)
+        })
+        .and_then(move |stream| {
+            let domain = dns_name(&domain).expect("domain was checked before connecting");
+            connector.connect(domain, stream)
+        })
+        .map(UniConnect::from)
+}
+
+/// Resolve `domain` with [SystemResolver], connect to the first address and perform TLS
+/// handshake.
+pub fn tls_connect_host(
+    domain: &str,
+    port: u16,
+    connector: TlsConnector,
+) -> impl Future<Item = UniConnect, Error = io::Error> {
+    let domain = domain.to_owned();
+    SystemResolver
+        .resolve(&domain, port)
+        .and_then(|addrs| {
+            addrs
+                .into_iter()
+                .next()
+                .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "hostname has no addresses"))
+        })
+        .and_then(move |addr| tls_connect(&addr, &domain, connector))
+}
+
+/// Connector trusting only certificates in `pem`, e.g. pinned self-signed server certificate
+/// or private CA.
+pub fn connector_with_server_cert_pem(pem: &[u8]) -> io::Result<TlsConnector> {
+    let mut config = ClientConfig::new();
+    let (added, _) = config
+        .root_store
+        .add_pem_file(&mut BufReader::new(pem))
+        .map_err(|()| io::Error::new(io::ErrorKind::InvalidData, "invalid PEM"))?;
+    if added == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "no usable certificate in PEM",
+        ));
+    }
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+fn dns_name(domain: &str) -> io::Result<DNSNameRef<'_>> {
+    DNSNameRef::try_from_ascii_str(domain).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("`{}` is not a valid DNS name", domain),
+        )
+    })
+}
@@ -0,0 +1,119 @@
+//! UDP unicast to a single peer.
+
+use crate::UniConnect;
+
+use futures::Async;
+use log::debug;
+use tokio::net::UdpSocket;
+use tokio::prelude::{AsyncRead, AsyncWrite, Poll};
+
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr};
+
+/// UDP socket exchanging datagrams with one peer.
+///
+/// Every write sends one datagram to the peer and every read returns one received datagram,
+/// a datagram not fitting into read buffer is truncated. Datagrams from other sources are
+/// discarded. UDP is connectionless, so the peer is stored alongside the socket and lost
+/// datagrams are not reported.
+pub struct UdpConn {
+    socket: UdpSocket,
+    peer: SocketAddr,
+}
+
+impl UdpConn {
+    pub fn new(socket: UdpSocket, peer: SocketAddr) -> Self {
+        Self { socket, peer }
+    }
+
+    /// Bind to unspecified address of the same family as `peer`, port is chosen by the OS.
+    pub fn bind(peer: &SocketAddr) -> io::Result<Self> {
+        let ip = match peer {
+            SocketAddr::V4(_) => IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+            SocketAddr::V6(_) => IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+        };
+        let socket = UdpSocket::bind(&SocketAddr::new(ip, 0))?;
+        Ok(Self::new(socket, *peer))
+    }
+
+    pub fn peer_addr(&self) -> SocketAddr {
+        self.peer
+    }
+
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.socket.local_addr()
+    }
+
+    /// Set `SO_BROADCAST`, needed by [send_broadcast](UdpConn::send_broadcast) to reach
+    /// broadcast address such as `255.255.255.255`.
+    pub fn enable_broadcast(&self) -> io::Result<()> {
+        self.socket.set_broadcast(true)
+    }
+
+    /// Send one datagram to `addr` instead of the peer, used by discovery protocols to find
+    /// devices on the subnet. Returns `WouldBlock` when the socket is not ready.
+    pub fn send_broadcast(&mut self, addr: &SocketAddr, data: &[u8]) -> io::Result<usize> {
+        match self.socket.poll_send_to(data, addr)? {
+            Async::Ready(n) => Ok(n),
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    /// Receive one datagram from any source together with its address, unlike `read` nothing
+    /// is discarded. Answers to [send_broadcast](UdpConn::send_broadcast) come this way.
+    pub fn poll_recv_from(&mut self, buf: &mut [u8]) -> Poll<(usize, SocketAddr), io::Error> {
+        self.socket.poll_recv_from(buf)
+    }
+
+    pub fn get_ref(&self) -> &UdpSocket {
+        &self.socket
+    }
+
+    pub fn get_mut(&mut self) -> &mut UdpSocket {
+        &mut self.socket
+    }
+
+    pub fn into_inner(self) -> (UdpSocket, SocketAddr) {
+        (self.socket, self.peer)
+    }
+}
+
+impl Read for UdpConn {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            match self.socket.poll_recv_from(buf)? {
+                Async::Ready((n, from)) if from == self.peer => return Ok(n),
+                Async::Ready((n, from)) => {
+                    debug!("UdpConn => discarding {} bytes from {}", n, from);
+                }
+                Async::NotReady => return Err(io::ErrorKind::WouldBlock.into()),
+            }
+        }
+    }
+}
+
+impl AsyncRead for UdpConn {}
+
+impl Write for UdpConn {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.socket.poll_send_to(buf, &self.peer)? {
+            Async::Ready(n) => Ok(n),
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl AsyncWrite for UdpConn {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        Ok(Async::Ready(()))
+    }
+}
+
+/// Bind UDP socket exchanging datagrams with `peer`, see [UdpConn].
+pub fn connect_udp(peer: &SocketAddr) -> io::Result<UniConnect> {
+    UdpConn::bind(peer).map(UniConnect::from)
+}
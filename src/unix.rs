@@ -0,0 +1,46 @@
+//! Unix domain socket connections.
+
+use crate::UniConnect;
+
+use tokio::net::UnixStream;
+use tokio::prelude::{future, Future};
+use tokio::reactor::Handle;
+
+use std::io;
+use std::path::Path;
+
+/// Connect to Linux abstract namespace socket `name` (without the leading `\0`). Such sockets
+/// have no file system entry and are scoped by network namespace, e.g. of a container.
+#[cfg(target_os = "linux")]
+pub fn connect_abstract_unix(name: &[u8]) -> impl Future<Item = UniConnect, Error = io::Error> {
+    future::result(connect_abstract_unix_sync(name).map(UniConnect::from))
+}
+
+/// Connect to Unix socket at `path`.
+pub fn connect_unix(path: &Path) -> impl Future<Item = UniConnect, Error = io::Error> {
+    UnixStream::connect(path).map(UniConnect::from)
+}
+
+// Connecting to local socket doesn't block for long, used by the sync builder and parsing
+pub(crate) fn connect_unix_sync(path: &Path) -> io::Result<UnixStream> {
+    let stream = std::os::unix::net::UnixStream::connect(path).map_err(|err| {
+        io::Error::new(
+            err.kind(),
+            format!("can't connect to unix socket {}: {}", path.display(), err),
+        )
+    })?;
+    from_std(stream)
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn connect_abstract_unix_sync(name: &[u8]) -> io::Result<UnixStream> {
+    use std::os::linux::net::SocketAddrExt;
+
+    let addr = std::os::unix::net::SocketAddr::from_abstract_name(name)?;
+    let stream = std::os::unix::net::UnixStream::connect_addr(&addr)?;
+    from_std(stream)
+}
+
+fn from_std(stream: std::os::unix::net::UnixStream) -> io::Result<UnixStream> {
+    UnixStream::from_std(stream, &Handle::default())
+}
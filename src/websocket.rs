@@ -0,0 +1,138 @@
+//! Binary byte stream tunneled over WebSocket.
+
+use crate::UniConnect;
+
+use bytes::Bytes;
+use futures::{Async, Sink, Stream};
+use log::debug;
+use tokio::net::TcpStream;
+use tokio::prelude::{AsyncRead, AsyncWrite, Future, Poll};
+use tokio_tungstenite::tungstenite::{Error as WsError, Message};
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+use std::io::{self, Read, Write};
+
+/// WebSocket client stream, `ws://` or `wss://`
+pub type WebSocketTransport = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+/// WebSocket connection used as byte stream.
+///
+/// Writes are sent as `Binary` messages, reads return payloads of received `Binary` and `Text`
+/// messages. A message not fitting into read buffer is returned by following reads. Pings are
+/// answered with pongs automatically.
+pub struct WebSocketConn {
+    stream: WebSocketTransport,
+    // rest of the last received message
+    read_buf: Bytes,
+}
+
+impl WebSocketConn {
+    pub fn new(stream: WebSocketTransport) -> Self {
+        Self {
+            stream,
+            read_buf: Bytes::new(),
+        }
+    }
+
+    pub fn get_ref(&self) -> &WebSocketTransport {
+        &self.stream
+    }
+
+    pub fn get_mut(&mut self) -> &mut WebSocketTransport {
+        &mut self.stream
+    }
+
+    pub fn into_inner(self) -> WebSocketTransport {
+        self.stream
+    }
+
+    fn poll_message(&mut self) -> Poll<bool, io::Error> {
+        loop {
+            let message = match self.stream.poll().map_err(ws_to_io)? {
+                Async::Ready(Some(message)) => message,
+                Async::Ready(None) => return Ok(Async::Ready(false)),
+                Async::NotReady => return Ok(Async::NotReady),
+            };
+            match message {
+                Message::Binary(data) => self.read_buf = data.into(),
+                Message::Text(text) => self.read_buf = text.into(),
+                // pong is queued by tungstenite and sent with next read or write
+                Message::Ping(_) | Message::Pong(_) => continue,
+                Message::Close(frame) => {
+                    debug!("WebSocketConn => closed by peer: {:?}", frame);
+                    continue;
+                }
+            }
+            if !self.read_buf.is_empty() {
+                return Ok(Async::Ready(true));
+            }
+        }
+    }
+}
+
+impl Read for WebSocketConn {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.read_buf.is_empty() {
+            match self.poll_message()? {
+                Async::Ready(true) => {}
+                Async::Ready(false) => return Ok(0),
+                Async::NotReady => return Err(io::ErrorKind::WouldBlock.into()),
+            }
+        }
+        let n = buf.len().min(self.read_buf.len());
+        buf[..n].copy_from_slice(&self.read_buf[..n]);
+        self.read_buf.advance(n);
+        Ok(n)
+    }
+}
+
+impl AsyncRead for WebSocketConn {}
+
+impl Write for WebSocketConn {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self
+            .stream
+            .start_send(Message::Binary(buf.to_vec()))
+            .map_err(ws_to_io)?
+        {
+            futures::AsyncSink::Ready => Ok(buf.len()),
+            futures::AsyncSink::NotReady(_) => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.stream.poll_complete().map_err(ws_to_io)? {
+            Async::Ready(()) => Ok(()),
+            Async::NotReady => Err(io::ErrorKind::WouldBlock.into()),
+        }
+    }
+}
+
+impl AsyncWrite for WebSocketConn {
+    fn shutdown(&mut self) -> Poll<(), io::Error> {
+        match self.stream.close() {
+            Err(WsError::ConnectionClosed) | Err(WsError::AlreadyClosed) => Ok(Async::Ready(())),
+            res => res.map_err(ws_to_io),
+        }
+    }
+}
+
+/// Connect to WebSocket server at `url` (`ws://` or `wss://`).
+pub fn websocket_connect(url: &str) -> impl Future<Item = UniConnect, Error = io::Error> {
+    let url = url::Url::parse(url).map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err));
+    futures::future::result(url).and_then(|url| {
+        tokio_tungstenite::connect_async(url)
+            .map(|(stream, _response)| UniConnect::WebSocket(WebSocketConn::new(stream)))
+            .map_err(ws_to_io)
+    })
+}
+
+fn ws_to_io(err: WsError) -> io::Error {
+    match err {
+        WsError::Io(err) => err,
+        WsError::ConnectionClosed | WsError::AlreadyClosed => {
+            io::Error::new(io::ErrorKind::NotConnected, err)
+        }
+        err => io::Error::other(err),
+    }
+}
@@ -0,0 +1,58 @@
+use bytes::BytesMut;
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::bridge::{InspectingBridge, InterceptHandler};
+use tokio_uniconnect::UniConnect;
+
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::thread;
+
+fn connected(listener: &TcpListener) -> (UniConnect, TcpStream) {
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (peer, _) = listener.accept().unwrap();
+    let stream = tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap();
+    (UniConnect::from(stream), peer)
+}
+
+// Uppercase requests, prefix responses
+struct Rewrite;
+
+impl InterceptHandler for Rewrite {
+    fn intercept_a_to_b(&mut self, data: &mut BytesMut) {
+        data.make_ascii_uppercase();
+    }
+
+    fn intercept_b_to_a(&mut self, data: &mut BytesMut) {
+        let response = data.take();
+        data.extend_from_slice(b"> ");
+        data.extend_from_slice(&response);
+    }
+}
+
+#[test]
+fn bridge_forwards_intercepted_data_until_eof() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (a, mut client) = connected(&listener);
+    let (b, mut server) = connected(&listener);
+
+    let peers = thread::spawn(move || {
+        client.write_all(b"hello").unwrap();
+        let mut request = [0u8; 5];
+        server.read_exact(&mut request).unwrap();
+        assert_eq!(&request, b"HELLO");
+
+        server.write_all(b"world").unwrap();
+        let mut response = [0u8; 7];
+        client.read_exact(&mut response).unwrap();
+        assert_eq!(&response, b"> world");
+        client.shutdown(Shutdown::Write).unwrap();
+    });
+
+    let written = rt
+        .block_on(InspectingBridge::new(a, b, Rewrite).run())
+        .unwrap();
+    assert_eq!(written, (5, 7));
+    peers.join().unwrap();
+}
@@ -0,0 +1,50 @@
+use tokio::reactor::Handle;
+use tokio_uniconnect::UniConnect;
+
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+
+#[test]
+fn tcp_buffer_sizes_can_be_changed() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let conn =
+        UniConnect::from(tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap());
+
+    conn.set_send_buffer_size(64 * 1024).unwrap();
+    conn.set_recv_buffer_size(32 * 1024).unwrap();
+    // kernel may round sizes up (Linux doubles them)
+    let send = conn.send_buffer_size().unwrap();
+    let recv = conn.recv_buffer_size().unwrap();
+    assert!(send >= 64 * 1024, "{}", send);
+    assert!(recv >= 32 * 1024, "{}", recv);
+
+    conn.set_send_buffer_size(16 * 1024).unwrap();
+    assert!(conn.send_buffer_size().unwrap() < send);
+}
+
+#[test]
+fn udp_has_no_tcp_buffers() {
+    let conn = tokio_uniconnect::udp::connect_udp(&"127.0.0.1:9".parse().unwrap()).unwrap();
+    assert_eq!(
+        conn.send_buffer_size().unwrap_err().kind(),
+        ErrorKind::Unsupported
+    );
+    assert_eq!(
+        conn.set_recv_buffer_size(1024).unwrap_err().kind(),
+        ErrorKind::Unsupported
+    );
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn connecting_stream_is_not_connected() {
+    use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let conn = UniConnect::from(RetryingTcpStream::connect(&listener.local_addr().unwrap()));
+    assert_eq!(
+        conn.recv_buffer_size().unwrap_err().kind(),
+        ErrorKind::NotConnected
+    );
+}
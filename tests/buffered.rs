@@ -0,0 +1,73 @@
+use tokio::net::TcpStream;
+use tokio::prelude::Future;
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::UniConnect;
+
+use std::io::{Read, Write};
+use std::time::Duration;
+
+fn loopback_pair() -> (UniConnect, std::net::TcpStream) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    let client = TcpStream::from_std(client, &Handle::default()).unwrap();
+    (UniConnect::from(client), server)
+}
+
+#[test]
+fn writes_are_sent_on_flush() {
+    let (conn, mut server) = loopback_pair();
+    let mut rt = Runtime::new().unwrap();
+    let mut conn = conn.buffered();
+    assert_eq!(conn.peer_addr().unwrap(), server.local_addr().unwrap());
+
+    for byte in b"hello" {
+        assert_eq!(conn.write(&[*byte]).unwrap(), 1);
+    }
+    assert_eq!(conn.write_buffer(), b"hello");
+    server
+        .set_read_timeout(Some(Duration::from_millis(50)))
+        .unwrap();
+    assert!(
+        server.read(&mut [0u8; 5]).is_err(),
+        "nothing sent before flush"
+    );
+
+    let conn = rt.block_on(tokio::io::flush(conn)).unwrap();
+    assert!(conn.write_buffer().is_empty());
+    let mut received = [0u8; 5];
+    server.read_exact(&mut received).unwrap();
+    assert_eq!(&received, b"hello");
+}
+
+#[test]
+fn reads_lines() {
+    let (conn, mut server) = loopback_pair();
+    let mut rt = Runtime::new().unwrap();
+    server.write_all(b"first\nsecond\n").unwrap();
+
+    let (conn, line) = rt
+        .block_on(tokio::io::read_until(conn.buffered(), b'\n', Vec::new()))
+        .unwrap();
+    assert_eq!(line, b"first\n");
+    // rest of data stays buffered
+    assert_eq!(conn.read_buffer(), b"second\n");
+    let (_conn, line) = rt
+        .block_on(tokio::io::read_until(conn, b'\n', Vec::new()))
+        .unwrap();
+    assert_eq!(line, b"second\n");
+}
+
+#[test]
+fn shutdown_flushes() {
+    let (conn, mut server) = loopback_pair();
+    let mut rt = Runtime::new().unwrap();
+    let mut conn = conn.buffered();
+    conn.write_all(b"bye").unwrap();
+
+    rt.block_on(tokio::io::shutdown(conn).map(drop)).unwrap();
+    let mut received = Vec::new();
+    server.read_to_end(&mut received).unwrap();
+    assert_eq!(received, b"bye");
+}
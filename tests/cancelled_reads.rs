@@ -0,0 +1,70 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::{future, AsyncRead, FutureExt};
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+const TOTAL: usize = 64 * 1024;
+
+/// Small deterministic generator, good enough to scatter chunk sizes and timeouts.
+struct Lcg(u64);
+
+impl Lcg {
+    fn next(&mut self, bound: u64) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1);
+        (self.0 >> 33) % bound
+    }
+}
+
+fn payload() -> Vec<u8> {
+    (0..TOTAL).map(|i| (i % 251) as u8).collect()
+}
+
+#[test]
+fn cancelled_reads_lose_no_bytes() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut stream = RetryingTcpStream::from_std(stream, &Handle::default()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    let writer = thread::spawn(move || {
+        let mut rng = Lcg(7);
+        let data = payload();
+        let mut sent = 0;
+        while sent < data.len() {
+            let len = (1 + rng.next(4096) as usize).min(data.len() - sent);
+            peer.write_all(&data[sent..sent + len]).unwrap();
+            sent += len;
+            thread::sleep(Duration::from_micros(rng.next(3000)));
+        }
+        peer
+    });
+
+    let mut rt = Runtime::new().unwrap();
+    let mut rng = Lcg(11);
+    let mut received = Vec::with_capacity(TOTAL);
+    let mut buf = [0u8; 1024];
+    let mut cancelled = 0;
+    while received.len() < TOTAL {
+        let read = future::poll_fn(|| stream.poll_read(&mut buf))
+            .timeout(Duration::from_micros(500 + rng.next(1500)));
+        match rt.block_on(read) {
+            Ok(n) => received.extend_from_slice(&buf[..n]),
+            Err(err) if err.is_elapsed() => cancelled += 1,
+            Err(err) => panic!("read failed: {:?}", err),
+        }
+    }
+
+    let _peer = writer.join().unwrap();
+    assert!(cancelled > 0, "no read was cancelled");
+    assert_eq!(received, payload());
+}
@@ -0,0 +1,119 @@
+#![cfg(feature = "pcap")]
+
+use pcap_file::pcap::PcapReader;
+use pcap_file::DataLink;
+use tokio_uniconnect::capture::{CaptureLink, PcapCapture};
+
+use std::fs::File;
+use std::io::{Cursor, Read, Write};
+use std::path::PathBuf;
+
+fn capture_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("uniconnect-{}-{}.pcap", name, std::process::id()))
+}
+
+// Read link type and all records of PCAP file at `path`, then remove it
+fn records(path: &PathBuf) -> (DataLink, Vec<Vec<u8>>) {
+    let mut reader = PcapReader::new(File::open(path).unwrap()).unwrap();
+    let datalink = reader.header().datalink;
+    let mut records = Vec::new();
+    while let Some(packet) = reader.next_packet() {
+        records.push(packet.unwrap().data.into_owned());
+    }
+    std::fs::remove_file(path).unwrap();
+    (datalink, records)
+}
+
+#[test]
+fn raw_records_carry_direction() {
+    let path = capture_path("raw");
+    let mut capture =
+        PcapCapture::create(Cursor::new(b"in".to_vec()), &path, CaptureLink::Raw).unwrap();
+
+    assert_eq!(capture.read(&mut [0u8; 2]).unwrap(), 2);
+    capture.write_all(b"out").unwrap();
+    // EOF is not recorded
+    assert_eq!(capture.read(&mut [0u8; 2]).unwrap(), 0);
+    let inner = capture.into_inner().unwrap();
+    assert_eq!(inner.into_inner(), b"inout");
+
+    let (datalink, records) = records(&path);
+    assert_eq!(datalink, DataLink::USER0);
+    assert_eq!(records, vec![b"\0in".to_vec(), b"\x01out".to_vec()]);
+}
+
+#[test]
+fn tcp_records_are_dissectable_frames() {
+    let path = capture_path("tcp");
+    let local = "10.0.0.1:40000".parse().unwrap();
+    let peer = "10.0.0.2:502".parse().unwrap();
+    let mut capture = PcapCapture::create(
+        Cursor::new(b"response".to_vec()),
+        &path,
+        CaptureLink::Tcp { local, peer },
+    )
+    .unwrap();
+
+    capture.read_exact(&mut [0u8; 8]).unwrap();
+    capture.write_all(b"request").unwrap();
+    capture.flush().unwrap();
+
+    let (datalink, records) = records(&path);
+    assert_eq!(datalink, DataLink::ETHERNET);
+    assert_eq!(records.len(), 2);
+    for record in &records {
+        // IPv4 ethertype
+        assert_eq!(&record[12..14], &[0x08, 0x00]);
+    }
+    let (rx, tx) = (&records[0], &records[1]);
+    // IP addresses
+    assert_eq!(&tx[26..34], &[10, 0, 0, 1, 10, 0, 0, 2]);
+    assert_eq!(&rx[26..34], &[10, 0, 0, 2, 10, 0, 0, 1]);
+    // ports
+    assert_eq!(&tx[34..38], &[0x9c, 0x40, 0x01, 0xf6]);
+    // sequence number counts bytes sent, acknowledgment number bytes received
+    assert_eq!(&rx[38..46], &[0, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(&tx[38..46], &[0, 0, 0, 0, 0, 0, 0, 8]);
+    assert_eq!(&tx[54..], b"request");
+    assert_eq!(&rx[54..], b"response");
+}
+
+#[test]
+fn uniconnect_capture_uses_tcp_link_for_tcp() {
+    let path = capture_path("uniconnect");
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let conn = tokio_uniconnect::UniConnect::from(
+        tokio::net::TcpStream::from_std(stream, &tokio::reactor::Handle::default()).unwrap(),
+    );
+
+    let capture = conn.with_pcap_capture(&path).unwrap();
+    capture.into_inner().unwrap();
+
+    let (datalink, records) = records(&path);
+    assert_eq!(datalink, DataLink::ETHERNET);
+    assert!(records.is_empty());
+}
+
+#[cfg(target_os = "linux")]
+#[test]
+fn capture_failure_does_not_fail_io() {
+    // every write to /dev/full fails with ENOSPC
+    let mut capture = PcapCapture::create(
+        Cursor::new(vec![0u8; 10_000]),
+        std::path::Path::new("/dev/full"),
+        CaptureLink::Raw,
+    )
+    .unwrap();
+
+    // records are larger than the file buffer, so recording fails at once
+    let mut buf = vec![0u8; 10_000];
+    assert_eq!(capture.read(&mut buf).unwrap(), 10_000);
+    assert_eq!(capture.write(&buf).unwrap(), 10_000);
+    capture.flush().unwrap();
+
+    let err = capture.flush_capture().unwrap_err();
+    // ENOSPC
+    assert_eq!(err.raw_os_error(), Some(28));
+    assert_eq!(capture.get_ref().get_ref().len(), 20_000);
+}
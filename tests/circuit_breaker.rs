@@ -0,0 +1,86 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::{future, FutureExt};
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retry::{is_circuit_open, CircuitBreaker, CircuitState};
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::time::Duration;
+
+fn poll_ready(rt: &mut Runtime, stream: &mut RetryingTcpStream) -> io::Result<()> {
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()).timeout(Duration::from_secs(5)))
+        .map(drop)
+        .map_err(|err| err.into_inner().expect("poll timed out"))
+}
+
+#[test]
+fn breaker_opens_after_threshold() {
+    let mut breaker = CircuitBreaker::new(3, Duration::from_secs(60), Duration::from_secs(60));
+    assert!(!breaker.record_failure());
+    assert!(!breaker.record_failure());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(breaker.record_failure());
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(breaker.open_until().is_some());
+
+    breaker.record_success();
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert_eq!(breaker.open_until(), None);
+}
+
+#[test]
+fn failures_outside_window_are_forgotten() {
+    let mut breaker = CircuitBreaker::new(2, Duration::from_millis(20), Duration::from_secs(60));
+    assert!(!breaker.record_failure());
+    std::thread::sleep(Duration::from_millis(40));
+    assert!(!breaker.record_failure());
+    assert_eq!(breaker.state(), CircuitState::Closed);
+}
+
+#[test]
+fn half_open_after_cool_down() {
+    let mut breaker = CircuitBreaker::new(1, Duration::from_secs(60), Duration::from_millis(20));
+    assert!(breaker.record_failure());
+    std::thread::sleep(Duration::from_millis(40));
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    // failed probe opens circuit again
+    assert!(breaker.record_failure());
+    assert_eq!(breaker.state(), CircuitState::Open);
+}
+
+#[test]
+fn stream_fails_fast_while_open_and_recovers() {
+    let mut rt = Runtime::new().unwrap();
+    // nobody listens after the listener is dropped
+    let addr: SocketAddr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let cool_down = Duration::from_millis(200);
+    let mut stream = RetryingTcpStream::connect(&addr).with_circuit_breaker(CircuitBreaker::new(
+        2,
+        Duration::from_secs(60),
+        cool_down,
+    ));
+    assert_eq!(stream.circuit_state(), CircuitState::Closed);
+
+    for _ in 0..2 {
+        let err = poll_ready(&mut rt, &mut stream).unwrap_err();
+        assert!(!is_circuit_open(&err), "{}", err);
+    }
+    assert_eq!(stream.circuit_state(), CircuitState::Open);
+    assert_eq!(stream.state_description(), "CircuitOpen");
+    let err = poll_ready(&mut rt, &mut stream).unwrap_err();
+    assert!(is_circuit_open(&err), "{}", err);
+    assert_eq!(err.kind(), io::ErrorKind::NotConnected);
+
+    // probe succeeds once server is back
+    let _listener = TcpListener::bind(addr).unwrap();
+    std::thread::sleep(cool_down);
+    assert_eq!(stream.circuit_state(), CircuitState::HalfOpen);
+    poll_ready(&mut rt, &mut stream).unwrap();
+    assert_eq!(stream.circuit_state(), CircuitState::Closed);
+    assert_eq!(stream.state_description(), "Connected");
+}
@@ -0,0 +1,132 @@
+#![cfg(all(
+    feature = "toml",
+    feature = "json",
+    feature = "serial",
+    feature = "retrying-tcp"
+))]
+
+use tokio_uniconnect::address::ConnectionAddress;
+use tokio_uniconnect::config::UniConnectConfig;
+
+use std::io::Write;
+use std::path::Path;
+
+#[test]
+fn example_configs_load() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/config");
+
+    let tcp = UniConnectConfig::from_toml_file(&dir.join("tcp.toml")).unwrap();
+    assert_eq!(
+        tcp.address,
+        "tcp://127.0.0.1:5000".parse::<ConnectionAddress>().unwrap()
+    );
+    assert!(tcp.tcp.is_some());
+
+    let serial = UniConnectConfig::from_toml_file(&dir.join("serial.toml")).unwrap();
+    assert_eq!(serial.serial.unwrap().baud_rate, 19200);
+
+    let serial = UniConnectConfig::from_json_file(&dir.join("serial.json")).unwrap();
+    assert_eq!(serial.serial.unwrap().baud_rate, 115200);
+}
+
+#[test]
+fn toml_error_reports_line() {
+    let path = std::env::temp_dir().join("tokio-uniconnect-invalid.toml");
+    let mut file = std::fs::File::create(&path).unwrap();
+    writeln!(
+        file,
+        "address = \"tcp://127.0.0.1:5000\"\n\n[serial]\nbaud_rate = \"fast\""
+    )
+    .unwrap();
+
+    let err = UniConnectConfig::from_toml_file(&path).unwrap_err();
+    assert_eq!(err.path(), path);
+    assert_eq!(err.line(), Some(4));
+}
+
+#[test]
+fn build_all_reports_every_config() {
+    use tokio_uniconnect::config::{build_all, build_all_or_fail};
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let tcp = UniConnectConfig {
+        address: ConnectionAddress::Tcp(listener.local_addr().unwrap()),
+        serial: None,
+        tcp: None,
+        use_retrying_serial: false,
+        retry: None,
+        priority: UniConnectConfig::DEFAULT_PRIORITY,
+    };
+    let missing_serial = UniConnectConfig {
+        address: ConnectionAddress::Serial("/dev/uniconnect-does-not-exist".into()),
+        ..tcp.clone()
+    };
+
+    let results = build_all(vec![tcp.clone(), missing_serial.clone(), tcp.clone()]);
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_ok());
+    assert!(results[1].is_err());
+    assert!(results[2].is_ok());
+
+    assert_eq!(
+        build_all_or_fail(vec![tcp.clone(), tcp.clone()])
+            .unwrap()
+            .len(),
+        2
+    );
+    assert!(build_all_or_fail(vec![tcp, missing_serial]).is_err());
+}
+
+#[test]
+fn config_keeps_retrying_serial_and_retry() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+    use tokio_uniconnect::retry::RetryConfig;
+    use tokio_uniconnect::UniConnect;
+
+    use std::time::Duration;
+
+    let retry = RetryConfig {
+        initial_delay: Duration::from_secs(1),
+        max_attempts: Some(5),
+        ..RetryConfig::default()
+    };
+    let mut builder =
+        RetryingTcpOrSerial::new(ConnectionAddress::Serial("/dev/uniconnect-missing".into()));
+    builder
+        .set_use_retrying_serial(true)
+        .set_retry_config(Some(retry.clone()));
+
+    let config = builder.to_config();
+    assert!(config.use_retrying_serial);
+    assert_eq!(config.retry, Some(retry));
+    // missing port is reopened in background instead of failing the build
+    assert!(matches!(
+        config.build().unwrap(),
+        UniConnect::RetryingSerial(_)
+    ));
+}
+
+#[test]
+fn toml_reads_retry_settings() {
+    let path = std::env::temp_dir().join("tokio-uniconnect-retry.toml");
+    let mut file = std::fs::File::create(&path).unwrap();
+    writeln!(
+        file,
+        "address = \"serial:///dev/ttyUSB0\"\nuse_retrying_serial = true\n\n[retry]\ninitial_delay = 2\nmax_attempts = 3"
+    )
+    .unwrap();
+
+    let config = UniConnectConfig::from_toml_file(&path).unwrap();
+    assert!(config.use_retrying_serial);
+    let retry = config.retry.unwrap();
+    assert_eq!(retry.initial_delay, std::time::Duration::from_secs(2));
+    assert_eq!(retry.max_attempts, Some(3));
+    assert_eq!(retry.multiplier, 2.0);
+
+    let config = UniConnectConfig::from_toml_file(
+        &Path::new(env!("CARGO_MANIFEST_DIR")).join("examples/config/serial.toml"),
+    )
+    .unwrap();
+    assert!(!config.use_retrying_serial);
+    assert!(config.retry.is_none());
+}
@@ -0,0 +1,88 @@
+#![cfg(feature = "config-reload")]
+
+use tokio::prelude::{future, AsyncRead, Future};
+use tokio::sync::watch;
+use tokio_uniconnect::builder::{ConnectionAddress, RetryingTcpOrSerial};
+use tokio_uniconnect::config::UniConnectConfig;
+
+use std::io::Read;
+use std::net::TcpListener;
+use std::thread;
+use std::time::{Duration, Instant};
+
+fn tcp_config(listener: &TcpListener) -> UniConnectConfig {
+    UniConnectConfig {
+        address: ConnectionAddress::Tcp(listener.local_addr().unwrap()),
+        serial: None,
+        tcp: None,
+        use_retrying_serial: false,
+        retry: None,
+        priority: UniConnectConfig::DEFAULT_PRIORITY,
+    }
+}
+
+#[test]
+fn reconnects_on_config_change() {
+    let first = TcpListener::bind("127.0.0.1:0").unwrap();
+    let second = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (mut tx, rx) = watch::channel(tcp_config(&first));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let builder = RetryingTcpOrSerial::new(tcp_config(&first).address);
+    let conn = rt
+        .block_on(future::lazy(move || builder.with_config_watch(rx)))
+        .unwrap();
+    assert_eq!(conn.current_config(), tcp_config(&first));
+
+    let (conn, _) = rt.block_on(tokio::io::write_all(conn, b"one")).unwrap();
+    let mut buf = [0u8; 3];
+    first.accept().unwrap().0.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"one");
+
+    tx.broadcast(tcp_config(&second)).unwrap();
+    let conn = rt
+        .block_on(tokio::io::write_all(conn, b"two").and_then(|(conn, _)| tokio::io::flush(conn)))
+        .unwrap();
+    assert_eq!(conn.current_config(), tcp_config(&second));
+    second.accept().unwrap().0.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"two");
+}
+
+fn accept_within(listener: &TcpListener, timeout: Duration) -> bool {
+    listener.set_nonblocking(true).unwrap();
+    let deadline = Instant::now() + timeout;
+    while Instant::now() < deadline {
+        if listener.accept().is_ok() {
+            return true;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn pending_read_follows_every_config_change() {
+    let listeners: Vec<_> = (0..3)
+        .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+        .collect();
+    let (mut tx, rx) = watch::channel(tcp_config(&listeners[0]));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let builder = RetryingTcpOrSerial::new(tcp_config(&listeners[0]).address);
+    let mut conn = rt
+        .block_on(future::lazy(move || builder.with_config_watch(rx)))
+        .unwrap();
+    // nothing is ever sent, so the read stays pending and only config changes wake it
+    rt.spawn(future::poll_fn(move || {
+        let mut buf = [0u8; 8];
+        conn.poll_read(&mut buf)
+            .map(|ready| ready.map(|_| ()))
+            .map_err(|_| ())
+    }));
+    assert!(accept_within(&listeners[0], Duration::from_secs(5)));
+
+    for listener in &listeners[1..] {
+        tx.broadcast(tcp_config(listener)).unwrap();
+        assert!(accept_within(listener, Duration::from_secs(5)));
+    }
+}
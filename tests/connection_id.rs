@@ -0,0 +1,49 @@
+#![cfg(feature = "retrying-tcp")]
+
+use log::{Log, Metadata, Record};
+use tokio::reactor::Handle;
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::Mutex;
+
+// Messages of all log records, installed once for this test binary
+struct Captured(Mutex<Vec<String>>);
+
+impl Log for Captured {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        self.0.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: Captured = Captured(Mutex::new(Vec::new()));
+
+fn connected(listener: &TcpListener) -> RetryingTcpStream {
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    RetryingTcpStream::from_std(stream, &Handle::default()).unwrap()
+}
+
+#[test]
+fn every_stream_has_own_id_in_its_logs() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut first = connected(&listener);
+    let second = connected(&listener);
+    assert_ne!(first.connection_id(), second.connection_id());
+    assert_eq!(first.connection_id().get_version_num(), 4);
+
+    first.force_reconnect();
+    let tag = format!("RetryingTcpStream[{}]", first.connection_id());
+    let logs = LOGGER.0.lock().unwrap();
+    assert!(logs.iter().any(|line| line.starts_with(&tag)), "{:?}", logs);
+    let other = second.connection_id().to_string();
+    assert!(!logs.iter().any(|line| line.contains(&other)));
+}
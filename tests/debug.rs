@@ -0,0 +1,234 @@
+#![cfg(feature = "tracing")]
+
+use tokio_uniconnect::debug::{hex_dump, HexDumpConn};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+use std::fmt;
+use std::io::{Cursor, Read, Write};
+use std::sync::{Arc, Mutex};
+
+// `(direction, offset, len)` of every event
+#[derive(Clone, Default)]
+struct DumpEvents(Arc<Mutex<Vec<(String, u64, u64)>>>);
+
+#[derive(Default)]
+struct DumpVisitor {
+    direction: String,
+    offset: u64,
+    len: u64,
+}
+
+impl Visit for DumpVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "direction" {
+            self.direction = value.to_string();
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "offset" => self.offset = value,
+            "len" => self.len = value,
+            _ => {}
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn fmt::Debug) {}
+}
+
+impl Subscriber for DumpEvents {
+    fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _span: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _span: &Id, _values: &Record<'_>) {}
+
+    fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = DumpVisitor::default();
+        event.record(&mut visitor);
+        self.0
+            .lock()
+            .unwrap()
+            .push((visitor.direction, visitor.offset, visitor.len));
+    }
+
+    fn enter(&self, _span: &Id) {}
+
+    fn exit(&self, _span: &Id) {}
+}
+
+#[test]
+fn hex_dump_format() {
+    assert_eq!(
+        hex_dump(b"Hello, world!\r\n"),
+        "0000  48 65 6c 6c 6f 2c 20 77  6f 72 6c 64 21 0d 0a     Hello, w orld!.."
+    );
+    let dump = hex_dump(&[0u8; 17]);
+    let lines: Vec<_> = dump.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with("0010  00 "));
+    assert!(lines[1].ends_with(" ."));
+}
+
+#[test]
+fn hex_dump_conn_traces_offsets_per_direction() {
+    let events = DumpEvents::default();
+    let mut conn = HexDumpConn::new(Cursor::new(b"abcdef".to_vec()));
+
+    tracing::subscriber::with_default(events.clone(), || {
+        let mut buf = [0u8; 4];
+        assert_eq!(conn.read(&mut buf).unwrap(), 4);
+        assert_eq!(conn.read(&mut buf).unwrap(), 2);
+        // nothing is traced for EOF
+        assert_eq!(conn.read(&mut buf).unwrap(), 0);
+        conn.write_all(b"xyz").unwrap();
+    });
+
+    assert_eq!(
+        *events.0.lock().unwrap(),
+        vec![
+            ("RX".to_string(), 0, 4),
+            ("RX".to_string(), 4, 2),
+            ("TX".to_string(), 0, 3),
+        ]
+    );
+    assert_eq!(conn.into_inner().into_inner(), b"abcdefxyz");
+}
+
+#[cfg(feature = "tokio-console")]
+mod instrumented {
+    use tokio_uniconnect::debug::InstrumentedConn;
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+
+    use std::fmt;
+    use std::io::{self, Cursor, Read, Write};
+    use std::sync::{Arc, Mutex};
+
+    #[derive(Clone, Debug, Default, PartialEq)]
+    struct PollSpan {
+        conn: String,
+        op: String,
+        bytes: Option<u64>,
+        ready: Option<bool>,
+        timed: bool,
+    }
+
+    impl Visit for PollSpan {
+        fn record_str(&mut self, field: &Field, value: &str) {
+            if field.name() == "op" {
+                self.op = value.to_string();
+            }
+        }
+
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            match field.name() {
+                "bytes" => self.bytes = Some(value),
+                "duration_us" => self.timed = true,
+                _ => {}
+            }
+        }
+
+        fn record_bool(&mut self, field: &Field, value: bool) {
+            if field.name() == "ready" {
+                self.ready = Some(value);
+            }
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            if field.name() == "conn" {
+                self.conn = format!("{:?}", value);
+            }
+        }
+    }
+
+    // Fields of every span, span ID is index + 1
+    #[derive(Clone, Default)]
+    struct PollSpans(Arc<Mutex<Vec<PollSpan>>>);
+
+    impl Subscriber for PollSpans {
+        fn enabled(&self, _metadata: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = PollSpan::default();
+            span.record(&mut fields);
+            let mut spans = self.0.lock().unwrap();
+            spans.push(fields);
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.0.lock().unwrap();
+            values.record(&mut spans[span.into_u64() as usize - 1]);
+        }
+
+        fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+
+        fn event(&self, _event: &Event<'_>) {}
+
+        fn enter(&self, _span: &Id) {}
+
+        fn exit(&self, _span: &Id) {}
+    }
+
+    struct Blocked;
+
+    impl Read for Blocked {
+        fn read(&mut self, _buf: &mut [u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::WouldBlock.into())
+        }
+    }
+
+    fn span(op: &str, bytes: u64, ready: bool) -> PollSpan {
+        PollSpan {
+            conn: "plc".into(),
+            op: op.into(),
+            bytes: Some(bytes),
+            ready: Some(ready),
+            timed: true,
+        }
+    }
+
+    #[test]
+    fn every_poll_is_recorded_as_span() {
+        let spans = PollSpans::default();
+        let mut conn = InstrumentedConn::new(Cursor::new(b"abc".to_vec()), "plc");
+        let mut blocked = InstrumentedConn::new(Blocked, "plc");
+
+        tracing::subscriber::with_default(spans.clone(), || {
+            assert_eq!(conn.read(&mut [0u8; 8]).unwrap(), 3);
+            assert_eq!(conn.write(b"de").unwrap(), 2);
+            conn.flush().unwrap();
+            blocked.read(&mut [0u8; 8]).unwrap_err();
+        });
+
+        assert_eq!(
+            *spans.0.lock().unwrap(),
+            vec![
+                span("read", 3, true),
+                span("write", 2, true),
+                span("flush", 0, true),
+                span("read", 0, false),
+            ]
+        );
+        assert_eq!(conn.name(), "plc");
+    }
+
+    #[test]
+    fn uniconnect_instrumented_keeps_name() {
+        let conn = tokio_uniconnect::udp::connect_udp(&"127.0.0.1:9".parse().unwrap()).unwrap();
+        let conn = conn.instrumented("udp");
+        assert_eq!(conn.name(), "udp");
+    }
+}
@@ -0,0 +1,74 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::resolver::{AddressResolver, ResolveFuture};
+use tokio_uniconnect::retrying_tcp_stream::{
+    DnsRotatingAddr, RetryingTcpStream, TcpStreamSettings,
+};
+
+use std::io;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+
+// Return the queued answers one by one, then fail
+struct ScriptedResolver(Mutex<Vec<Vec<SocketAddr>>>);
+
+impl AddressResolver for ScriptedResolver {
+    fn resolve(&self, _hostname: &str, _port: u16) -> ResolveFuture {
+        let mut answers = self.0.lock().unwrap();
+        let res = if answers.is_empty() {
+            Err(io::Error::other("DNS server unreachable"))
+        } else {
+            Ok(answers.remove(0))
+        };
+        Box::new(future::result(res))
+    }
+}
+
+fn connect(answers: Vec<Vec<SocketAddr>>) -> RetryingTcpStream {
+    let peer = DnsRotatingAddr {
+        hostname: "service.local".into(),
+        port: 502,
+        resolver: Arc::new(ScriptedResolver(Mutex::new(answers))),
+    };
+    RetryingTcpStream::connect_to(peer, TcpStreamSettings::default())
+}
+
+fn connected_to(rt: &mut Runtime, stream: &mut RetryingTcpStream) -> SocketAddr {
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+    stream.peer_addr().unwrap()
+}
+
+#[test]
+fn reconnect_uses_next_resolved_address() {
+    let mut rt = Runtime::new().unwrap();
+    let listeners: Vec<_> = (0..2)
+        .map(|_| TcpListener::bind("127.0.0.1:0").unwrap())
+        .collect();
+    let addrs: Vec<_> = listeners
+        .iter()
+        .map(|listener| listener.local_addr().unwrap())
+        .collect();
+    let mut stream = connect(vec![addrs.clone(); 3]);
+
+    assert_eq!(connected_to(&mut rt, &mut stream), addrs[0]);
+    stream.force_reconnect();
+    assert_eq!(connected_to(&mut rt, &mut stream), addrs[1]);
+    stream.force_reconnect();
+    assert_eq!(connected_to(&mut rt, &mut stream), addrs[0]);
+}
+
+#[test]
+fn failed_resolution_falls_back_to_last_connected_address() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut stream = connect(vec![vec![addr]]);
+
+    assert_eq!(connected_to(&mut rt, &mut stream), addr);
+    stream.force_reconnect();
+    assert_eq!(connected_to(&mut rt, &mut stream), addr);
+    assert_eq!(stream.reconnect_count(), 1);
+}
@@ -0,0 +1,40 @@
+use tokio::net::TcpStream;
+use tokio::prelude::Future;
+use tokio::reactor::Handle;
+use tokio_uniconnect::dynamic::DynUniConnect;
+use tokio_uniconnect::testing::EchoConn;
+use tokio_uniconnect::UniConnect;
+
+// Application type free of the connection type
+struct Client {
+    conn: DynUniConnect,
+}
+
+fn round_trip(client: Client) -> Vec<u8> {
+    let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let exchange = tokio::io::write_all(client.conn, b"ping")
+        .and_then(|(conn, _)| tokio::io::read_exact(conn, [0u8; 4]))
+        .map(|(_, buf)| buf.to_vec());
+    rt.block_on(exchange).unwrap()
+}
+
+#[test]
+fn uniconnect_round_trip() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    std::thread::spawn(move || {
+        let (mut read, mut write) = (server.try_clone().unwrap(), server);
+        std::io::copy(&mut read, &mut write).unwrap();
+    });
+    let client = TcpStream::from_std(client, &Handle::default()).unwrap();
+
+    let conn: DynUniConnect = UniConnect::from(client).into();
+    assert_eq!(round_trip(Client { conn }), b"ping");
+}
+
+#[test]
+fn fake_connection_round_trip() {
+    let conn = DynUniConnect::new(EchoConn::new());
+    assert_eq!(round_trip(Client { conn }), b"ping");
+}
@@ -0,0 +1,61 @@
+use tokio::prelude::{future, Async, Future};
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::testing::EchoConn;
+
+use std::io::{ErrorKind, Write};
+use std::time::{Duration, Instant};
+
+#[test]
+fn written_data_is_read_back_until_eof() {
+    let mut rt = Runtime::new().unwrap();
+    let (conn, _) = rt
+        .block_on(tokio::io::write_all(EchoConn::new(), b"ping"))
+        .unwrap();
+    assert_eq!(conn.pending_len(), 4);
+    let conn = rt.block_on(tokio::io::shutdown(conn)).unwrap();
+
+    let (mut conn, data) = rt
+        .block_on(tokio::io::read_to_end(conn, Vec::new()))
+        .unwrap();
+    assert_eq!(data, b"ping");
+    assert_eq!(
+        conn.write(b"late").unwrap_err().kind(),
+        ErrorKind::BrokenPipe
+    );
+}
+
+#[test]
+fn pending_read_is_woken_by_write_of_the_same_task() {
+    let mut rt = Runtime::new().unwrap();
+    let mut conn = EchoConn::new();
+    let mut written = false;
+    let read = future::poll_fn(move || {
+        let mut buf = [0u8; 4];
+        match std::io::Read::read(&mut conn, &mut buf) {
+            Ok(n) => Ok(Async::Ready(buf[..n].to_vec())),
+            Err(err) if err.kind() == ErrorKind::WouldBlock && !written => {
+                written = true;
+                conn.write_all(b"pong")?;
+                Ok(Async::NotReady)
+            }
+            Err(err) => Err(err),
+        }
+    });
+    assert_eq!(rt.block_on(read).unwrap(), b"pong");
+}
+
+#[test]
+fn delay_holds_data_back() {
+    let mut rt = Runtime::new().unwrap();
+    let delay = Duration::from_millis(100);
+    let start = Instant::now();
+    let data = rt
+        .block_on(
+            tokio::io::write_all(EchoConn::with_delay(delay), b"slow")
+                .and_then(|(conn, _)| tokio::io::read_exact(conn, [0u8; 4])),
+        )
+        .unwrap()
+        .1;
+    assert_eq!(&data, b"slow");
+    assert!(start.elapsed() >= delay);
+}
@@ -0,0 +1,78 @@
+#![cfg(all(feature = "serial", feature = "retrying-tcp"))]
+
+use tokio_uniconnect::builder::{EnvConfigError, RetryingTcpOrSerial};
+
+use std::env;
+
+// every test uses its own prefix, tests run in parallel
+#[test]
+fn missing_address() {
+    env::set_var("ENV_MISSING_BAUD", "9600");
+    assert_eq!(
+        RetryingTcpOrSerial::from_env_prefix("ENV_MISSING").err(),
+        Some(EnvConfigError::Missing("ENV_MISSING_ADDR".into()))
+    );
+}
+
+#[test]
+fn malformed_values() {
+    env::set_var("ENV_MALFORMED_ADDR", "/dev/ttyUSB0");
+    env::set_var("ENV_MALFORMED_PARITY", "mark");
+    match RetryingTcpOrSerial::from_env_prefix("ENV_MALFORMED") {
+        Err(EnvConfigError::Malformed { name, value, .. }) => {
+            assert_eq!(name, "ENV_MALFORMED_PARITY");
+            assert_eq!(value, "mark");
+        }
+        res => panic!("expected Malformed, got {:?}", res.err()),
+    }
+
+    env::set_var("ENV_MALFORMED_PARITY", "even");
+    env::set_var("ENV_MALFORMED_BAUD", "12345");
+    let err = RetryingTcpOrSerial::from_env_prefix("ENV_MALFORMED")
+        .err()
+        .unwrap();
+    assert!(err
+        .to_string()
+        .starts_with("invalid ENV_MALFORMED_BAUD=`12345`"));
+}
+
+#[test]
+fn builds_tcp_connection() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    env::set_var("ENV_TCP_ADDR", format!("tcp://{}", addr));
+    env::set_var("ENV_TCP_NODELAY", "1");
+    env::set_var("ENV_TCP_CONNECT_TIMEOUT_MS", "500");
+
+    let conn = RetryingTcpOrSerial::from_env_prefix("ENV_TCP")
+        .unwrap()
+        .build()
+        .unwrap();
+    assert_eq!(conn.peer_addr().unwrap(), addr);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn reads_serial_settings() {
+    use std::time::Duration;
+    use tokio_uniconnect::builder::{DataBits, Parity, SerialPortSettings, StopBits};
+
+    env::set_var("ENV_SERIAL_ADDR", "serial:///dev/ttyUSB0");
+    env::set_var("ENV_SERIAL_BAUD", "19200");
+    env::set_var("ENV_SERIAL_DATA_BITS", "7");
+    env::set_var("ENV_SERIAL_PARITY", "Even");
+    env::set_var("ENV_SERIAL_STOP_BITS", "2");
+    env::set_var("ENV_SERIAL_TIMEOUT_MS", "250");
+
+    let config = RetryingTcpOrSerial::from_env_prefix("ENV_SERIAL")
+        .unwrap()
+        .to_config();
+    assert_eq!(config.address.to_string(), "serial:///dev/ttyUSB0");
+    assert_eq!(config.tcp, None);
+    let settings = SerialPortSettings::from(config.serial.unwrap());
+    assert_eq!(settings.baud_rate, 19200);
+    assert_eq!(settings.data_bits, DataBits::Seven);
+    assert_eq!(settings.parity, Parity::Even);
+    assert_eq!(settings.stop_bits, StopBits::Two);
+    assert_eq!(settings.timeout, Duration::from_millis(250));
+}
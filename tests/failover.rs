@@ -0,0 +1,80 @@
+#![cfg(all(feature = "serde", feature = "serial", feature = "retrying-tcp"))]
+
+use tokio::prelude::Future;
+use tokio_uniconnect::builder::ConnectionAddress;
+use tokio_uniconnect::config::UniConnectConfig;
+use tokio_uniconnect::failover::{SwitchEvent, WeightedFailoverPool};
+
+use std::io::Read;
+use std::net::{SocketAddr, TcpListener};
+use std::time::{Duration, Instant};
+
+fn config(addr: SocketAddr, priority: u8) -> UniConnectConfig {
+    UniConnectConfig {
+        address: ConnectionAddress::Tcp(addr),
+        serial: None,
+        tcp: None,
+        use_retrying_serial: false,
+        retry: None,
+        priority,
+    }
+}
+
+#[test]
+fn switches_back_to_recovered_primary() {
+    // primary is down at start
+    let primary_addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let secondary = TcpListener::bind("127.0.0.1:0").unwrap();
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let mut pool = WeightedFailoverPool::new(vec![
+        config(secondary.local_addr().unwrap(), 200),
+        config(primary_addr, 10),
+    ]);
+    let switch_events = pool.switch_events();
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pool.active_priority() != Some(200) {
+        assert!(Instant::now() < deadline, "secondary not connected");
+        pool = rt
+            .block_on(tokio::io::write_all(pool, b"s").map(|(pool, _)| pool))
+            .unwrap();
+    }
+    let mut buf = [0u8; 1];
+    let (mut secondary_conn, _) = secondary.accept().unwrap();
+    secondary_conn.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"s");
+    assert_eq!(
+        switch_events.get_ref().clone(),
+        SwitchEvent {
+            from: None,
+            to: Some(200)
+        }
+    );
+
+    let primary = TcpListener::bind(primary_addr).unwrap();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pool.active_priority() != Some(10) {
+        assert!(Instant::now() < deadline, "did not switch to primary");
+        std::thread::sleep(Duration::from_millis(50));
+        pool = rt
+            .block_on(tokio::io::write_all(pool, b"s").map(|(pool, _)| pool))
+            .unwrap();
+    }
+    assert_eq!(
+        switch_events.get_ref().clone(),
+        SwitchEvent {
+            from: Some(200),
+            to: Some(10)
+        }
+    );
+
+    rt.block_on(tokio::io::write_all(pool, b"p")).unwrap();
+    // write that noticed the switch already went to primary
+    let mut buf = [0u8; 2];
+    primary.accept().unwrap().0.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"sp");
+}
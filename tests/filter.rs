@@ -0,0 +1,95 @@
+use tokio_uniconnect::filter::{ConnFilter, Filtered};
+
+use std::borrow::Cow;
+use std::io::{self, Cursor, Read, Write};
+
+// Flip case of ASCII letters both ways, drop lines starting with `#` on read
+struct CaseFlip;
+
+impl ConnFilter for CaseFlip {
+    fn on_read(&mut self, buf: &mut [u8], n: usize) -> io::Result<usize> {
+        if buf[0] == b'#' {
+            return Ok(0);
+        }
+        buf[..n].iter_mut().for_each(|b| *b ^= 0x20);
+        Ok(n)
+    }
+
+    fn on_write<'a>(&mut self, buf: &'a [u8]) -> io::Result<Cow<'a, [u8]>> {
+        Ok(buf.iter().map(|b| b ^ 0x20).collect::<Vec<_>>().into())
+    }
+}
+
+// Accept at most `limit` bytes per write, `WouldBlock` when `limit` is 0
+struct Throttled {
+    written: Vec<u8>,
+    limit: usize,
+}
+
+impl Write for Throttled {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.limit == 0 {
+            return Err(io::ErrorKind::WouldBlock.into());
+        }
+        let n = buf.len().min(self.limit);
+        self.written.extend_from_slice(&buf[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+// Return one chunk per read
+struct Chunks(Vec<&'static [u8]>);
+
+impl Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.0.is_empty() {
+            return Ok(0);
+        }
+        let chunk = self.0.remove(0);
+        buf[..chunk.len()].copy_from_slice(chunk);
+        Ok(chunk.len())
+    }
+}
+
+#[test]
+fn read_is_transformed_and_swallowed_chunks_skipped() {
+    let mut conn = Filtered::new(Chunks(vec![b"#comment", b"ABC"]), CaseFlip);
+    let mut buf = [0u8; 16];
+    assert_eq!(conn.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf[..3], b"abc");
+    assert_eq!(conn.read(&mut buf).unwrap(), 0);
+}
+
+#[test]
+fn write_is_transformed() {
+    let mut conn = Filtered::new(Cursor::new(Vec::new()), CaseFlip);
+    conn.write_all(b"Hello").unwrap();
+    conn.flush().unwrap();
+    assert_eq!(conn.into_inner().0.into_inner(), b"hELLO");
+}
+
+#[test]
+fn output_not_accepted_by_inner_is_written_later() {
+    let inner = Throttled {
+        written: Vec::new(),
+        limit: 2,
+    };
+    let mut conn = Filtered::new(inner, CaseFlip);
+
+    // whole input is consumed even if inner takes only a part of it
+    assert_eq!(conn.write(b"abcde").unwrap(), 5);
+    assert_eq!(conn.get_ref().written, b"AB");
+
+    conn.get_mut().limit = 0;
+    let err = conn.flush().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    conn.get_mut().limit = 2;
+    assert_eq!(conn.write(b"f").unwrap(), 1);
+    conn.flush().unwrap();
+    assert_eq!(conn.get_ref().written, b"ABCDEF");
+}
@@ -0,0 +1,62 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::Future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::{RetryingTcpStream, TcpStreamSettings};
+
+use std::io::{ErrorKind, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+fn connect_to(listener: &TcpListener) -> RetryingTcpStream {
+    let mut settings = TcpStreamSettings::default();
+    settings.set_first_byte_timeout(Some(Duration::from_millis(100)));
+    RetryingTcpStream::connect_with_settings(&listener.local_addr().unwrap(), settings)
+}
+
+#[test]
+fn silent_peer_times_out_and_reconnects() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = connect_to(&listener);
+    let server = thread::spawn(move || {
+        let (silent, _) = listener.accept().unwrap();
+        let (mut peer, _) = listener.accept().unwrap();
+        peer.write_all(b"ok").unwrap();
+        drop(silent);
+    });
+
+    let err = rt
+        .block_on(tokio::io::read_exact(&mut stream, [0u8; 2]).map(drop))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TimedOut);
+
+    let (_, data) = rt
+        .block_on(tokio::io::read_exact(&mut stream, [0u8; 2]))
+        .unwrap();
+    assert_eq!(&data, b"ok");
+    assert_eq!(stream.reconnect_count(), 1);
+    server.join().unwrap();
+}
+
+#[test]
+fn timeout_ends_with_first_byte() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = connect_to(&listener);
+    let server = thread::spawn(move || {
+        let (mut peer, _) = listener.accept().unwrap();
+        peer.write_all(b"a").unwrap();
+        // later data may take longer than first byte timeout
+        thread::sleep(Duration::from_millis(300));
+        peer.write_all(b"b").unwrap();
+    });
+
+    let (stream, data) = rt
+        .block_on(tokio::io::read_exact(stream, [0u8; 2]))
+        .unwrap();
+    assert_eq!(&data, b"ab");
+    assert_eq!(stream.reconnect_count(), 0);
+    server.join().unwrap();
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "futures-io")]
+
+use futures03::executor::block_on;
+use futures03::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::prelude::Future;
+use tokio_uniconnect::UniConnect;
+
+use std::io::{Read, Write};
+
+#[test]
+fn round_trip_through_futures_io() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4];
+        stream.read_exact(&mut request).unwrap();
+        stream.write_all(b"pong").unwrap();
+        request
+    });
+
+    let stream = tokio::net::TcpStream::connect(&addr).wait().unwrap();
+    let mut conn = UniConnect::from(stream).into_futures_io();
+    let response = block_on(async {
+        conn.write_all(b"ping").await?;
+        conn.flush().await?;
+        let mut response = [0u8; 4];
+        conn.read_exact(&mut response).await?;
+        Ok::<_, std::io::Error>(response)
+    })
+    .unwrap();
+
+    assert_eq!(&response, b"pong");
+    assert_eq!(&server.join().unwrap(), b"ping");
+    let _conn: UniConnect = conn.into_uni_connect();
+}
+
+#[cfg(feature = "async-std")]
+#[test]
+fn uni_connect_implements_futures_io() {
+    static_assertions::assert_impl_all!(
+        UniConnect: futures03::io::AsyncRead,
+        futures03::io::AsyncWrite,
+        Unpin
+    );
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4];
+        stream.read_exact(&mut request).unwrap();
+        // reply after a while so the read has to wait for readiness
+        std::thread::sleep(std::time::Duration::from_millis(50));
+        stream.write_all(b"pong").unwrap();
+        request
+    });
+
+    let stream = tokio::net::TcpStream::connect(&addr).wait().unwrap();
+    let mut conn = UniConnect::from(stream);
+    // std::io traits are implemented too, methods need disambiguation
+    let response = block_on(async {
+        AsyncWriteExt::write_all(&mut conn, b"ping").await?;
+        AsyncWriteExt::flush(&mut conn).await?;
+        let mut response = [0u8; 4];
+        AsyncReadExt::read_exact(&mut conn, &mut response).await?;
+        AsyncWriteExt::close(&mut conn).await?;
+        Ok::<_, std::io::Error>(response)
+    })
+    .unwrap();
+
+    assert_eq!(&response, b"pong");
+    assert_eq!(&server.join().unwrap(), b"ping");
+}
+
+#[cfg(feature = "async-std")]
+#[test]
+fn async_std_wrapper_round_trip() {
+    use tokio_uniconnect::compat::AsyncStdUniConnect;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = std::thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut request = [0u8; 4];
+        stream.read_exact(&mut request).unwrap();
+        stream.write_all(b"pong").unwrap();
+        request
+    });
+
+    let stream = tokio::net::TcpStream::connect(&addr).wait().unwrap();
+    let mut conn = AsyncStdUniConnect::from(UniConnect::from(stream));
+    let response = block_on(async {
+        conn.write_all(b"ping").await?;
+        conn.flush().await?;
+        let mut response = [0u8; 4];
+        conn.read_exact(&mut response).await?;
+        Ok::<_, std::io::Error>(response)
+    })
+    .unwrap();
+
+    assert_eq!(&response, b"pong");
+    assert_eq!(&server.join().unwrap(), b"ping");
+    let conn = UniConnect::from(conn);
+    assert!(conn.is_connected());
+}
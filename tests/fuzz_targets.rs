@@ -0,0 +1,22 @@
+#![cfg(feature = "fuzz")]
+
+use tokio_uniconnect::fuzz;
+
+// Valid frames, truncated frames and garbage, must not panic
+const INPUTS: &[&[u8]] = &[
+    b"",
+    b"$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n",
+    b"$GPGGA,123519*4",
+    b"\x00\x55\xc1\x01\x02\x03\x04",
+    b"\x00\x00\x00\x05\x94\xa1a\xc4\x00\xc0\x90",
+    b"\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+];
+
+#[test]
+fn targets_accept_seed_inputs() {
+    for input in INPUTS {
+        fuzz::lin(input);
+        fuzz::nmea(input);
+        fuzz::msgpack(input);
+    }
+}
@@ -0,0 +1,51 @@
+use tokio_uniconnect::retry::ReconnectGovernor;
+
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[test]
+fn reconnects_over_limit_go_to_next_windows() {
+    let window = Duration::from_secs(10);
+    let governor = ReconnectGovernor::new(2, window);
+    let now = Instant::now();
+
+    let slots: Vec<_> = (0..5).map(|_| governor.reserve()).collect();
+    // both permits of the first window are granted right away
+    assert!(slots[0] >= now);
+    assert!(slots[1] >= slots[0]);
+    assert!(slots[1] < slots[0] + Duration::from_secs(1));
+    assert_eq!(slots[2], slots[0] + window);
+    assert_eq!(slots[3], slots[0] + window);
+    assert_eq!(slots[4], slots[0] + window * 2);
+}
+
+#[test]
+fn idle_governor_starts_new_window() {
+    let window = Duration::from_millis(50);
+    let governor = ReconnectGovernor::new(1, window);
+    let first = governor.reserve();
+    let second = governor.reserve();
+    assert_eq!(second, first + window);
+
+    // both reserved windows are over
+    thread::sleep(window * 2 + Duration::from_millis(10));
+    let now = Instant::now();
+    let third = governor.reserve();
+    assert!(third >= now);
+    assert!(third < now + window);
+}
+
+#[test]
+#[should_panic(expected = "governor needs at least one permit")]
+fn zero_permits_panics() {
+    ReconnectGovernor::new(0, Duration::from_secs(1));
+}
+
+#[test]
+fn global_governor_is_shared() {
+    let global = ReconnectGovernor::global();
+    assert!(Arc::ptr_eq(&global, &ReconnectGovernor::global()));
+    assert_eq!(global.permits_per_window(), 50);
+    assert_eq!(global.window(), Duration::from_secs(1));
+}
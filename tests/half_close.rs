@@ -0,0 +1,66 @@
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::UniConnect;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+fn connected(listener: &TcpListener) -> (UniConnect, TcpStream) {
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (peer, _) = listener.accept().unwrap();
+    let stream = tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap();
+    (UniConnect::from(stream), peer)
+}
+
+#[test]
+fn peer_sees_eof_and_can_still_respond() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (conn, mut peer) = connected(&listener);
+
+    let (mut conn, _) = rt.block_on(tokio::io::write_all(conn, b"request")).unwrap();
+    conn.shutdown_write().unwrap();
+
+    let mut request = Vec::new();
+    peer.read_to_end(&mut request).unwrap();
+    assert_eq!(request, b"request");
+    peer.write_all(b"response").unwrap();
+    drop(peer);
+
+    let (_, response) = rt
+        .block_on(tokio::io::read_to_end(conn, Vec::new()))
+        .unwrap();
+    assert_eq!(response, b"response");
+}
+
+#[test]
+fn udp_has_no_write_half() {
+    let mut conn = tokio_uniconnect::udp::connect_udp(&"127.0.0.1:9".parse().unwrap()).unwrap();
+    let err = conn.shutdown_write().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn retrying_stream_half_close() {
+    use tokio::prelude::future;
+    use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = RetryingTcpStream::connect(&listener.local_addr().unwrap());
+
+    let err = stream.shutdown_write().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+    let mut conn = UniConnect::from(stream);
+    conn.shutdown_write().unwrap();
+    assert_eq!(peer.read(&mut [0u8; 1]).unwrap(), 0);
+
+    peer.write_all(b"still open").unwrap();
+    let (_, data) = rt.block_on(tokio::io::read_exact(conn, [0u8; 10])).unwrap();
+    assert_eq!(&data, b"still open");
+}
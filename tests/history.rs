@@ -0,0 +1,61 @@
+#![cfg(feature = "history")]
+
+use tokio_uniconnect::history::{ConnectionHistory, HistoryEventKind};
+
+#[test]
+fn full_history_drops_oldest_event() {
+    let mut history = ConnectionHistory::new(2);
+    history.push(HistoryEventKind::Connected, "first");
+    history.push(HistoryEventKind::Error, "second");
+    history.push(HistoryEventKind::Reconnecting, "third");
+
+    let details: Vec<_> = history.iter().map(|event| event.detail.as_str()).collect();
+    assert_eq!(details, ["second", "third"]);
+    assert_eq!(history.last().unwrap().kind, HistoryEventKind::Reconnecting);
+    assert!(history.iter().next().unwrap().timestamp <= history.last().unwrap().timestamp);
+
+    history.clear();
+    assert!(history.is_empty());
+    assert_eq!(history.max_len(), 2);
+}
+
+#[test]
+fn zero_length_history_keeps_nothing() {
+    let mut history = ConnectionHistory::new(0);
+    history.push(HistoryEventKind::Connected, "ignored");
+    assert!(history.is_empty());
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn retrying_stream_records_reconnect() {
+    use tokio::prelude::future;
+    use tokio::runtime::current_thread::Runtime;
+    use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+    let mut rt = Runtime::new().unwrap();
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut stream = RetryingTcpStream::connect(&addr).with_history_len(8);
+
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+    stream.force_reconnect();
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+
+    let kinds: Vec<_> = stream.history().iter().map(|event| event.kind).collect();
+    assert_eq!(
+        kinds,
+        [
+            HistoryEventKind::Connected,
+            HistoryEventKind::Disconnected,
+            HistoryEventKind::Reconnecting,
+            HistoryEventKind::Connected,
+        ]
+    );
+    assert_eq!(stream.history().last().unwrap().detail, addr.to_string());
+
+    stream.clear_history();
+    assert!(stream.history().is_empty());
+}
@@ -0,0 +1,128 @@
+use tokio_uniconnect::address::ConnectionAddress;
+use tokio_uniconnect::resolver::{resolve_first_blocking, ResolveError};
+
+fn host(hostname: &str, port: u16) -> ConnectionAddress {
+    ConnectionAddress::TcpHost {
+        hostname: hostname.into(),
+        port,
+    }
+}
+
+#[test]
+fn detect_host_and_port() {
+    assert_eq!(
+        "myhost.local:8080".parse::<ConnectionAddress>().unwrap(),
+        host("myhost.local", 8080)
+    );
+    let address: ConnectionAddress = "tcp://myhost.local:8080".parse().unwrap();
+    assert_eq!(address, host("myhost.local", 8080));
+    assert_eq!(address.to_string(), "tcp://myhost.local:8080");
+    // socket address literals are not hostnames
+    assert_eq!(
+        "[::1]:502".parse::<ConnectionAddress>().unwrap(),
+        ConnectionAddress::Tcp("[::1]:502".parse().unwrap())
+    );
+    assert!("tcp://myhost.local".parse::<ConnectionAddress>().is_err());
+}
+
+#[test]
+fn serial_paths_with_colon_are_not_hostnames() {
+    let path = "/dev/serial/by-path/pci-0000:00:14.0-usb-0:1";
+    assert_eq!(
+        path.parse::<ConnectionAddress>().unwrap(),
+        ConnectionAddress::Serial(path.into())
+    );
+}
+
+#[test]
+fn resolve_localhost() {
+    let addr = resolve_first_blocking("localhost", 502).unwrap();
+    assert!(addr.ip().is_loopback());
+    assert_eq!(addr.port(), 502);
+}
+
+#[test]
+fn resolve_error_is_not_found() {
+    let err = std::io::Error::from(ResolveError {
+        hostname: "myhost.local".into(),
+        port: 8080,
+    });
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert_eq!(
+        err.to_string(),
+        "myhost.local:8080 resolved to no addresses"
+    );
+}
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[test]
+fn builder_connects_to_hostname() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+    use tokio_uniconnect::UniConnect;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let conn = RetryingTcpOrSerial::new(host("localhost", port))
+        .build()
+        .unwrap();
+    match conn {
+        UniConnect::RetringTcpStream(stream) => {
+            assert!(stream.peer_addr().unwrap().ip().is_loopback())
+        }
+        _ => panic!("expected RetryingTcpStream"),
+    }
+}
+
+#[test]
+fn static_resolver_answers_from_map() {
+    use tokio::prelude::Future;
+    use tokio_uniconnect::resolver::{AddressResolver, StaticResolver};
+
+    let addr: std::net::SocketAddr = "10.0.0.1:502".parse().unwrap();
+    let resolver = StaticResolver(
+        vec![("plc.local".to_string(), vec![addr])]
+            .into_iter()
+            .collect(),
+    );
+    assert_eq!(resolver.resolve("plc.local", 1).wait().unwrap(), vec![addr]);
+    let err = resolver.resolve("other.local", 502).wait().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[test]
+fn system_resolver_resolves_localhost() {
+    use tokio::prelude::Future;
+    use tokio_uniconnect::resolver::{AddressResolver, SystemResolver};
+
+    let addrs = SystemResolver.resolve("localhost", 502).wait().unwrap();
+    assert!(!addrs.is_empty());
+    assert!(addrs
+        .iter()
+        .all(|addr| addr.ip().is_loopback() && addr.port() == 502));
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn connect_host_uses_custom_resolver() {
+    use tokio::prelude::future;
+    use tokio::runtime::current_thread::Runtime;
+    use tokio_uniconnect::resolver::StaticResolver;
+    use tokio_uniconnect::retrying_tcp_stream::{RetryingTcpStream, TcpStreamSettings};
+
+    use std::sync::Arc;
+
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let mut settings = TcpStreamSettings::default();
+    settings.set_resolver(Some(Arc::new(StaticResolver(
+        vec![("plc.local".to_string(), vec![addr])]
+            .into_iter()
+            .collect(),
+    ))));
+    let mut stream = RetryingTcpStream::connect_host("plc.local", addr.port(), settings);
+
+    let mut rt = Runtime::new().unwrap();
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+}
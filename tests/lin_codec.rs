@@ -0,0 +1,117 @@
+use bytes::BytesMut;
+use tokio::codec::{Decoder, Encoder};
+use tokio_uniconnect::codec::{protected_id, ChecksumType, LinCodec, LinFrame};
+
+fn encode(frame: LinFrame) -> BytesMut {
+    let mut buf = BytesMut::new();
+    LinCodec::new().encode(frame, &mut buf).unwrap();
+    buf
+}
+
+#[test]
+fn protected_id_parity() {
+    assert_eq!(protected_id(0x00), 0x80);
+    assert_eq!(protected_id(0x01), 0xC1);
+    assert_eq!(protected_id(0x3C), 0x3C);
+    // parity bits of input are ignored
+    assert_eq!(protected_id(0xC1), 0xC1);
+}
+
+#[test]
+fn encode_checksums() {
+    let classic = encode(LinFrame::new(0x01, vec![0x01, 0x02], ChecksumType::Classic));
+    assert_eq!(&classic[..], &[0x55, 0xC1, 0x01, 0x02, 0xFC]);
+
+    let enhanced = encode(LinFrame::new(
+        0x01,
+        vec![0x01, 0x02],
+        ChecksumType::Enhanced,
+    ));
+    assert_eq!(&enhanced[..], &[0x55, 0xC1, 0x01, 0x02, 0x3B]);
+}
+
+#[test]
+fn round_trip_detects_checksum_type() {
+    let mut codec = LinCodec::new();
+    let classic = LinFrame::new(0x10, vec![0xAA, 0xBB], ChecksumType::Classic);
+    let enhanced = LinFrame::new(0x30, vec![1, 2, 3, 4, 5, 6, 7, 8], ChecksumType::Enhanced);
+    codec.set_data_len(0x30, 8);
+
+    let mut buf = BytesMut::new();
+    codec.encode(classic.clone(), &mut buf).unwrap();
+    codec.encode(enhanced.clone(), &mut buf).unwrap();
+
+    assert_eq!(codec.decode(&mut buf).unwrap(), Some(classic));
+    let decoded = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(decoded.id(), 0x30);
+    assert_eq!(decoded, enhanced);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn decode_skips_break_and_waits_for_frame() {
+    let mut codec = LinCodec::new();
+    let frame = encode(LinFrame::new(0x01, vec![0x01, 0x02], ChecksumType::Classic));
+
+    // break is received as zero byte
+    let mut buf = BytesMut::from(&[0x00][..]);
+    buf.extend_from_slice(&frame[..3]);
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+    buf.extend_from_slice(&frame[3..]);
+    let decoded = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(decoded.data, vec![0x01, 0x02]);
+    assert_eq!(decoded.checksum_type, ChecksumType::Classic);
+}
+
+#[test]
+fn decode_rejects_bad_checksum() {
+    let mut buf = BytesMut::from(&[0x55, 0xC1, 0x01, 0x02, 0x00][..]);
+    let err = LinCodec::new().decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn encode_rejects_bad_data_len() {
+    let mut buf = BytesMut::new();
+    let mut codec = LinCodec::new();
+    for data in [vec![], vec![0; 9]] {
+        let err = codec
+            .encode(LinFrame::new(0x01, data, ChecksumType::Classic), &mut buf)
+            .unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn encode_rejects_frame_decoder_would_misread() {
+    let mut buf = BytesMut::new();
+    let mut codec = LinCodec::new();
+    // 0x01 carries 2 bytes by default
+    let err = codec
+        .encode(
+            LinFrame::new(0x01, vec![0; 4], ChecksumType::Classic),
+            &mut buf,
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let wrong_parity = LinFrame {
+        protected_id: 0x01,
+        data: vec![0x01, 0x02],
+        checksum_type: ChecksumType::Classic,
+    };
+    let err = codec.encode(wrong_parity, &mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    assert!(buf.is_empty());
+
+    codec.set_data_len(0x01, 4);
+    codec
+        .encode(
+            LinFrame::new(0x01, vec![0; 4], ChecksumType::Classic),
+            &mut buf,
+        )
+        .unwrap();
+    assert_eq!(buf.len(), 7);
+}
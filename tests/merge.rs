@@ -0,0 +1,70 @@
+use tokio::prelude::Stream;
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::merge::merge_readers;
+use tokio_uniconnect::UniConnect;
+
+use std::io::Write;
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+fn connections(count: usize) -> (Vec<TcpStream>, Vec<UniConnect>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut peers = Vec::new();
+    let mut conns = Vec::new();
+    for _ in 0..count {
+        let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        conns.push(UniConnect::from(
+            tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap(),
+        ));
+        peers.push(listener.accept().unwrap().0);
+    }
+    (peers, conns)
+}
+
+#[test]
+fn stream_tags_data_with_source_and_ends_after_all_eof() {
+    let mut rt = Runtime::new().unwrap();
+    let (mut peers, conns) = connections(2);
+    peers[0].write_all(b"first").unwrap();
+    peers[1].write_all(b"second").unwrap();
+    drop(peers);
+
+    let mut received = rt
+        .block_on(merge_readers(conns).into_stream().collect())
+        .unwrap();
+    received.sort();
+    assert_eq!(
+        received,
+        vec![(0, b"first"[..].into()), (1, b"second"[..].into())]
+    );
+}
+
+#[test]
+fn read_rotates_between_ready_connections() {
+    let mut rt = Runtime::new().unwrap();
+    let (mut peers, conns) = connections(3);
+    for peer in &mut peers {
+        peer.write_all(b"x").unwrap();
+    }
+    thread::sleep(Duration::from_millis(20));
+
+    let mut reader = merge_readers(conns);
+    let mut sources = Vec::new();
+    for _ in 0..3 {
+        rt.block_on(tokio::io::read_exact(&mut reader, [0u8; 1]))
+            .unwrap();
+        sources.push(reader.source_index());
+    }
+    // first read starts after the initial source 0
+    assert_eq!(sources, [1, 2, 0]);
+
+    // connections stay usable after into_inner
+    let mut conns = reader.into_inner();
+    peers[2].write_all(b"y").unwrap();
+    let (_, data) = rt
+        .block_on(tokio::io::read_exact(conns.remove(2), [0u8; 1]))
+        .unwrap();
+    assert_eq!(&data, b"y");
+}
@@ -0,0 +1,42 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn migration_keeps_data_received_by_old_connection() {
+    let mut rt = Runtime::new().unwrap();
+    let old = TcpListener::bind("127.0.0.1:0").unwrap();
+    let new = TcpListener::bind("127.0.0.1:0").unwrap();
+    let new_addr = new.local_addr().unwrap();
+
+    let mut stream = RetryingTcpStream::connect(&old.local_addr().unwrap());
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+    let (mut old_peer, _) = old.accept().unwrap();
+    old_peer.write_all(b"old").unwrap();
+    // received, not read yet
+    thread::sleep(Duration::from_millis(20));
+
+    rt.block_on(stream.migrate_to(new_addr, Duration::from_secs(1)))
+        .unwrap();
+    // old connection is closed
+    assert_eq!(old_peer.read(&mut [0u8; 1]).unwrap(), 0);
+
+    let server = thread::spawn(move || {
+        let (mut new_peer, _) = new.accept().unwrap();
+        new_peer.write_all(b"new").unwrap();
+    });
+    let (stream, data) = rt
+        .block_on(tokio::io::read_exact(stream, [0u8; 6]))
+        .unwrap();
+    assert_eq!(&data, b"oldnew");
+    assert_eq!(stream.peer_addr().unwrap(), new_addr);
+    server.join().unwrap();
+}
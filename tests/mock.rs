@@ -0,0 +1,49 @@
+#![cfg(feature = "mock")]
+
+use tokio::prelude::Future;
+use tokio::runtime::current_thread::block_on_all;
+use tokio_uniconnect::dynamic::DynUniConnect;
+use tokio_uniconnect::mock::MockUniConnect;
+
+use std::io;
+
+#[test]
+fn both_directions() {
+    let (conn, peer) = MockUniConnect::channel();
+    let test = peer
+        .write(b"request")
+        .join(tokio::io::read_exact(conn, [0u8; 7]))
+        .and_then(|(peer, (conn, request))| {
+            assert_eq!(&request, b"request");
+            tokio::io::write_all(conn, b"response").map(move |_| peer)
+        })
+        .and_then(|peer| peer.read_exact(8));
+    let (_, response) = block_on_all(test).unwrap();
+    assert_eq!(response, b"response");
+}
+
+#[test]
+fn dropped_peer_gives_eof_and_broken_pipe() {
+    let (conn, peer) = MockUniConnect::channel();
+    let test = peer.write(b"last").map(drop).and_then(|()| {
+        tokio::io::read_to_end(conn, Vec::new()).and_then(|(conn, data)| {
+            assert_eq!(data, b"last");
+            tokio::io::write_all(conn, b"more")
+        })
+    });
+    assert_eq!(
+        block_on_all(test).err().map(|err| err.kind()),
+        Some(io::ErrorKind::BrokenPipe)
+    );
+}
+
+#[test]
+fn shutdown_gives_peer_eof() {
+    let (conn, peer) = MockUniConnect::channel();
+    let test = tokio::io::shutdown(DynUniConnect::new(conn))
+        .and_then(move |conn| peer.read_exact(1).map(move |_| drop(conn)));
+    assert_eq!(
+        block_on_all(test).err().map(|err| err.kind()),
+        Some(io::ErrorKind::UnexpectedEof)
+    );
+}
@@ -0,0 +1,52 @@
+#![cfg(feature = "serial")]
+
+use tokio::prelude::future;
+use tokio_serial::{Serial, SerialPortSettings};
+use tokio_uniconnect::serial::ModemControl;
+use tokio_uniconnect::UniConnect;
+
+use std::io;
+
+fn open(path: &str) -> UniConnect {
+    UniConnect::from(Serial::from_path(path, &SerialPortSettings::default()).unwrap())
+}
+
+#[test]
+fn tcp_has_no_modem_lines() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let mut conn = rt
+        .block_on(future::lazy(|| {
+            tokio::net::TcpStream::from_std(stream, &Default::default()).map(UniConnect::from)
+        }))
+        .unwrap();
+
+    let err = conn.write_data_terminal_ready(true).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    let err = conn.read_clear_to_send().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+}
+
+// Null modem pair with DTR wired to DSR and RTS to CTS, e.g. com0com or tty0tty, given as
+// `UNICONNECT_LOOPBACK_PAIR=/dev/tnt0,/dev/tnt1`.
+#[test]
+#[ignore = "needs virtual null modem pair in UNICONNECT_LOOPBACK_PAIR"]
+fn lines_reach_other_end() {
+    let pair = std::env::var("UNICONNECT_LOOPBACK_PAIR").unwrap();
+    let (a, b) = pair.split_once(',').unwrap();
+    let (a, b) = (a.to_string(), b.to_string());
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(future::lazy(move || {
+        let (mut a, mut b) = (open(&a), open(&b));
+        for level in [true, false] {
+            a.write_data_terminal_ready(level).unwrap();
+            assert_eq!(b.read_data_set_ready().unwrap(), level);
+            a.write_request_to_send(level).unwrap();
+            assert_eq!(b.read_clear_to_send().unwrap(), level);
+        }
+        Ok::<(), ()>(())
+    }))
+    .unwrap();
+}
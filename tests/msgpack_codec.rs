@@ -0,0 +1,53 @@
+#![cfg(feature = "msgpack")]
+
+use bytes::BytesMut;
+use serde::{Deserialize, Serialize};
+use tokio::codec::{Decoder, Encoder};
+use tokio_uniconnect::codec::MsgpackCodec;
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Reading {
+    sensor: String,
+    values: Vec<f64>,
+    location: Location,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+struct Location {
+    lat: f64,
+    lon: f64,
+    label: Option<String>,
+}
+
+#[test]
+fn round_trip_nested_struct() {
+    let reading = Reading {
+        sensor: "temp-1".into(),
+        values: vec![21.5, 21.7],
+        location: Location {
+            lat: 52.23,
+            lon: 21.01,
+            label: Some("lab".into()),
+        },
+    };
+
+    let mut codec = MsgpackCodec::new();
+    let mut buf = BytesMut::new();
+    codec.encode(reading.clone(), &mut buf).unwrap();
+
+    // partial frame
+    let mut partial = buf.split_to(buf.len() - 1);
+    assert_eq!(codec.decode(&mut partial).unwrap(), None);
+    partial.extend_from_slice(&buf);
+
+    assert_eq!(codec.decode(&mut partial).unwrap(), Some(reading));
+    assert!(partial.is_empty());
+}
+
+#[test]
+fn invalid_payload() {
+    let mut codec = MsgpackCodec::<Reading>::new();
+    let mut buf = BytesMut::from(&[0, 0, 0, 1, 0xc1][..]);
+    let err = codec.decode(&mut buf).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
@@ -0,0 +1,58 @@
+use tokio_uniconnect::address::ConnectionAddress;
+
+use std::path::PathBuf;
+
+#[test]
+fn detects_named_pipe() {
+    for input in [r"\\.\pipe\myapp", "//./pipe/myapp"] {
+        let address = ConnectionAddress::detect(input).unwrap();
+        assert_eq!(address, ConnectionAddress::NamedPipe(PathBuf::from(input)));
+        assert_eq!(address.to_string(), input);
+    }
+    assert!(ConnectionAddress::detect(r"\\.\pipe\").is_err());
+}
+
+#[cfg(not(windows))]
+#[test]
+fn named_pipe_is_unsupported() {
+    use tokio_uniconnect::parse::UniConnectParseError;
+    use tokio_uniconnect::UniConnect;
+
+    assert!(matches!(
+        r"\\.\pipe\myapp".parse::<UniConnect>(),
+        Err(UniConnectParseError::UnsupportedScheme(_))
+    ));
+}
+
+#[cfg(windows)]
+#[test]
+fn exchanges_data_with_server() {
+    use mio_named_pipes::NamedPipe;
+    use tokio::reactor::{Handle, PollEvented2};
+    use tokio::runtime::current_thread::Runtime;
+    use tokio_uniconnect::{ConnectionKind, UniConnect};
+
+    let name = format!(r"\\.\pipe\uniconnect-test-{}", std::process::id());
+    let server = NamedPipe::new(&name).unwrap();
+    // pipe has to be registered with the reactor before connect
+    let server = PollEvented2::new_with_handle(server, &Handle::default()).unwrap();
+    match server.get_ref().connect() {
+        Err(ref err) if err.kind() == std::io::ErrorKind::WouldBlock => {}
+        res => res.unwrap(),
+    }
+
+    let conn: UniConnect = name.parse().unwrap();
+    assert_eq!(conn.kind(), ConnectionKind::NamedPipe);
+    assert_eq!(conn.to_string(), name);
+
+    let mut rt = Runtime::new().unwrap();
+    let conn = rt.block_on(tokio::io::write_all(conn, b"ping")).unwrap().0;
+    let (server, buf) = rt
+        .block_on(tokio::io::read_exact(server, [0u8; 4]))
+        .unwrap();
+    assert_eq!(&buf, b"ping");
+
+    rt.block_on(tokio::io::write_all(server, b"pong")).unwrap();
+    let (_, buf) = rt.block_on(tokio::io::read_exact(conn, [0u8; 4])).unwrap();
+    assert_eq!(&buf, b"pong");
+}
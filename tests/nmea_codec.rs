@@ -0,0 +1,84 @@
+use bytes::BytesMut;
+use proptest::prelude::*;
+use tokio::codec::{Decoder, Encoder};
+use tokio_uniconnect::codec::{NmeaCodec, NmeaSentence};
+
+#[test]
+fn decode_gga() {
+    let mut buf = BytesMut::from(
+        &b"garbage$GPGGA,123519,4807.038,N,01131.000,E,1,08,0.9,545.4,M,46.9,M,,*47\r\n"[..],
+    );
+    let sentence = NmeaCodec::new().decode(&mut buf).unwrap().unwrap();
+
+    assert_eq!(sentence.talker, "GP");
+    assert_eq!(sentence.sentence_type, "GGA");
+    assert_eq!(sentence.fields.len(), 14);
+    assert_eq!(sentence.fields[0], "123519");
+    assert!(sentence.checksum_valid);
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn decode_bad_checksum() {
+    let mut buf = BytesMut::from(&b"$GPGLL,4916.45,N*00\r\n"[..]);
+    let sentence = NmeaCodec::new().decode(&mut buf).unwrap().unwrap();
+    assert!(!sentence.checksum_valid);
+}
+
+#[test]
+fn decode_garbled_checksum() {
+    let mut codec = NmeaCodec::new();
+    let mut buf = BytesMut::from(&b"$GPGLL,4916.45,N*ZZ\r\n$GPGLL,4916.45,N*\r\n"[..]);
+    for _ in 0..2 {
+        let sentence = codec.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(sentence.fields, vec!["4916.45", "N"]);
+        assert!(!sentence.checksum_valid);
+    }
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn encode_rejects_reserved_characters() {
+    let mut codec = NmeaCodec::new();
+    let mut buf = BytesMut::new();
+    for field in &["4916,45", "N*", "$GP", "N\r\n"] {
+        let sentence = NmeaSentence {
+            talker: "GP".to_owned(),
+            sentence_type: "GLL".to_owned(),
+            fields: vec![field.to_string()],
+            checksum_valid: true,
+        };
+        let err = codec.encode(sentence, &mut buf).unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+    }
+    assert!(buf.is_empty());
+}
+
+#[test]
+fn decode_waits_for_terminator() {
+    let mut codec = NmeaCodec::new();
+    let mut buf = BytesMut::from(&b"$GPGLL,4916.45"[..]);
+    assert_eq!(codec.decode(&mut buf).unwrap(), None);
+
+    buf.extend_from_slice(b",N\r\n");
+    let sentence = codec.decode(&mut buf).unwrap().unwrap();
+    assert_eq!(sentence.fields, vec!["4916.45", "N"]);
+    assert!(!sentence.checksum_valid);
+}
+
+proptest! {
+    #[test]
+    fn encode_decode_round_trip(
+        talker in "[A-OQ-Z][A-Z]",
+        sentence_type in "[A-Z]{3}",
+        fields in prop::collection::vec("[A-Za-z0-9.-]{0,6}", 0..10),
+    ) {
+        let sentence = NmeaSentence { talker, sentence_type, fields, checksum_valid: true };
+        let mut codec = NmeaCodec::new();
+        let mut buf = BytesMut::new();
+
+        codec.encode(sentence.clone(), &mut buf).unwrap();
+        prop_assert_eq!(codec.decode(&mut buf).unwrap(), Some(sentence));
+        prop_assert!(buf.is_empty());
+    }
+}
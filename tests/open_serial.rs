@@ -0,0 +1,42 @@
+#![cfg(all(unix, feature = "serial"))]
+
+use tokio_serial::{Serial, SerialPort, SerialPortSettings};
+use tokio_uniconnect::serial::open_serial_async;
+
+use std::time::Duration;
+
+#[test]
+fn open_serial_async_opens_port() {
+    let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let (master, slave) = Serial::pair().unwrap();
+    let path = slave.name().unwrap();
+    drop(slave);
+
+    let serial = rt
+        .block_on(open_serial_async(
+            &path,
+            &SerialPortSettings::default(),
+            Duration::from_secs(5),
+        ))
+        .unwrap();
+    rt.block_on(tokio::io::write_all(serial, b"hello")).unwrap();
+    let (_, received) = rt
+        .block_on(tokio::io::read_exact(master, [0u8; 5]))
+        .unwrap();
+    assert_eq!(&received, b"hello");
+}
+
+#[test]
+fn open_error_is_not_timeout() {
+    let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let err = rt
+        .block_on(open_serial_async(
+            "/dev/uniconnect-missing-port",
+            &SerialPortSettings::default(),
+            Duration::from_secs(5),
+        ))
+        .map(drop)
+        .unwrap_err();
+    assert_ne!(err.kind(), std::io::ErrorKind::TimedOut);
+    assert_eq!(err.to_string(), "No such file or directory");
+}
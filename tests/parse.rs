@@ -0,0 +1,54 @@
+use tokio_uniconnect::parse::UniConnectParseError;
+use tokio_uniconnect::{ConnectionKind, UniConnect};
+
+use std::net::TcpListener;
+
+#[test]
+fn parses_socket_address() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let conn: UniConnect = listener.local_addr().unwrap().to_string().parse().unwrap();
+    #[cfg(feature = "retrying-tcp")]
+    assert_eq!(conn.kind(), ConnectionKind::RetryingTcp);
+    #[cfg(not(feature = "retrying-tcp"))]
+    assert_eq!(conn.kind(), ConnectionKind::Tcp);
+}
+
+#[test]
+fn unknown_scheme_is_unsupported() {
+    match "http://example.com".parse::<UniConnect>() {
+        Err(UniConnectParseError::UnsupportedScheme(scheme)) => assert_eq!(scheme, "http"),
+        res => panic!("expected UnsupportedScheme, got {:?}", res.map(|_| ())),
+    }
+    assert!(matches!(
+        "tls://example.com:443".parse::<UniConnect>(),
+        Err(UniConnectParseError::UnsupportedScheme(_))
+    ));
+}
+
+#[test]
+fn invalid_address() {
+    assert!(matches!(
+        "tcp://".parse::<UniConnect>(),
+        Err(UniConnectParseError::InvalidAddress(_))
+    ));
+}
+
+#[cfg(feature = "serial")]
+#[test]
+fn missing_serial_port_fails_to_open() {
+    let err = "/dev/uniconnect-missing-port"
+        .parse::<UniConnect>()
+        .unwrap_err();
+    assert!(matches!(err, UniConnectParseError::SerialOpenError(_)));
+    assert!(std::error::Error::source(&err).is_some());
+}
+
+#[cfg(all(unix, feature = "serial"))]
+#[test]
+fn opens_serial_port() {
+    let (_master, slave) = tokio_serial::Serial::pair().unwrap();
+    let path = tokio_serial::SerialPort::name(&slave).unwrap();
+    drop(slave);
+    let conn: UniConnect = path.parse().unwrap();
+    assert_eq!(conn.kind(), ConnectionKind::Serial);
+}
@@ -0,0 +1,145 @@
+use futures::{future, Async};
+use tokio::net::TcpStream;
+use tokio::prelude::Future;
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::pool::{SelectionStrategy, UniConnectPool};
+use tokio_uniconnect::UniConnect;
+
+use std::io::Read;
+use std::net::TcpListener;
+
+fn pool_of(listener: &TcpListener, size: usize) -> (Vec<std::net::TcpStream>, Vec<UniConnect>) {
+    let mut peers = Vec::new();
+    let mut conns = Vec::new();
+    for _ in 0..size {
+        let stream = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+        conns.push(UniConnect::from(
+            TcpStream::from_std(stream, &Handle::default()).unwrap(),
+        ));
+        peers.push(listener.accept().unwrap().0);
+    }
+    (peers, conns)
+}
+
+#[test]
+fn guard_reads_and_writes() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (mut peers, conns) = pool_of(&listener, 1);
+    let pool = UniConnectPool::new(conns);
+    let mut rt = Runtime::new().unwrap();
+
+    let guard = rt.block_on(pool.acquire()).unwrap();
+    let guard = rt.block_on(tokio::io::write_all(guard, b"ping")).unwrap().0;
+    let mut buf = [0u8; 4];
+    peers[0].read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+
+    std::io::Write::write_all(&mut peers[0], b"pong").unwrap();
+    let (_, buf) = rt.block_on(tokio::io::read_exact(guard, [0u8; 4])).unwrap();
+    assert_eq!(&buf, b"pong");
+}
+
+#[test]
+fn strategies_pick_different_connections() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut rt = Runtime::new().unwrap();
+
+    let mut picks = Vec::new();
+    for strategy in [
+        SelectionStrategy::RoundRobin,
+        SelectionStrategy::LeastRecentlyUsed,
+    ] {
+        let (_peers, conns) = pool_of(&listener, 2);
+        let pool = UniConnectPool::new(conns).with_strategy(strategy);
+
+        let first = rt.block_on(pool.acquire()).unwrap();
+        assert_eq!(first.index(), 0);
+        assert_eq!(rt.block_on(pool.acquire()).unwrap().index(), 1);
+        // the first connection is still in use
+        assert_eq!(rt.block_on(pool.acquire()).unwrap().index(), 1);
+        drop(first);
+
+        picks.push(rt.block_on(pool.acquire()).unwrap().index());
+    }
+    // round robin continues after the last start, LRU goes back to the first connection
+    assert_eq!(picks, [1, 0]);
+}
+
+#[test]
+fn acquire_waits_for_returned_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (_peers, conns) = pool_of(&listener, 1);
+    let pool = UniConnectPool::new(conns);
+    let mut rt = Runtime::new().unwrap();
+
+    let guard = rt.block_on(pool.acquire()).unwrap();
+    let mut acquire = pool.acquire();
+    let mut guard = Some(guard);
+    let acquired = rt
+        .block_on(future::poll_fn(move || {
+            let res = acquire.poll();
+            // return connection after the waiting task was registered
+            if let Ok(Async::NotReady) = res {
+                // wakes this task
+                drop(guard.take());
+            }
+            res
+        }))
+        .unwrap();
+    assert_eq!(acquired.index(), 0);
+}
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[test]
+fn builds_every_slot() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+    use tokio_uniconnect::ConnectionKind;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let builder = RetryingTcpOrSerial::new(format!("tcp://{}", addr).parse().unwrap());
+    let pool = UniConnectPool::from_builder(&builder, 3).unwrap();
+    assert_eq!(pool.len(), 3);
+
+    let mut rt = Runtime::new().unwrap();
+    let guard = rt
+        .block_on(pool.acquire().map(|guard| guard.kind()))
+        .unwrap();
+    assert_eq!(guard, ConnectionKind::RetryingTcp);
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn health_checks_skip_unreachable_connection() {
+    use tokio_uniconnect::pool::HealthConfig;
+    use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (mut peers, mut conns) = pool_of(&listener, 1);
+    let closed = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    conns.insert(0, UniConnect::from(RetryingTcpStream::connect(&closed)));
+    let pool = Arc::new(UniConnectPool::new(conns).with_health_checks(HealthConfig {
+        interval: Duration::from_millis(20),
+        probe: b"\n".to_vec(),
+        timeout: Duration::from_millis(50),
+    }));
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    pool.start_health_checks(&rt.executor());
+
+    let mut probe = [0u8; 1];
+    peers[0].read_exact(&mut probe).unwrap();
+    assert_eq!(&probe, b"\n");
+    std::thread::sleep(Duration::from_millis(200));
+    assert!(!pool.is_healthy(0));
+    assert!(pool.is_healthy(1));
+    for _ in 0..2 {
+        assert_eq!(rt.block_on(pool.acquire()).unwrap().index(), 1);
+    }
+}
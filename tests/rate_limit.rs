@@ -0,0 +1,80 @@
+#![cfg(feature = "rate-limit")]
+
+use tokio::prelude::{future, Future};
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::rate_limit::TokenBucketConn;
+use tokio_uniconnect::testing::EchoConn;
+
+use std::io::{Cursor, Write};
+use std::time::{Duration, Instant};
+
+const RATE: u64 = 1000;
+
+#[test]
+fn burst_of_one_second_is_written_at_once() {
+    let mut rt = Runtime::new().unwrap();
+    let mut conn = TokenBucketConn::new(EchoConn::new(), RATE);
+
+    let written = rt
+        .block_on(future::lazy(|| {
+            future::ok::<_, ()>(conn.write(&[0u8; 1500]).unwrap())
+        }))
+        .unwrap();
+    assert_eq!(written, RATE as usize);
+    assert_eq!(conn.get_ref().pending_len(), RATE as usize);
+}
+
+#[test]
+fn writes_over_limit_wait_for_refill() {
+    let mut rt = Runtime::new().unwrap();
+    let conn = TokenBucketConn::new(EchoConn::new(), RATE);
+    let start = Instant::now();
+
+    let (conn, _) = rt
+        .block_on(tokio::io::write_all(conn, vec![0u8; 1200]))
+        .unwrap();
+    let elapsed = start.elapsed();
+    assert!(elapsed >= Duration::from_millis(190), "{:?}", elapsed);
+    assert!(elapsed < Duration::from_secs(1), "{:?}", elapsed);
+    assert_eq!(conn.into_inner().pending_len(), 1200);
+}
+
+#[test]
+fn reads_are_limited_only_with_read_limit() {
+    let mut rt = Runtime::new().unwrap();
+    let data = vec![7u8; 1200];
+
+    let start = Instant::now();
+    let unlimited = TokenBucketConn::new(Cursor::new(data.clone()), RATE);
+    rt.block_on(tokio::io::read_exact(unlimited, vec![0u8; 1200]))
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_millis(100));
+
+    let start = Instant::now();
+    let limited = TokenBucketConn::new(Cursor::new(data.clone()), RATE).with_read_limit(RATE);
+    let (_, read) = rt
+        .block_on(tokio::io::read_exact(limited, vec![0u8; 1200]))
+        .unwrap();
+    assert_eq!(read, data);
+    assert!(start.elapsed() >= Duration::from_millis(190));
+}
+
+#[test]
+#[should_panic(expected = "bandwidth limit must be positive")]
+fn zero_limit_panics() {
+    TokenBucketConn::new(EchoConn::new(), 0);
+}
+
+#[test]
+fn limited_write_is_forwarded_as_is() {
+    let mut rt = Runtime::new().unwrap();
+    let conn = TokenBucketConn::new(EchoConn::new(), RATE);
+    let data = rt
+        .block_on(
+            tokio::io::write_all(conn, b"hello")
+                .and_then(|(conn, _)| tokio::io::read_exact(conn.into_inner(), [0u8; 5])),
+        )
+        .unwrap()
+        .1;
+    assert_eq!(&data, b"hello");
+}
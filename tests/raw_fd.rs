@@ -0,0 +1,31 @@
+#![cfg(unix)]
+
+use tokio::reactor::Handle;
+use tokio_uniconnect::UniConnect;
+
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::AsRawFd;
+
+#[test]
+fn connected_stream_has_raw_fd() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let fd = stream.as_raw_fd();
+    let conn =
+        UniConnect::from(tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap());
+    assert_eq!(conn.try_as_raw_fd(), Some(fd));
+
+    let (a, _b) = tokio::net::UnixStream::pair().unwrap();
+    let fd = a.as_raw_fd();
+    assert_eq!(UniConnect::from(a).try_as_raw_fd(), Some(fd));
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn connecting_stream_has_no_raw_fd() {
+    use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let conn = UniConnect::from(RetryingTcpStream::connect(&listener.local_addr().unwrap()));
+    assert_eq!(conn.try_as_raw_fd(), None);
+}
@@ -0,0 +1,47 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+#[test]
+fn trigger_wakes_pending_read_and_reconnects() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = RetryingTcpStream::connect(&listener.local_addr().unwrap());
+    let trigger = stream.reconnect_trigger();
+    let server = thread::spawn(move || {
+        let (mut old_peer, _) = listener.accept().unwrap();
+        // trigger while the reader waits for data
+        thread::sleep(Duration::from_millis(50));
+        trigger.trigger();
+        assert_eq!(old_peer.read(&mut [0u8; 1]).unwrap(), 0);
+        let (mut new_peer, _) = listener.accept().unwrap();
+        new_peer.write_all(b"new").unwrap();
+    });
+
+    let (stream, data) = rt
+        .block_on(tokio::io::read_exact(stream, [0u8; 3]))
+        .unwrap();
+    assert_eq!(&data, b"new");
+    assert_eq!(stream.reconnect_count(), 1);
+    server.join().unwrap();
+}
+
+#[test]
+fn trigger_while_connecting_is_ignored() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = RetryingTcpStream::connect(&listener.local_addr().unwrap());
+
+    stream.reconnect_trigger().trigger();
+    stream.force_reconnect();
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+    assert_eq!(stream.reconnect_count(), 0);
+}
@@ -0,0 +1,203 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::{future, FutureExt};
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retry::{is_retry_exhausted, ReconnectGovernor, RetryBudget, RetryConfig};
+use tokio_uniconnect::retrying_tcp_stream::{ReconnectEvent, RetryingTcpStream};
+
+use std::net::{SocketAddr, TcpListener};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+// Address nobody listens on
+fn refused_addr() -> SocketAddr {
+    TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+}
+
+// Poll stream until connection attempt fails
+fn fail_attempt(rt: &mut Runtime, stream: &mut RetryingTcpStream) {
+    let err = rt
+        .block_on(future::poll_fn(|| stream.poll_write_ready()).timeout(Duration::from_secs(5)))
+        .unwrap_err();
+    assert!(!err.is_elapsed(), "connection attempt didn't fail");
+}
+
+#[test]
+fn waits_between_attempts() {
+    let mut rt = Runtime::new().unwrap();
+    let retry_config = RetryConfig {
+        initial_delay: Duration::from_secs(10),
+        ..RetryConfig::default()
+    };
+    let mut stream =
+        RetryingTcpStream::connect_with_retry(&refused_addr(), Default::default(), retry_config);
+
+    fail_attempt(&mut rt, &mut stream);
+    assert_eq!(stream.state_description(), "WaitingToRetry");
+}
+
+#[test]
+fn default_config_reconnects_immediately() {
+    let mut rt = Runtime::new().unwrap();
+    let mut stream = RetryingTcpStream::connect(&refused_addr());
+
+    fail_attempt(&mut rt, &mut stream);
+    assert_eq!(stream.state_description(), "Connecting");
+}
+
+#[test]
+fn gives_up_after_max_attempts() {
+    let mut rt = Runtime::new().unwrap();
+    let retry_config = RetryConfig {
+        max_attempts: Some(2),
+        ..RetryConfig::default()
+    };
+    let mut stream =
+        RetryingTcpStream::connect_with_retry(&refused_addr(), Default::default(), retry_config);
+
+    // first connect and two reconnects
+    for _ in 0..3 {
+        fail_attempt(&mut rt, &mut stream);
+    }
+    assert_eq!(stream.state_description(), "Failed");
+    let err = rt
+        .block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap_err();
+    assert!(is_retry_exhausted(&err));
+    assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+}
+
+#[test]
+fn successful_connection_resets_attempts() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let retry_config = RetryConfig {
+        max_attempts: Some(1),
+        ..RetryConfig::default()
+    };
+    let mut stream = RetryingTcpStream::connect_with_retry(
+        &listener.local_addr().unwrap(),
+        Default::default(),
+        retry_config,
+    );
+
+    for _ in 0..3 {
+        rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+            .unwrap();
+        assert_eq!(stream.state_description(), "Connected");
+        // every reconnect is the first one after a successful connection
+        stream.force_reconnect();
+        assert_eq!(stream.state_description(), "Reconnecting");
+    }
+}
+
+#[test]
+fn governor_delays_reconnect_over_limit() {
+    let mut rt = Runtime::new().unwrap();
+    let governor = Arc::new(ReconnectGovernor::new(1, Duration::from_secs(10)));
+    let mut stream = RetryingTcpStream::connect(&refused_addr()).with_governor(governor);
+
+    // first reconnect takes the only slot of this window
+    fail_attempt(&mut rt, &mut stream);
+    assert_eq!(stream.state_description(), "Connecting");
+    fail_attempt(&mut rt, &mut stream);
+    assert_eq!(stream.state_description(), "WaitingToRetry");
+}
+
+#[test]
+fn clones_of_seeded_config_share_jitter() {
+    let config = RetryConfig {
+        initial_delay: Duration::from_secs(1),
+        ..RetryConfig::default()
+    }
+    .with_jitter_seed(42);
+    let streams: Vec<_> = (0..4).map(|_| config.clone()).collect();
+    let delays: Vec<_> = streams.iter().map(|config| config.next_delay(0)).collect();
+    assert!(
+        delays.windows(2).any(|pair| pair[0] != pair[1]),
+        "{:?}",
+        delays
+    );
+
+    // same sequence on every run
+    let again = config.clone().with_jitter_seed(42);
+    let repeated: Vec<_> = (0..4).map(|_| again.clone().next_delay(0)).collect();
+    assert_eq!(delays, repeated);
+}
+
+#[test]
+fn delay_at_large_attempt() {
+    let immediate = RetryConfig::default();
+    assert_eq!(immediate.delay_for_attempt(100_000), Duration::ZERO);
+
+    let config = RetryConfig {
+        initial_delay: Duration::from_millis(100),
+        ..RetryConfig::default()
+    };
+    assert_eq!(config.delay_for_attempt(100_000), config.max_delay);
+    assert_eq!(config.delay_for_attempt(u32::MAX), config.max_delay);
+}
+
+#[test]
+fn budget_idle_past_capacity_refills_only_capacity() {
+    let budget = RetryBudget::new(2, Duration::from_millis(20), Duration::from_secs(0));
+    assert!(budget.try_acquire() && budget.try_acquire());
+
+    std::thread::sleep(Duration::from_millis(100));
+    assert_eq!(budget.available(), 2);
+    assert!(budget.try_acquire() && budget.try_acquire());
+    assert!(!budget.try_acquire());
+}
+
+#[test]
+fn notifies_every_reconnect() {
+    let mut rt = Runtime::new().unwrap();
+    let start = Instant::now();
+    let (tx, rx) = tokio::sync::watch::channel(ReconnectEvent {
+        attempt: 0,
+        at: start,
+    });
+    let mut stream = RetryingTcpStream::with_reconnect_notifier(&refused_addr(), tx);
+
+    fail_attempt(&mut rt, &mut stream);
+    assert_eq!(rx.get_ref().attempt, 1);
+    fail_attempt(&mut rt, &mut stream);
+    let event = *rx.get_ref();
+    assert_eq!(event.attempt, 2);
+    assert!(event.at >= start);
+}
+
+#[test]
+fn seeded_jitter_is_reproducible() {
+    let config = RetryConfig {
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(1),
+        ..RetryConfig::default()
+    };
+    let mut rng = config.jitter_rng();
+    // without jitter nothing random is applied
+    for attempt in 0..5 {
+        assert_eq!(
+            config.jittered_delay_for_attempt(attempt, &mut rng),
+            config.delay_for_attempt(attempt)
+        );
+    }
+
+    let config = config.with_jitter_seed(42);
+    let delays = |config: &RetryConfig| {
+        let mut rng = config.jitter_rng();
+        (0..8)
+            .map(|attempt| config.jittered_delay_for_attempt(attempt, &mut rng))
+            .collect::<Vec<_>>()
+    };
+    let jittered = delays(&config);
+    assert_eq!(jittered, delays(&config));
+    assert_ne!(jittered, delays(&config.clone().with_jitter_seed(43)));
+    for (attempt, delay) in (0..).zip(&jittered) {
+        let base = config.delay_for_attempt(attempt);
+        assert!(*delay >= base / 2 && *delay <= base * 3 / 2, "{:?}", delay);
+    }
+}
@@ -0,0 +1,57 @@
+use tokio_uniconnect::retry::{ImmediateFirstRetrySchedule, LimitedRetrySchedule};
+
+use std::iter;
+use std::time::Duration;
+
+fn ms(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn immediate_first_retry_then_subsequent() {
+    let schedule = ImmediateFirstRetrySchedule::new(Box::new(vec![ms(100), ms(200)].into_iter()));
+    assert_eq!(schedule.collect::<Vec<_>>(), [ms(0), ms(100), ms(200)]);
+
+    let schedule =
+        ImmediateFirstRetrySchedule::with_first_delay(ms(10), Box::new(iter::repeat(ms(50))));
+    assert_eq!(
+        schedule.take(3).collect::<Vec<_>>(),
+        [ms(10), ms(50), ms(50)]
+    );
+}
+
+#[test]
+fn first_retry_is_made_even_when_subsequent_is_empty() {
+    let mut schedule = ImmediateFirstRetrySchedule::new(Box::new(iter::empty()));
+    assert_eq!(schedule.next(), Some(ms(0)));
+    assert_eq!(schedule.next(), None);
+}
+
+#[test]
+fn limited_schedule_stops_after_attempts() {
+    let mut schedule = LimitedRetrySchedule::new(Box::new(iter::repeat(ms(5))), 2);
+    assert_eq!(schedule.remaining(), 2);
+    assert_eq!(schedule.next(), Some(ms(5)));
+    assert_eq!(schedule.next(), Some(ms(5)));
+    assert_eq!(schedule.remaining(), 0);
+    assert_eq!(schedule.next(), None);
+}
+
+#[test]
+fn limited_schedule_ends_with_shorter_inner() {
+    let schedule = LimitedRetrySchedule::new(Box::new(iter::once(ms(5))), 3);
+    assert_eq!(schedule.collect::<Vec<_>>(), [ms(5)]);
+}
+
+#[test]
+fn schedules_compose_with_iterator_adapters() {
+    let backoff = iter::successors(Some(ms(100)), |delay| Some(*delay * 2)).map(|d| d.min(ms(300)));
+    let schedule = LimitedRetrySchedule::new(
+        Box::new(ImmediateFirstRetrySchedule::new(Box::new(backoff))),
+        5,
+    );
+    assert_eq!(
+        schedule.collect::<Vec<_>>(),
+        [ms(0), ms(100), ms(200), ms(300), ms(300)]
+    );
+}
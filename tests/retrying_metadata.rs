@@ -0,0 +1,98 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::reactor::Handle;
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::io::Read;
+use std::net::{TcpListener, TcpStream};
+
+#[test]
+fn metadata_survives_reconnect() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut stream = RetryingTcpStream::from_std(stream, &Handle::default()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    stream.set_label("sensor-7");
+    stream.set_metadata("firmware", "1.2.3");
+    assert_eq!(stream.set_metadata("location", "hall"), None);
+    assert_eq!(
+        stream.set_metadata("location", "roof"),
+        Some("hall".to_string())
+    );
+
+    let connection_id = stream.connection_id();
+    stream.force_reconnect();
+    // old connection is closed
+    assert_eq!(peer.read(&mut [0u8; 1]).unwrap(), 0);
+
+    assert_eq!(stream.connection_id(), connection_id);
+    assert_eq!(stream.label(), Some("sensor-7"));
+    assert_eq!(stream.metadata().len(), 2);
+    assert_eq!(stream.metadata()["firmware"], "1.2.3");
+    assert_eq!(stream.metadata()["location"], "roof");
+    assert_eq!(stream.remove_metadata("location"), Some("roof".to_string()));
+}
+
+#[test]
+fn diagnostics_follow_state() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut stream = RetryingTcpStream::from_std(stream, &Handle::default()).unwrap();
+    stream.set_label("plc");
+
+    assert_eq!(stream.state_description(), "Connected");
+    let connected_at = stream.state_changed_at();
+
+    stream.force_reconnect();
+    assert_eq!(stream.state_description(), "Reconnecting");
+    assert!(stream.state_changed_at() >= connected_at);
+
+    let conn = tokio_uniconnect::UniConnect::from(stream);
+    assert_eq!(
+        format!("{:?}", conn),
+        format!(
+            "RetryingTcpStream {{ target: \"{}\", state: \"Reconnecting\" }}",
+            listener.local_addr().unwrap()
+        )
+    );
+    let diagnostics = conn.diagnostics().unwrap();
+    assert_eq!(diagnostics.state, "Reconnecting");
+    assert_eq!(diagnostics.label.as_deref(), Some("plc"));
+    assert_eq!(diagnostics.peer, Some(listener.local_addr().unwrap()));
+}
+
+#[test]
+fn tcp_stream_only_while_connected() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut stream = RetryingTcpStream::from_std(stream, &Handle::default()).unwrap();
+
+    let ts = stream.as_tcp_stream().unwrap();
+    assert_eq!(ts.peer_addr().unwrap(), listener.local_addr().unwrap());
+    stream
+        .as_tcp_stream_mut()
+        .unwrap()
+        .set_nodelay(true)
+        .unwrap();
+    assert!(stream.as_tcp_stream().unwrap().nodelay().unwrap());
+
+    stream.force_reconnect();
+    assert!(stream.as_tcp_stream().is_none());
+    assert!(stream.as_tcp_stream_mut().is_none());
+}
+
+#[test]
+fn is_connected_follows_state() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let plain = tokio::net::TcpStream::from_std(stream.try_clone().unwrap(), &Handle::default());
+    assert!(tokio_uniconnect::UniConnect::from(plain.unwrap()).is_connected());
+
+    let mut stream = RetryingTcpStream::from_std(stream, &Handle::default()).unwrap();
+    assert!(stream.is_connected());
+
+    stream.force_reconnect();
+    assert!(!stream.is_connected());
+    assert!(!tokio_uniconnect::UniConnect::from(stream).is_connected());
+}
@@ -0,0 +1,67 @@
+#![cfg(all(unix, feature = "serial"))]
+
+use tokio::prelude::{future, Future};
+use tokio_serial::{Serial, SerialPort, SerialPortSettings};
+use tokio_uniconnect::retry::{is_retry_exhausted, RetryConfig};
+use tokio_uniconnect::retrying_serial::RetryingSerial;
+use tokio_uniconnect::UniConnect;
+
+use std::io::Write;
+
+#[test]
+fn opens_pseudo_terminal() {
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let received = rt.block_on(future::lazy(|| {
+        let (master, slave) = Serial::pair().unwrap();
+        let path = slave.name().unwrap();
+        drop(slave);
+
+        let conn = UniConnect::from(RetryingSerial::open(path, SerialPortSettings::default()));
+        assert_eq!(conn.kind().to_string(), "RetryingSerial");
+        tokio::io::write_all(conn, b"hello")
+            .and_then(move |(conn, _)| {
+                tokio::io::read_exact(master, [0u8; 5]).map(move |(_, buf)| (conn, buf))
+            })
+            .map(|(_, buf)| buf)
+    }));
+    assert_eq!(&received.unwrap(), b"hello");
+}
+
+#[test]
+fn missing_port_is_retried_until_max_attempts() {
+    let path = "/dev/uniconnect-missing-port";
+    let mut serial =
+        RetryingSerial::open(path, SerialPortSettings::default()).with_retry_config(RetryConfig {
+            max_attempts: Some(1),
+            ..RetryConfig::default()
+        });
+    assert!(serial.as_serial().is_none());
+    assert_eq!(serial.reconnect_count(), 1);
+
+    let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let err = rt
+        .block_on(future::poll_fn(|| match serial.write(b"x") {
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                Ok(futures::Async::NotReady)
+            }
+            res => res.map(futures::Async::Ready),
+        }))
+        .unwrap_err();
+    assert!(is_retry_exhausted(&err));
+    assert_eq!(serial.reconnect_count(), 1);
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn builder_opts_in() {
+    use tokio_uniconnect::builder::{ConnectionAddress, RetryingTcpOrSerial};
+
+    let address = ConnectionAddress::Serial("/dev/uniconnect-missing-port".into());
+    assert!(RetryingTcpOrSerial::new(address.clone()).build().is_err());
+
+    let builder = RetryingTcpOrSerial::new(address).with_retrying_serial(true);
+    assert!(matches!(
+        builder.build().unwrap(),
+        UniConnect::RetryingSerial(_)
+    ));
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::{future, AsyncWrite};
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::{OverflowPolicy, RetryingTcpStream, TcpStreamSettings};
+use tokio_uniconnect::UniConnect;
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+// Reads and writes must run inside a task
+fn assert_broken_pipe(rt: &mut Runtime, stream: &mut RetryingTcpStream) {
+    rt.block_on(future::lazy(|| {
+        assert_eq!(
+            stream.write(b"data").unwrap_err().kind(),
+            ErrorKind::BrokenPipe
+        );
+        assert_eq!(
+            stream.read(&mut [0u8; 4]).unwrap_err().kind(),
+            ErrorKind::BrokenPipe
+        );
+        future::ok::<_, ()>(())
+    }))
+    .unwrap();
+}
+
+// Stream that queues `data` while it is connecting to `listener`
+fn stream_with_queued(rt: &mut Runtime, listener: &TcpListener, data: &[u8]) -> RetryingTcpStream {
+    let mut settings = TcpStreamSettings::default();
+    settings.set_write_buffer(Some(64), OverflowPolicy::Block);
+    let mut stream =
+        RetryingTcpStream::connect_with_settings(&listener.local_addr().unwrap(), settings);
+    rt.block_on(future::lazy(|| {
+        assert_eq!(stream.write(data).unwrap(), data.len());
+        future::ok::<_, ()>(())
+    }))
+    .unwrap();
+    assert_eq!(stream.queued_write_len(), data.len());
+    stream
+}
+
+#[test]
+fn shutdown_while_connecting() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = RetryingTcpStream::connect(&listener.local_addr().unwrap());
+    assert_eq!(stream.state_description(), "Connecting");
+
+    rt.block_on(future::poll_fn(|| stream.shutdown())).unwrap();
+    assert_eq!(stream.state_description(), "Shutdown");
+    assert_broken_pipe(&mut rt, &mut stream);
+}
+
+#[test]
+fn shutdown_connected_stream_doesnt_reconnect() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let mut stream = RetryingTcpStream::from_std(stream, &Handle::default()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+
+    rt.block_on(future::poll_fn(|| stream.shutdown())).unwrap();
+    assert_eq!(stream.state_description(), "Shutdown");
+    // peer sees EOF
+    assert_eq!(peer.read(&mut [0u8; 1]).unwrap(), 0);
+
+    assert_broken_pipe(&mut rt, &mut stream);
+    assert_eq!(stream.reconnect_count(), 0);
+    // shutdown again is no-op
+    rt.block_on(future::poll_fn(|| stream.shutdown())).unwrap();
+}
+
+#[test]
+fn shutdown_sends_queued_bytes() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = stream_with_queued(&mut rt, &listener, b"queued");
+    // connected, queued bytes not sent yet
+    rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap();
+    assert_eq!(stream.queued_write_len(), 6);
+    let (mut peer, _) = listener.accept().unwrap();
+
+    rt.block_on(future::poll_fn(|| stream.shutdown())).unwrap();
+    assert_eq!(stream.state_description(), "Shutdown");
+    let mut received = Vec::new();
+    peer.read_to_end(&mut received).unwrap();
+    assert_eq!(received, b"queued");
+}
+
+#[test]
+fn graceful_shutdown_while_connecting_sends_queued_bytes() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = stream_with_queued(&mut rt, &listener, b"queued");
+    assert_eq!(stream.state_description(), "Connecting");
+
+    rt.block_on(UniConnect::from(stream).shutdown_gracefully(Duration::from_secs(5)))
+        .unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+    let mut received = Vec::new();
+    peer.read_to_end(&mut received).unwrap();
+    assert_eq!(received, b"queued");
+}
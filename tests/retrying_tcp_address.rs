@@ -0,0 +1,118 @@
+use tokio_uniconnect::address::ConnectionAddress;
+use tokio_uniconnect::retry::RetryConfig;
+
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::time::Duration;
+
+fn hash(address: &ConnectionAddress) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    address.hash(&mut hasher);
+    hasher.finish()
+}
+
+#[test]
+fn parses_policy_from_query() {
+    let address: ConnectionAddress =
+        "retrying-tcp://127.0.0.1:502?initial_delay=0.5&max_delay=10&multiplier=1.5&max_attempts=5&jitter=true&jitter_seed=7"
+            .parse()
+            .unwrap();
+    let policy = RetryConfig {
+        initial_delay: Duration::from_millis(500),
+        max_delay: Duration::from_secs(10),
+        multiplier: 1.5,
+        max_attempts: Some(5),
+        jitter: true,
+        jitter_seed: Some(7),
+        ..RetryConfig::default()
+    };
+    assert_eq!(
+        address,
+        ConnectionAddress::RetryingTcp {
+            addr: "127.0.0.1:502".parse().unwrap(),
+            policy,
+        }
+    );
+
+    // missing options take default values
+    let address: ConnectionAddress = "retrying-tcp://[::1]:502".parse().unwrap();
+    assert_eq!(
+        address,
+        ConnectionAddress::RetryingTcp {
+            addr: "[::1]:502".parse().unwrap(),
+            policy: RetryConfig::default(),
+        }
+    );
+}
+
+#[test]
+fn display_round_trips() {
+    let default = ConnectionAddress::RetryingTcp {
+        addr: "127.0.0.1:502".parse().unwrap(),
+        policy: RetryConfig::default(),
+    };
+    assert_eq!(default.to_string(), "retrying-tcp://127.0.0.1:502");
+
+    let custom = ConnectionAddress::RetryingTcp {
+        addr: "127.0.0.1:502".parse().unwrap(),
+        policy: RetryConfig {
+            initial_delay: Duration::from_millis(100),
+            max_attempts: Some(3),
+            ..RetryConfig::default()
+        },
+    };
+    assert_eq!(
+        custom.to_string(),
+        "retrying-tcp://127.0.0.1:502?initial_delay=0.1&max_attempts=3"
+    );
+    let parsed: ConnectionAddress = custom.to_string().parse().unwrap();
+    assert_eq!(parsed, custom);
+
+    assert_eq!(hash(&parsed), hash(&custom));
+    assert_ne!(hash(&parsed), hash(&default));
+}
+
+#[test]
+fn rejects_invalid_options() {
+    for input in &[
+        "retrying-tcp://localhost:502",
+        "retrying-tcp://127.0.0.1:502?timeout=1",
+        "retrying-tcp://127.0.0.1:502?max_attempts=many",
+        "retrying-tcp://127.0.0.1:502?multiplier=NaN",
+        "retrying-tcp://127.0.0.1:502?initial_delay=-1",
+        "retrying-tcp://127.0.0.1:502?jitter",
+    ] {
+        let err = input.parse::<ConnectionAddress>().unwrap_err();
+        assert_eq!(err.input(), *input);
+    }
+}
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[test]
+fn builder_uses_address_policy() {
+    use tokio::prelude::{future, AsyncWrite};
+    use tokio::runtime::current_thread::Runtime;
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+    use tokio_uniconnect::retry::is_retry_exhausted;
+
+    let refused = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let address = format!("retrying-tcp://{}?max_attempts=1", refused)
+        .parse()
+        .unwrap();
+    // retry config of the builder would reconnect forever
+    let mut conn = RetryingTcpOrSerial::new(address)
+        .with_retry(RetryConfig::default())
+        .build()
+        .unwrap();
+
+    let mut rt = Runtime::new().unwrap();
+    let exhausted = (0..5).any(|_| {
+        rt.block_on(future::poll_fn(|| conn.poll_write(b"x")))
+            .map_err(|err| is_retry_exhausted(&err))
+            .unwrap_err()
+    });
+    assert!(exhausted);
+}
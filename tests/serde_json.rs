@@ -0,0 +1,60 @@
+#![cfg(all(feature = "json", feature = "retrying-tcp"))]
+
+use serde_json::json;
+use tokio_uniconnect::retry::RetryConfig;
+use tokio_uniconnect::retrying_tcp_stream::{TcpKeepalive, TcpStreamSettings};
+
+use std::time::Duration;
+
+#[test]
+fn tcp_settings_round_trip() {
+    let mut settings = TcpStreamSettings::default();
+    settings.set_nodelay(true);
+    settings.set_first_byte_timeout(Some(Duration::from_millis(1500)));
+    settings.set_linger(Some(Duration::from_secs(2)));
+    settings.set_keepalive(Some(TcpKeepalive {
+        interval: Some(Duration::from_millis(250)),
+        ..TcpKeepalive::new(Duration::from_secs(60))
+    }));
+
+    let value = serde_json::to_value(&settings).unwrap();
+    assert_eq!(value["first_byte_timeout"], json!(1.5));
+    assert_eq!(value["linger"], json!(2.0));
+    assert_eq!(value["connect_timeout"], json!(null));
+    assert_eq!(value["keepalive"]["time"], json!(60.0));
+    assert_eq!(value["keepalive"]["interval"], json!(0.25));
+
+    let reloaded: TcpStreamSettings = serde_json::from_value(value).unwrap();
+    assert_eq!(reloaded, settings);
+}
+
+#[test]
+fn retry_config_round_trip() {
+    let config = RetryConfig {
+        initial_delay: Duration::from_millis(100),
+        max_delay: Duration::from_secs(10),
+        multiplier: 1.5,
+        max_attempts: Some(5),
+        ..RetryConfig::default()
+    }
+    .with_jitter_seed(3);
+
+    let value = serde_json::to_value(&config).unwrap();
+    assert_eq!(value["initial_delay"], json!(0.1));
+    assert_eq!(value["max_delay"], json!(10.0));
+
+    let reloaded: RetryConfig = serde_json::from_value(value).unwrap();
+    assert_eq!(reloaded, config);
+}
+
+#[test]
+fn retry_config_defaults_and_legacy_durations() {
+    let config: RetryConfig =
+        serde_json::from_str(r#"{"max_delay": {"secs": 3, "nanos": 0}, "initial_delay": 1}"#)
+            .unwrap();
+    assert_eq!(config.initial_delay, Duration::from_secs(1));
+    assert_eq!(config.max_delay, Duration::from_secs(3));
+    assert_eq!(config.multiplier, RetryConfig::default().multiplier);
+
+    assert!(serde_json::from_str::<RetryConfig>(r#"{"max_delay": -1.0}"#).is_err());
+}
@@ -0,0 +1,62 @@
+#![cfg(all(feature = "serde", feature = "serial", feature = "retrying-tcp"))]
+
+use serde::{Deserialize, Serialize};
+use tokio_uniconnect::address::ConnectionAddress;
+use tokio_uniconnect::builder::{DataBits, FlowControl, Parity, SerialPortSettings, StopBits};
+use tokio_uniconnect::retrying_tcp_stream::TcpStreamSettings;
+use tokio_uniconnect::serial::SerialConfig;
+
+use std::time::Duration;
+
+#[derive(Serialize, Deserialize)]
+struct Config {
+    address: ConnectionAddress,
+    serial: SerialConfig,
+    tcp: TcpStreamSettings,
+}
+
+const CONFIG: &str = r#"
+address = "serial:///dev/ttyUSB0"
+
+[serial]
+baud_rate = 19200
+data_bits = 7
+parity = "even"
+stop_bits = 2
+timeout_ms = 250
+
+[tcp]
+nodelay = true
+"#;
+
+#[test]
+fn toml_round_trip() {
+    let config: Config = toml::from_str(CONFIG).unwrap();
+    assert_eq!(
+        config.address,
+        ConnectionAddress::Serial("/dev/ttyUSB0".into())
+    );
+
+    let settings = SerialPortSettings::from(config.serial);
+    assert_eq!(settings.baud_rate, 19200);
+    assert_eq!(settings.data_bits, DataBits::Seven);
+    assert_eq!(settings.parity, Parity::Even);
+    assert_eq!(settings.stop_bits, StopBits::Two);
+    assert_eq!(settings.flow_control, FlowControl::None);
+    assert_eq!(settings.timeout, Duration::from_millis(250));
+
+    let reloaded: Config = toml::from_str(&toml::to_string(&config).unwrap()).unwrap();
+    assert_eq!(reloaded.address, config.address);
+    assert_eq!(reloaded.serial, config.serial);
+    assert_eq!(reloaded.tcp, config.tcp);
+    assert_eq!(
+        toml::Value::try_from(&config.tcp).unwrap()["nodelay"].as_bool(),
+        Some(true)
+    );
+}
+
+#[test]
+fn invalid_data_bits() {
+    let err = toml::from_str::<SerialConfig>("data_bits = 9").unwrap_err();
+    assert!(err.to_string().contains("invalid data bits 9"));
+}
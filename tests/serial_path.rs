@@ -0,0 +1,76 @@
+#![cfg(feature = "serial")]
+
+use tokio_uniconnect::serial::{normalize_serial_path, normalize_serial_path_checked};
+
+use std::path::PathBuf;
+
+#[cfg(not(windows))]
+#[test]
+fn bare_tty_names_get_dev_prefix() {
+    assert_eq!(
+        normalize_serial_path("ttyUSB0"),
+        PathBuf::from("/dev/ttyUSB0")
+    );
+    assert_eq!(
+        normalize_serial_path("ttyACM1"),
+        PathBuf::from("/dev/ttyACM1")
+    );
+    assert_eq!(normalize_serial_path("ttyS3"), PathBuf::from("/dev/ttyS3"));
+
+    let (path, warning) = normalize_serial_path_checked("ttyUSB0");
+    assert_eq!(warning.unwrap().normalized, path);
+}
+
+#[cfg(windows)]
+#[test]
+fn bare_com_ports_get_device_prefix() {
+    assert_eq!(normalize_serial_path("COM3"), PathBuf::from(r"\\.\COM3"));
+    assert_eq!(normalize_serial_path("com12"), PathBuf::from(r"\\.\com12"));
+}
+
+#[test]
+fn full_paths_are_unchanged() {
+    for input in &[
+        "/dev/ttyUSB0",
+        r"\\.\COM3",
+        "/dev/serial/by-id/usb-FTDI-if00",
+    ] {
+        let (path, warning) = normalize_serial_path_checked(input);
+        assert_eq!(path, PathBuf::from(input));
+        assert_eq!(warning, None);
+    }
+}
+
+#[test]
+fn validate_standard_baud_rates() {
+    use tokio_uniconnect::serial::{validate_baud_rate, UnsupportedBaudRate};
+
+    for baud in [9600, 19200, 115200] {
+        validate_baud_rate(baud).unwrap();
+        assert!(UnsupportedBaudRate::supported().contains(&baud));
+    }
+    let err = validate_baud_rate(12345).unwrap_err();
+    assert_eq!(err, UnsupportedBaudRate { baud: 12345 });
+    let message = err.to_string();
+    assert!(message.starts_with("baud rate 12345 is not supported, supported rates: "));
+    assert!(message.contains(", 9600, "));
+    assert_eq!(
+        std::io::Error::from(err).kind(),
+        std::io::ErrorKind::InvalidInput
+    );
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn builder_rejects_unsupported_baud_rate() {
+    use tokio_uniconnect::builder::{ConnectionAddress, RetryingTcpOrSerial};
+
+    let mut builder = RetryingTcpOrSerial::new(ConnectionAddress::Serial("/dev/ttyUSB0".into()));
+    builder.validate().unwrap();
+    builder.set_serial_port_settings(Some(tokio_serial::SerialPortSettings {
+        baud_rate: 12345,
+        ..Default::default()
+    }));
+    let err = builder.validate().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
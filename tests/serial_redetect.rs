@@ -0,0 +1,27 @@
+#![cfg(all(target_os = "linux", feature = "serial-redetect"))]
+
+use tokio::prelude::Stream;
+use tokio_uniconnect::serial::InotifySerialWatcher;
+
+use std::fs::File;
+use std::path::PathBuf;
+
+#[test]
+fn yields_replugged_device() {
+    let dir = std::env::temp_dir().join(format!("uniconnect-redetect-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+
+    let watcher = InotifySerialWatcher::new(dir.join("ttyUSB0")).unwrap();
+    for name in &["ttyACM0", "ttyUSB1", "ttyUSB", "notes.txt", "ttyUSB0"] {
+        File::create(dir.join(name)).unwrap();
+    }
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let found: Vec<PathBuf> = rt.block_on(watcher.take(3).collect()).unwrap();
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    assert_eq!(
+        found,
+        vec![dir.join("ttyUSB1"), dir.join("ttyUSB"), dir.join("ttyUSB0")]
+    );
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::future;
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::{OverflowPolicy, RetryingTcpStream, TcpStreamSettings};
+use tokio_uniconnect::shutdown::ShutdownCoordinator;
+use tokio_uniconnect::UniConnect;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+
+fn connected(listener: &TcpListener) -> (UniConnect, TcpStream) {
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (peer, _) = listener.accept().unwrap();
+    let stream = tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap();
+    (UniConnect::from(stream), peer)
+}
+
+// Stream with queued bytes that can never be sent
+fn unreachable_with_queued(rt: &mut Runtime) -> UniConnect {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut settings = TcpStreamSettings::default();
+    settings.set_write_buffer(Some(16), OverflowPolicy::Block);
+    let mut stream = RetryingTcpStream::connect_with_settings(&addr, settings);
+    rt.block_on(future::lazy(|| future::ok::<_, ()>(stream.write(b"lost"))))
+        .unwrap()
+        .unwrap();
+    UniConnect::from(stream)
+}
+
+#[test]
+fn shutdown_all_closes_every_connection() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (first, mut first_peer) = connected(&listener);
+    let (second, mut second_peer) = connected(&listener);
+
+    let mut coordinator = ShutdownCoordinator::new();
+    coordinator.register(first);
+    coordinator.register_labeled("second", second);
+    let errors = rt
+        .block_on(coordinator.shutdown_all(Duration::from_secs(1)))
+        .unwrap();
+
+    assert!(errors.is_empty());
+    assert_eq!(first_peer.read(&mut [0u8; 1]).unwrap(), 0);
+    assert_eq!(second_peer.read(&mut [0u8; 1]).unwrap(), 0);
+}
+
+#[test]
+fn shutdown_all_reports_failed_connections_by_label() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let (healthy, mut healthy_peer) = connected(&listener);
+    let stuck = unreachable_with_queued(&mut rt);
+
+    let mut coordinator = ShutdownCoordinator::new();
+    coordinator.register(healthy);
+    coordinator.register_labeled("stuck", stuck);
+    let errors = rt
+        .block_on(coordinator.shutdown_all(Duration::from_millis(200)))
+        .unwrap();
+
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].label, "stuck");
+    assert!(errors[0]
+        .to_string()
+        .starts_with("connection stuck shutdown failed"));
+    // the healthy connection is closed anyway
+    assert_eq!(healthy_peer.read(&mut [0u8; 1]).unwrap(), 0);
+}
@@ -0,0 +1,84 @@
+use bytes::Bytes;
+use tokio::codec::{Decoder, FramedRead, LengthDelimitedCodec};
+use tokio::net::TcpStream;
+use tokio::prelude::{stream, Future, Sink, Stream};
+use tokio::reactor::Handle;
+use tokio_uniconnect::UniConnect;
+
+use std::io;
+
+fn loopback_pair() -> (TcpStream, TcpStream) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    let handle = Handle::default();
+    (
+        TcpStream::from_std(client, &handle).unwrap(),
+        TcpStream::from_std(server, &handle).unwrap(),
+    )
+}
+
+fn frames() -> Vec<Bytes> {
+    (0..5)
+        .map(|i| Bytes::from(format!("frame {}", i).into_bytes()))
+        .collect()
+}
+
+#[test]
+fn sink_writes_frames() {
+    let (client, server) = loopback_pair();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+    let sink = UniConnect::from(client).into_sink(LengthDelimitedCodec::new());
+    let send = sink
+        .send_all(stream::iter_ok::<_, io::Error>(frames()))
+        .map(drop);
+    let received = FramedRead::new(server, LengthDelimitedCodec::new())
+        .take(5)
+        .collect();
+
+    let ((), received) = rt.block_on(send.join(received)).unwrap();
+    let received: Vec<Bytes> = received.into_iter().map(|frame| frame.freeze()).collect();
+    assert_eq!(received, frames());
+}
+
+#[test]
+fn byte_sink_writes_raw_bytes() {
+    let (client, server) = loopback_pair();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+    let send = UniConnect::from(client)
+        .into_byte_sink()
+        .send_all(stream::iter_ok::<_, io::Error>(frames()))
+        // drop the sink to close connection
+        .map(drop);
+    let received = tokio::io::read_to_end(server, Vec::new()).map(|(_, data)| data);
+
+    let ((), received) = rt.block_on(send.join(received)).unwrap();
+    assert_eq!(received, frames().concat());
+}
+
+#[test]
+fn framed_reads_and_writes_frames() {
+    let (client, server) = loopback_pair();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+    let (sink, stream) = UniConnect::from(client)
+        .framed(LengthDelimitedCodec::new())
+        .split();
+    let send = sink
+        .send_all(stream::iter_ok::<_, io::Error>(frames()))
+        .map(drop);
+    // echo every frame back
+    let (server_sink, server_stream) = LengthDelimitedCodec::new().framed(server).split();
+    let echo = server_stream
+        .take(5)
+        .map(|frame| frame.freeze())
+        .forward(server_sink)
+        .map(drop);
+    let received = stream.take(5).collect();
+
+    let ((), (), received) = rt.block_on(send.join3(echo, received)).unwrap();
+    let received: Vec<Bytes> = received.into_iter().map(|frame| frame.freeze()).collect();
+    assert_eq!(received, frames());
+}
@@ -0,0 +1,72 @@
+use tokio::net::TcpStream;
+use tokio::reactor::Handle;
+use tokio_uniconnect::UniConnect;
+
+use std::io;
+use std::net::TcpListener;
+
+#[test]
+fn tcp_stream_addresses() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let local = client.local_addr().unwrap();
+    let conn = UniConnect::from(TcpStream::from_std(client, &Handle::default()).unwrap());
+
+    assert_eq!(conn.local_addr().unwrap(), local);
+    assert_eq!(conn.peer_addr().unwrap(), listener.local_addr().unwrap());
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn retrying_tcp_stream_addresses() {
+    use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = std::net::TcpStream::connect(addr).unwrap();
+    let local = client.local_addr().unwrap();
+    let retrying = RetryingTcpStream::from_std(client, &Handle::default()).unwrap();
+    let mut conn = UniConnect::from(retrying);
+    assert_eq!(conn.local_addr().unwrap(), local);
+    assert_eq!(conn.peer_addr().unwrap(), addr);
+
+    if let UniConnect::RetringTcpStream(inner) = &mut conn {
+        inner.force_reconnect();
+    }
+    // remote address is known while reconnecting, local one not
+    assert_eq!(conn.peer_addr().unwrap(), addr);
+    assert_eq!(
+        conn.local_addr().unwrap_err().kind(),
+        io::ErrorKind::NotConnected
+    );
+}
+
+#[cfg(unix)]
+#[test]
+fn unix_stream_has_no_socket_address() {
+    let (stream, _peer) = std::os::unix::net::UnixStream::pair().unwrap();
+    let conn =
+        UniConnect::from(tokio::net::UnixStream::from_std(stream, &Handle::default()).unwrap());
+    assert_eq!(
+        conn.peer_addr().unwrap_err().kind(),
+        io::ErrorKind::Unsupported
+    );
+    assert_eq!(
+        conn.local_addr().unwrap_err().kind(),
+        io::ErrorKind::Unsupported
+    );
+}
+
+#[cfg(all(unix, feature = "serial"))]
+#[test]
+fn serial_port_has_no_socket_address() {
+    let (master, _slave) = tokio_serial::Serial::pair().unwrap();
+    let conn = UniConnect::from(master);
+    let err = conn.peer_addr().unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    assert_eq!(err.to_string(), "serial port has no socket address");
+    assert_eq!(
+        conn.local_addr().unwrap_err().kind(),
+        io::ErrorKind::Unsupported
+    );
+}
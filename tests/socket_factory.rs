@@ -0,0 +1,80 @@
+#![cfg(feature = "retrying-tcp")]
+
+use socket2::{Domain, Socket, Type};
+use tokio::prelude::future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::io::ErrorKind;
+use std::net::{SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex};
+
+// Socket bound to a random local port, so the peer can tell which socket connected
+fn bound_socket(addr: &SocketAddr) -> std::io::Result<Socket> {
+    let socket = Socket::new(Domain::for_address(*addr), Type::STREAM, None)?;
+    socket.bind(&"127.0.0.1:0".parse::<SocketAddr>().unwrap().into())?;
+    Ok(socket)
+}
+
+fn local_addr(socket: &Socket) -> SocketAddr {
+    socket.local_addr().unwrap().as_socket().unwrap()
+}
+
+#[test]
+fn connect_from_socket_uses_given_socket() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let socket = bound_socket(&addr).unwrap();
+    let local = local_addr(&socket);
+
+    let stream = rt
+        .block_on(RetryingTcpStream::connect_from_socket(socket, addr))
+        .unwrap();
+    assert_eq!(stream.peer_addr().unwrap(), addr);
+    assert_eq!(listener.accept().unwrap().1, local);
+}
+
+#[test]
+fn factory_creates_socket_for_every_connect() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let bound = Arc::new(Mutex::new(Vec::new()));
+    let factory_bound = bound.clone();
+    let mut stream = RetryingTcpStream::connect(&listener.local_addr().unwrap())
+        .with_socket_factory(move |addr| {
+            let socket = bound_socket(addr)?;
+            factory_bound.lock().unwrap().push(local_addr(&socket));
+            Ok(socket)
+        });
+
+    for reconnect in [false, true] {
+        if reconnect {
+            stream.force_reconnect();
+        }
+        rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+            .unwrap();
+        // attempt started before `with_socket_factory` may reach the listener as well
+        let peer = loop {
+            let (_, peer) = listener.accept().unwrap();
+            if bound.lock().unwrap().contains(&peer) {
+                break peer;
+            }
+        };
+        assert_eq!(Some(&peer), bound.lock().unwrap().last());
+    }
+    assert_eq!(bound.lock().unwrap().len(), 2);
+}
+
+#[test]
+fn factory_error_fails_connect() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = RetryingTcpStream::connect(&listener.local_addr().unwrap())
+        .with_socket_factory(|_| Err(std::io::Error::new(ErrorKind::AddrInUse, "no socket")));
+
+    let err = rt
+        .block_on(future::poll_fn(|| stream.poll_write_ready()))
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::AddrInUse);
+}
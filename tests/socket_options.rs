@@ -0,0 +1,58 @@
+#![cfg(all(unix, feature = "retrying-tcp"))]
+
+use tokio::prelude::future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::{RetryingTcpStream, TcpKeepalive, TcpStreamSettings};
+
+use std::net::TcpListener;
+use std::os::unix::io::{AsRawFd, BorrowedFd};
+use std::time::Duration;
+
+fn settings() -> TcpStreamSettings {
+    let mut settings = TcpStreamSettings::default();
+    settings.set_keepalive(Some(TcpKeepalive {
+        time: Duration::from_secs(30),
+        interval: Some(Duration::from_secs(5)),
+        retries: Some(3),
+    }));
+    settings.set_linger(Some(Duration::from_secs(2)));
+    settings.set_recv_buffer_size(Some(64 * 1024));
+    settings.set_send_buffer_size(Some(32 * 1024));
+    settings
+}
+
+fn check_options(stream: &RetryingTcpStream) {
+    let ts = stream.as_tcp_stream().expect("connected");
+    let fd = unsafe { BorrowedFd::borrow_raw(ts.as_raw_fd()) };
+    let socket = socket2::SockRef::from(&fd);
+    assert!(socket.keepalive().unwrap());
+    assert_eq!(socket.keepalive_time().unwrap(), Duration::from_secs(30));
+    assert_eq!(socket.linger().unwrap(), Some(Duration::from_secs(2)));
+    // kernel may round buffer sizes up
+    assert!(socket.recv_buffer_size().unwrap() >= 64 * 1024);
+    assert!(socket.send_buffer_size().unwrap() >= 32 * 1024);
+}
+
+#[test]
+fn options_applied_after_every_reconnect() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream =
+        RetryingTcpStream::connect_with_settings(&listener.local_addr().unwrap(), settings());
+
+    for _ in 0..2 {
+        rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+            .unwrap();
+        check_options(&stream);
+        stream.force_reconnect();
+    }
+}
+
+#[test]
+fn settings_display_socket_options() {
+    let description = settings().to_string();
+    assert!(description.contains("keepalive=30s"), "{}", description);
+    assert!(description.contains("linger=2000ms"), "{}", description);
+    assert!(description.contains("recv_buffer=65536"), "{}", description);
+    assert!(description.contains("send_buffer=32768"), "{}", description);
+}
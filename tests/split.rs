@@ -0,0 +1,51 @@
+use tokio::net::TcpStream;
+use tokio::prelude::Future;
+use tokio::reactor::Handle;
+use tokio_uniconnect::UniConnect;
+
+fn loopback_pair() -> (TcpStream, TcpStream) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    let handle = Handle::default();
+    (
+        TcpStream::from_std(client, &handle).unwrap(),
+        TcpStream::from_std(server, &handle).unwrap(),
+    )
+}
+
+#[test]
+fn halves_read_and_write_concurrently() {
+    let (client, server) = loopback_pair();
+    let (read, write) = UniConnect::from(client).split();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+
+    // server echoes what it receives
+    let (server_read, server_write) = tokio::io::AsyncRead::split(server);
+    rt.spawn(
+        tokio::io::copy(server_read, server_write)
+            .map(drop)
+            .map_err(drop),
+    );
+
+    let reader = tokio::io::read_exact(read, [0u8; 5]);
+    let writer = tokio::io::write_all(write, b"hello");
+    let ((read, buf), (write, _)) = rt.block_on(reader.join(writer)).unwrap();
+    assert_eq!(&buf, b"hello");
+
+    let conn = UniConnect::reunite(read, write).unwrap();
+    assert!(matches!(conn, UniConnect::TcpStream(_)));
+}
+
+#[test]
+fn halves_of_different_connections_do_not_reunite() {
+    let (first, _first_peer) = loopback_pair();
+    let (second, _second_peer) = loopback_pair();
+    let (first_read, first_write) = UniConnect::from(first).split();
+    let (second_read, second_write) = UniConnect::from(second).split();
+
+    let err = UniConnect::reunite(first_read, second_write).unwrap_err();
+    // halves are given back
+    assert!(err.0.reunite(first_write).is_ok());
+    assert!(second_read.reunite(err.1).is_ok());
+}
@@ -0,0 +1,150 @@
+use bytes::{BufMut, BytesMut};
+use tokio::codec::{Decoder, Encoder, LinesCodec};
+use tokio_uniconnect::codec::StackedCodec;
+use tokio_uniconnect::codec_stack;
+
+use std::io;
+
+const END: u8 = 0xC0;
+const ESC: u8 = 0xDB;
+const ESC_END: u8 = 0xDC;
+const ESC_ESC: u8 = 0xDD;
+
+/// RFC 1055 SLIP framing
+struct SlipCodec;
+
+impl Decoder for SlipCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>, io::Error> {
+        let end = match src.iter().position(|b| *b == END) {
+            Some(end) => end,
+            None => return Ok(None),
+        };
+        let frame = src.split_to(end + 1);
+        let mut payload = BytesMut::with_capacity(end);
+        let mut escaped = false;
+        for &b in &frame[..end] {
+            match (escaped, b) {
+                (false, ESC) => escaped = true,
+                (true, ESC_END) | (true, ESC_ESC) => {
+                    payload.put_u8(if b == ESC_END { END } else { ESC });
+                    escaped = false;
+                }
+                (true, _) => return Err(io::Error::new(io::ErrorKind::InvalidData, "bad escape")),
+                (false, b) => payload.put_u8(b),
+            }
+        }
+        Ok(Some(payload))
+    }
+}
+
+impl Encoder for SlipCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn encode(&mut self, payload: BytesMut, dst: &mut BytesMut) -> Result<(), io::Error> {
+        dst.reserve(payload.len() * 2 + 1);
+        for &b in payload.iter() {
+            match b {
+                END => dst.put_slice(&[ESC, ESC_END]),
+                ESC => dst.put_slice(&[ESC, ESC_ESC]),
+                b => dst.put_u8(b),
+            }
+        }
+        dst.put_u8(END);
+        Ok(())
+    }
+}
+
+/// Length prefixed frame followed by CRC-16/CCITT-FALSE
+struct CrcCodec;
+
+fn crc16(data: &[u8]) -> u16 {
+    data.iter().fold(0xFFFF, |mut crc: u16, &b| {
+        crc ^= u16::from(b) << 8;
+        for _ in 0..8 {
+            crc = if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            };
+        }
+        crc
+    })
+}
+
+impl Decoder for CrcCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<BytesMut>, io::Error> {
+        if src.is_empty() || src.len() < 1 + src[0] as usize + 2 {
+            return Ok(None);
+        }
+        let len = src[0] as usize;
+        let frame = src.split_to(1 + len + 2);
+        let crc = u16::from_be_bytes([frame[1 + len], frame[2 + len]]);
+        if crc != crc16(&frame[1..1 + len]) {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "CRC mismatch"));
+        }
+        Ok(Some(BytesMut::from(&frame[1..1 + len])))
+    }
+}
+
+impl Encoder for CrcCodec {
+    type Item = BytesMut;
+    type Error = io::Error;
+
+    fn encode(&mut self, payload: BytesMut, dst: &mut BytesMut) -> Result<(), io::Error> {
+        dst.reserve(payload.len() + 3);
+        dst.put_u8(payload.len() as u8);
+        dst.put_slice(&payload);
+        dst.put_u16_be(crc16(&payload));
+        Ok(())
+    }
+}
+
+#[test]
+fn slip_crc_round_trip() {
+    let mut codec = StackedCodec::new(SlipCodec, CrcCodec);
+    // payload with both SLIP special bytes
+    let payload = BytesMut::from(&[0x01, END, 0x02, ESC, 0x03][..]);
+
+    let mut wire = BytesMut::new();
+    codec.encode(payload.clone(), &mut wire).unwrap();
+    codec
+        .encode(BytesMut::from(&b"second"[..]), &mut wire)
+        .unwrap();
+    assert_eq!(wire.iter().filter(|b| **b == END).count(), 2);
+
+    assert_eq!(codec.decode(&mut wire).unwrap(), Some(payload));
+    assert_eq!(
+        codec.decode(&mut wire).unwrap(),
+        Some(BytesMut::from(&b"second"[..]))
+    );
+    assert_eq!(codec.decode(&mut wire).unwrap(), None);
+}
+
+#[test]
+fn corrupted_frame_is_rejected() {
+    let mut codec = StackedCodec::new(SlipCodec, CrcCodec);
+    let mut wire = BytesMut::new();
+    codec
+        .encode(BytesMut::from(&b"data"[..]), &mut wire)
+        .unwrap();
+    wire[2] ^= 0xFF;
+
+    let err = codec.decode(&mut wire).unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn three_layer_stack() {
+    let mut codec = codec_stack!(SlipCodec, CrcCodec, LinesCodec::new());
+    let mut wire = BytesMut::new();
+    codec.encode("hello".to_string(), &mut wire).unwrap();
+
+    assert_eq!(codec.decode(&mut wire).unwrap(), Some("hello".to_string()));
+}
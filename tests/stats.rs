@@ -0,0 +1,61 @@
+use tokio::net::TcpStream;
+use tokio::prelude::Future;
+use tokio::reactor::Handle;
+use tokio_uniconnect::stats::UniConnectStats;
+use tokio_uniconnect::UniConnect;
+
+#[test]
+fn counts_bytes() {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    std::thread::spawn(move || {
+        let (mut read, mut write) = (server.try_clone().unwrap(), server);
+        std::io::copy(&mut read, &mut write).unwrap();
+    });
+    let client = TcpStream::from_std(client, &Handle::default()).unwrap();
+
+    let conn = UniConnect::from(client).with_stats();
+    let exchange = tokio::io::write_all(conn, b"hello")
+        .and_then(|(conn, _)| tokio::io::read_exact(conn, [0u8; 3]))
+        .map(|(conn, _)| conn);
+    let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    let conn = rt.block_on(exchange).unwrap();
+
+    assert_eq!(
+        *conn.stats(),
+        UniConnectStats {
+            bytes_read: 3,
+            bytes_written: 5,
+            reconnect_count: 0,
+        }
+    );
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn counts_reconnects() {
+    use std::io::Write;
+    use tokio::prelude::future;
+    use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+    // nobody listens on this address
+    let addr = std::net::TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut conn = UniConnect::from(RetryingTcpStream::connect(&addr)).with_stats();
+    let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+
+    for reconnects in 1..=2 {
+        let failed = rt.block_on(future::poll_fn(|| match conn.write(b"x") {
+            Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => {
+                Ok(futures::Async::NotReady)
+            }
+            res => res.map(futures::Async::Ready),
+        }));
+        assert!(failed.is_err());
+        assert_eq!(conn.stats().reconnect_count, reconnects);
+    }
+    assert_eq!(conn.stats().bytes_written, 0);
+}
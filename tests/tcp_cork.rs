@@ -0,0 +1,64 @@
+#![cfg(target_os = "linux")]
+
+use tokio::reactor::Handle;
+use tokio_uniconnect::UniConnect;
+
+use std::net::{TcpListener, TcpStream};
+use std::os::unix::io::{AsRawFd, BorrowedFd, RawFd};
+
+fn corked(fd: RawFd) -> bool {
+    // fd is owned by the connection that outlives this call
+    let fd = unsafe { BorrowedFd::borrow_raw(fd) };
+    socket2::SockRef::from(&fd).cork().unwrap()
+}
+
+#[test]
+fn set_cork_toggles_option() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let fd = stream.as_raw_fd();
+    let conn =
+        UniConnect::from(tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap());
+
+    assert!(!corked(fd));
+    conn.set_cork(true).unwrap();
+    assert!(corked(fd));
+    conn.set_cork(false).unwrap();
+    assert!(!corked(fd));
+}
+
+#[test]
+fn unix_socket_has_no_cork() {
+    let (a, _b) = tokio::net::UnixStream::pair().unwrap();
+    let err = UniConnect::from(a).set_cork(true).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+}
+
+#[cfg(feature = "retrying-tcp")]
+#[test]
+fn cork_is_set_on_every_connect() {
+    use tokio::prelude::future;
+    use tokio::runtime::current_thread::Runtime;
+    use tokio_uniconnect::retrying_tcp_stream::{RetryingTcpStream, TcpStreamSettings};
+
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut settings = TcpStreamSettings::default();
+    settings.set_tcp_cork_on_connect(true);
+    let mut stream =
+        RetryingTcpStream::connect_with_settings(&listener.local_addr().unwrap(), settings);
+
+    let err = stream.set_cork(false).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotConnected);
+
+    for _ in 0..2 {
+        rt.block_on(future::poll_fn(|| stream.poll_write_ready()))
+            .unwrap();
+        let fd = stream.as_tcp_stream().unwrap().as_raw_fd();
+        assert!(corked(fd));
+        // uncorked connection is dropped, the next one is corked again
+        stream.set_cork(false).unwrap();
+        assert!(!corked(fd));
+        stream.force_reconnect();
+    }
+}
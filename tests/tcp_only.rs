@@ -0,0 +1,39 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::TcpStreamSettings;
+use tokio_uniconnect::tcp_only::TcpOnly;
+use tokio_uniconnect::UniConnect;
+
+use std::net::TcpListener;
+
+#[test]
+fn connects_plain_stream() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut settings = TcpStreamSettings::default();
+    settings.set_nodelay(true);
+    let mut rt = Runtime::new().unwrap();
+
+    let conn = rt
+        .block_on(
+            TcpOnly::new(listener.local_addr().unwrap())
+                .with_tcp_settings(settings)
+                .build(),
+        )
+        .unwrap();
+    match &conn {
+        UniConnect::TcpStream(tcp) => assert!(tcp.nodelay().unwrap()),
+        _ => panic!("expected TcpStream"),
+    }
+    assert_eq!(conn.peer_addr().unwrap(), listener.local_addr().unwrap());
+}
+
+#[test]
+fn refused_connection_fails() {
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut rt = Runtime::new().unwrap();
+    assert!(rt.block_on(TcpOnly::new(addr).build()).is_err());
+}
@@ -0,0 +1,84 @@
+use tokio::net::TcpStream;
+use tokio::prelude::{Future, Write};
+use tokio::reactor::Handle;
+use tokio::runtime::current_thread::Runtime;
+use tokio::timer::Delay;
+use tokio_uniconnect::timeout::{ReadTimeout, WriteTimeout};
+use tokio_uniconnect::UniConnect;
+
+use std::io;
+use std::time::{Duration, Instant};
+
+fn loopback_pair() -> (TcpStream, std::net::TcpStream) {
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = std::net::TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let (server, _) = listener.accept().unwrap();
+    (
+        TcpStream::from_std(client, &Handle::default()).unwrap(),
+        server,
+    )
+}
+
+#[test]
+fn read_without_data_times_out() {
+    let (client, _server) = loopback_pair();
+    let mut rt = Runtime::new().unwrap();
+
+    let conn = UniConnect::from(client).with_timeout(Duration::from_millis(50));
+    let start = Instant::now();
+    let err = rt
+        .block_on(tokio::io::read(conn, vec![0u8; 4]))
+        .unwrap_err();
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn data_arriving_in_time_is_read() {
+    let (client, mut server) = loopback_pair();
+    let mut rt = Runtime::new().unwrap();
+
+    let conn = UniConnect::from(client).with_timeout(Duration::from_millis(500));
+    let writer = std::thread::spawn(move || {
+        std::thread::sleep(Duration::from_millis(20));
+        server.write_all(b"data").unwrap();
+        server
+    });
+    let (_conn, buf) = rt
+        .block_on(tokio::io::read_exact(conn, vec![0u8; 4]))
+        .unwrap();
+    assert_eq!(buf, b"data");
+    writer.join().unwrap();
+}
+
+#[test]
+fn read_timeout_can_be_disabled() {
+    let (client, _server) = loopback_pair();
+    let mut rt = Runtime::new().unwrap();
+
+    let mut conn = UniConnect::from(client).with_timeout(Duration::from_millis(10));
+    conn.set_read_timeout(None);
+    conn.set_write_timeout(Some(WriteTimeout(Duration::from_millis(20))));
+    assert_eq!(conn.read_timeout(), None);
+    assert_eq!(
+        conn.write_timeout(),
+        Some(WriteTimeout(Duration::from_millis(20)))
+    );
+
+    let read = tokio::io::read(conn, vec![0u8; 4]).map(|_| "read");
+    let wait = Delay::new(Instant::now() + Duration::from_millis(100))
+        .map(|()| "waited")
+        .map_err(io::Error::other);
+    let (winner, _) = rt
+        .block_on(read.select(wait))
+        .map_err(|(err, _)| err)
+        .unwrap();
+    assert_eq!(winner, "waited");
+
+    let mut conn = UniConnect::from(loopback_pair().0).with_timeout(Duration::from_secs(1));
+    conn.set_read_timeout(Some(ReadTimeout(Duration::from_millis(30))));
+    assert_eq!(
+        conn.read_timeout(),
+        Some(ReadTimeout(Duration::from_millis(30)))
+    );
+}
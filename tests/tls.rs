@@ -0,0 +1,167 @@
+#![cfg(feature = "tls")]
+
+use futures::{Future, Stream};
+use tokio::net::TcpListener;
+use tokio_rustls::rustls::internal::pemfile::{certs, pkcs8_private_keys};
+use tokio_rustls::rustls::{NoClientAuth, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tokio_uniconnect::address::ConnectionAddress;
+use tokio_uniconnect::tls::{connector_with_server_cert_pem, tls_connect};
+use tokio_uniconnect::UniConnect;
+
+use std::io::BufReader;
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+// self-signed certificate for `localhost` as (cert PEM, key PEM)
+fn self_signed() -> (String, String) {
+    let cert = rcgen::generate_simple_self_signed(vec!["localhost".to_string()]).unwrap();
+    (cert.cert.pem(), cert.key_pair.serialize_pem())
+}
+
+// TLS echo server accepting one connection
+fn echo_server(cert_pem: &str, key_pem: &str) -> (SocketAddr, impl Future<Item = (), Error = ()>) {
+    let certs = certs(&mut BufReader::new(cert_pem.as_bytes())).unwrap();
+    let key = pkcs8_private_keys(&mut BufReader::new(key_pem.as_bytes()))
+        .unwrap()
+        .remove(0);
+    let mut config = ServerConfig::new(NoClientAuth::new());
+    config.set_single_cert(certs, key).unwrap();
+    let acceptor = TlsAcceptor::from(Arc::new(config));
+
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = listener
+        .incoming()
+        .into_future()
+        .map_err(|(err, _)| panic!("accept failed: {}", err))
+        .and_then(move |(socket, _)| acceptor.accept(socket.unwrap()))
+        .and_then(|tls| tokio::io::read_exact(tls, [0u8; 5]))
+        .and_then(|(tls, buf)| tokio::io::write_all(tls, buf))
+        .and_then(|(tls, _)| tokio::io::flush(tls))
+        .map(drop)
+        // handshake failures are checked by the client
+        .map_err(drop);
+    (addr, server)
+}
+
+#[test]
+fn round_trip_with_pinned_certificate() {
+    let (cert_pem, key_pem) = self_signed();
+    let (addr, server) = echo_server(&cert_pem, &key_pem);
+    let connector = connector_with_server_cert_pem(cert_pem.as_bytes()).unwrap();
+
+    let client = tls_connect(&addr, "localhost", connector).and_then(move |conn| {
+        assert!(matches!(conn, UniConnect::TlsStream(_)));
+        assert_eq!(conn.to_string(), format!("tcp://{}", addr));
+        tokio::io::write_all(conn, b"hello")
+            .and_then(|(conn, _)| tokio::io::read_exact(conn, [0u8; 5]))
+            .map(|(_, buf)| buf)
+    });
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+    assert_eq!(&rt.block_on(client).unwrap(), b"hello");
+}
+
+#[test]
+fn certificate_for_other_domain_is_rejected() {
+    let (cert_pem, key_pem) = self_signed();
+    let (addr, server) = echo_server(&cert_pem, &key_pem);
+    let connector = connector_with_server_cert_pem(cert_pem.as_bytes()).unwrap();
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+    assert!(rt
+        .block_on(tls_connect(&addr, "example.com", connector))
+        .is_err());
+}
+
+#[test]
+fn invalid_pem_is_rejected() {
+    let err = connector_with_server_cert_pem(b"not a certificate").err();
+    assert_eq!(
+        err.map(|err| err.kind()),
+        Some(std::io::ErrorKind::InvalidData)
+    );
+}
+
+#[test]
+fn tls_address_round_trip() {
+    let address: ConnectionAddress = "tls://example.com:8443".parse().unwrap();
+    assert_eq!(
+        address,
+        ConnectionAddress::Tls {
+            domain: "example.com".into(),
+            port: 8443
+        }
+    );
+    assert_eq!(address.to_string(), "tls://example.com:8443");
+    assert!("tls://example.com".parse::<ConnectionAddress>().is_err());
+    assert!("tls://:443".parse::<ConnectionAddress>().is_err());
+}
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[test]
+fn builder_needs_async_build_and_connector() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+
+    let address: ConnectionAddress = "tls://localhost:8443".parse().unwrap();
+    let err = RetryingTcpOrSerial::new(address.clone())
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let err = rt
+        .block_on(RetryingTcpOrSerial::new(address).build_async())
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[test]
+fn builder_connects_with_server_cert() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+
+    let (cert_pem, key_pem) = self_signed();
+    let (addr, server) = echo_server(&cert_pem, &key_pem);
+    let mut builder = RetryingTcpOrSerial::new(ConnectionAddress::Tls {
+        domain: "localhost".into(),
+        port: addr.port(),
+    });
+    builder.set_server_cert_pem(cert_pem.as_bytes()).unwrap();
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+    let client = builder.build_async().and_then(|conn| {
+        tokio::io::write_all(conn, b"hello")
+            .and_then(|(conn, _)| tokio::io::read_exact(conn, [0u8; 5]))
+            .map(|(_, buf)| buf)
+    });
+    assert_eq!(&rt.block_on(client).unwrap(), b"hello");
+}
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[test]
+fn builder_uses_given_connector() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+
+    let (cert_pem, key_pem) = self_signed();
+    let (addr, server) = echo_server(&cert_pem, &key_pem);
+    let connector = connector_with_server_cert_pem(cert_pem.as_bytes()).unwrap();
+    let builder = RetryingTcpOrSerial::new(ConnectionAddress::Tls {
+        domain: "localhost".into(),
+        port: addr.port(),
+    })
+    .with_tls_connector(connector);
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.spawn(server);
+    let client = builder.build_async().and_then(|conn| {
+        tokio::io::write_all(conn, b"hello")
+            .and_then(|(conn, _)| tokio::io::read_exact(conn, [0u8; 5]))
+            .map(|(_, buf)| buf)
+    });
+    assert_eq!(&rt.block_on(client).unwrap(), b"hello");
+}
@@ -0,0 +1,16 @@
+use static_assertions::assert_impl_all;
+use tokio_uniconnect::dynamic::DynUniConnect;
+use tokio_uniconnect::UniConnect;
+
+// not Sync, RetryingTcpStream keeps `Box<dyn Future + Send>` while connecting
+assert_impl_all!(UniConnect: Send, Unpin, std::fmt::Debug, std::fmt::Display);
+assert_impl_all!(DynUniConnect: Send, Unpin, std::fmt::Debug);
+
+#[cfg(feature = "retrying-tcp")]
+mod retrying {
+    use static_assertions::assert_impl_all;
+    use tokio_uniconnect::retrying_tcp_stream::{RetryingTcpStream, TcpStreamSettings};
+
+    assert_impl_all!(RetryingTcpStream: Send, Unpin);
+    assert_impl_all!(TcpStreamSettings: Clone, Default, PartialEq, std::fmt::Debug);
+}
@@ -0,0 +1,37 @@
+#![cfg(unix)]
+
+use tokio::prelude::Future;
+use tokio::reactor::Handle;
+use tokio_uniconnect::UniConnect;
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+
+#[test]
+fn clone_shares_socket() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(listener.local_addr().unwrap()).unwrap();
+    let conn =
+        UniConnect::from(tokio::net::TcpStream::from_std(stream, &Handle::default()).unwrap());
+    let (mut peer, _) = listener.accept().unwrap();
+
+    let clone = conn.try_clone().unwrap();
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    rt.block_on(tokio::io::write_all(clone, b"from clone").map(drop))
+        .unwrap();
+    let mut buf = [0u8; 10];
+    peer.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"from clone");
+
+    // socket stays open while original exists
+    peer.write_all(b"to original").unwrap();
+    let (_, buf) = rt.block_on(tokio::io::read_exact(conn, [0u8; 11])).unwrap();
+    assert_eq!(&buf, b"to original");
+}
+
+#[test]
+fn unix_stream_is_not_cloned() {
+    let (a, _b) = tokio::net::UnixStream::pair().unwrap();
+    let err = UniConnect::from(a).try_clone().unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+}
@@ -0,0 +1,82 @@
+#![cfg(all(feature = "serial", feature = "retrying-tcp"))]
+
+use tokio_uniconnect::builder::{Builder, TcpStreamSettings};
+use tokio_uniconnect::UniConnect;
+
+use std::net::TcpListener;
+
+#[test]
+fn builds_tcp_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let conn = Builder::new()
+        .set_serial_path("/dev/ttyUSB0")
+        .set_tcp_addr(listener.local_addr().unwrap())
+        .set_tcp_settings(TcpStreamSettings::default())
+        .build()
+        .unwrap();
+    match conn {
+        UniConnect::RetringTcpStream(_) => {}
+        _ => panic!("expected RetryingTcpStream"),
+    }
+}
+
+#[test]
+fn missing_serial_port_fails_on_build() {
+    let result = Builder::new()
+        .set_serial_path("/dev/uniconnect-does-not-exist")
+        .build();
+    assert!(result.is_err());
+}
+
+#[test]
+fn chained_builder_applies_retry_config() {
+    use tokio::prelude::future;
+    use tokio::prelude::AsyncWrite;
+    use tokio_uniconnect::builder::{ConnectionAddress, RetryingTcpOrSerial};
+    use tokio_uniconnect::retry::{is_retry_exhausted, RetryConfig};
+
+    // nobody listens after the listener is dropped
+    let addr = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap();
+    let mut conn = RetryingTcpOrSerial::new(ConnectionAddress::Tcp(addr))
+        .with_tcp_settings(TcpStreamSettings::default())
+        .with_retry(RetryConfig {
+            max_attempts: Some(1),
+            ..RetryConfig::default()
+        })
+        .with_connect_timeout(std::time::Duration::from_secs(5))
+        .build()
+        .unwrap();
+
+    let mut rt = tokio::runtime::current_thread::Runtime::new().unwrap();
+    // first connect and the only reconnect fail with the connection error
+    for _ in 0..2 {
+        let err = rt
+            .block_on(future::poll_fn(|| conn.poll_flush()))
+            .unwrap_err();
+        assert!(!is_retry_exhausted(&err), "{}", err);
+    }
+    let err = rt
+        .block_on(future::poll_fn(|| conn.poll_flush()))
+        .unwrap_err();
+    assert!(is_retry_exhausted(&err), "{}", err);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn connect_timeout_is_part_of_tcp_settings() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+
+    let mut builder = RetryingTcpOrSerial::new("tcp://127.0.0.1:5000".parse().unwrap());
+    builder
+        .set_tcp_settings(None)
+        .set_connect_timeout(Some(std::time::Duration::from_millis(1500)));
+    let tcp = builder.to_config().tcp.unwrap();
+    assert!(
+        tcp.to_string().contains("connect_timeout=1500ms"),
+        "{}",
+        tcp
+    );
+}
@@ -0,0 +1,111 @@
+use tokio::prelude::{future, Async, Future};
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::address::ConnectionAddress;
+use tokio_uniconnect::udp::UdpConn;
+use tokio_uniconnect::{ConnectionKind, UniConnect};
+
+use std::io;
+use std::net::UdpSocket;
+use std::time::Duration;
+
+#[test]
+fn exchanges_datagrams_with_peer() {
+    let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+    peer.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+    let conn: UniConnect = format!("udp://{}", peer.local_addr().unwrap())
+        .parse()
+        .unwrap();
+    assert_eq!(conn.kind(), ConnectionKind::Udp);
+    assert_eq!(conn.peer_addr().unwrap(), peer.local_addr().unwrap());
+    let local = conn.local_addr().unwrap();
+
+    let mut rt = Runtime::new().unwrap();
+    let conn = rt.block_on(tokio::io::write_all(conn, b"ping")).unwrap().0;
+    let mut buf = [0u8; 16];
+    let (n, from) = peer.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"ping");
+    assert_eq!(from.port(), local.port());
+
+    // datagram from another source is discarded
+    let stranger = UdpSocket::bind("127.0.0.1:0").unwrap();
+    stranger
+        .send_to(b"noise", ("127.0.0.1", local.port()))
+        .unwrap();
+    peer.send_to(b"pong", ("127.0.0.1", local.port())).unwrap();
+
+    let read = tokio::io::read(conn, [0u8; 16]).map(|(_, buf, n)| buf[..n].to_vec());
+    assert_eq!(rt.block_on(read).unwrap(), b"pong");
+}
+
+#[test]
+fn parses_udp_address() {
+    let address: ConnectionAddress = "udp://[::1]:5000".parse().unwrap();
+    assert_eq!(
+        address,
+        ConnectionAddress::Udp {
+            hostname: "::1".into(),
+            port: 5000
+        }
+    );
+    assert_eq!(address.to_string(), "udp://[::1]:5000");
+    assert_eq!(
+        "udp://plc.local:5000"
+            .parse::<ConnectionAddress>()
+            .unwrap()
+            .to_string(),
+        "udp://plc.local:5000"
+    );
+    assert!("udp://plc.local".parse::<ConnectionAddress>().is_err());
+}
+
+#[cfg(all(feature = "serial", feature = "retrying-tcp"))]
+#[test]
+fn builder_binds_udp_socket() {
+    use tokio_uniconnect::builder::RetryingTcpOrSerial;
+
+    let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let address = format!("udp://{}", peer.local_addr().unwrap());
+    let conn = RetryingTcpOrSerial::new(address.parse().unwrap())
+        .build()
+        .unwrap();
+    assert_eq!(conn.kind(), ConnectionKind::Udp);
+    assert_eq!(conn.redacted().to_string(), address);
+}
+
+#[test]
+fn broadcast_and_answers_from_any_source() {
+    let peer = UdpSocket::bind("127.0.0.1:0").unwrap();
+    let device = UdpSocket::bind("127.0.0.1:0").unwrap();
+    device
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    let mut conn = UdpConn::bind(&peer.local_addr().unwrap()).unwrap();
+    assert!(!conn.get_ref().broadcast().unwrap());
+    conn.enable_broadcast().unwrap();
+    assert!(conn.get_ref().broadcast().unwrap());
+
+    let mut rt = Runtime::new().unwrap();
+    let device_addr = device.local_addr().unwrap();
+    let sent = rt
+        .block_on(future::poll_fn(|| {
+            match conn.send_broadcast(&device_addr, b"identify") {
+                Ok(n) => Ok(Async::Ready(n)),
+                Err(err) if err.kind() == io::ErrorKind::WouldBlock => Ok(Async::NotReady),
+                Err(err) => Err(err),
+            }
+        }))
+        .unwrap();
+    assert_eq!(sent, 8);
+    let mut buf = [0u8; 16];
+    let (n, from) = device.recv_from(&mut buf).unwrap();
+    assert_eq!(&buf[..n], b"identify");
+
+    // answer is not from the peer, but is received with its source
+    device.send_to(b"here", from).unwrap();
+    let mut buf = [0u8; 16];
+    let (n, from) = rt
+        .block_on(future::poll_fn(|| conn.poll_recv_from(&mut buf)))
+        .unwrap();
+    assert_eq!(&buf[..n], b"here");
+    assert_eq!(from, device_addr);
+}
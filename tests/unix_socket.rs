@@ -0,0 +1,88 @@
+#![cfg(all(target_os = "linux", feature = "serial", feature = "retrying-tcp"))]
+
+use tokio::prelude::Future;
+use tokio_uniconnect::address::ConnectionAddress;
+use tokio_uniconnect::builder::RetryingTcpOrSerial;
+use tokio_uniconnect::unix::connect_abstract_unix;
+
+use std::io::Read;
+use std::os::linux::net::SocketAddrExt;
+use std::os::unix::net::{SocketAddr, UnixListener};
+
+fn abstract_listener(name: &str) -> UnixListener {
+    let name = format!("{}-{}", name, std::process::id());
+    UnixListener::bind_addr(&SocketAddr::from_abstract_name(name.as_bytes()).unwrap()).unwrap()
+}
+
+fn listener_name(listener: &UnixListener) -> String {
+    let addr = listener.local_addr().unwrap();
+    String::from_utf8(addr.as_abstract_name().unwrap().to_vec()).unwrap()
+}
+
+#[test]
+fn parse_abstract_address() {
+    let address: ConnectionAddress = "@abstract:app.sock".parse().unwrap();
+    assert_eq!(
+        address,
+        ConnectionAddress::AbstractUnixSocket(b"app.sock".to_vec())
+    );
+    assert_eq!(address.to_string(), "@abstract:app.sock");
+    assert!("@abstract:".parse::<ConnectionAddress>().is_err());
+}
+
+#[test]
+fn builder_connects_to_abstract_socket() {
+    let listener = abstract_listener("uniconnect-builder");
+    let address = format!("@abstract:{}", listener_name(&listener));
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let conn = rt
+        .block_on(tokio::prelude::future::lazy(move || {
+            RetryingTcpOrSerial::new(address.parse().unwrap()).build()
+        }))
+        .unwrap();
+    assert_eq!(
+        conn.redacted().to_string(),
+        format!("@abstract:{}", listener_name(&listener))
+    );
+
+    rt.block_on(tokio::io::write_all(conn, b"ping")).unwrap();
+    let mut buf = [0u8; 4];
+    listener.accept().unwrap().0.read_exact(&mut buf).unwrap();
+    assert_eq!(&buf, b"ping");
+}
+
+#[test]
+fn connect_abstract_unix_fails_without_listener() {
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let res = rt.block_on(connect_abstract_unix(b"uniconnect-nobody-listens").map(drop));
+    assert_eq!(
+        res.unwrap_err().kind(),
+        std::io::ErrorKind::ConnectionRefused
+    );
+}
+
+#[test]
+fn plain_path_to_socket_file_is_unix_socket() {
+    let path = std::env::temp_dir().join(format!("uniconnect-detect-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let _listener = UnixListener::bind(&path).unwrap();
+
+    let detected = ConnectionAddress::detect(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(detected, ConnectionAddress::UnixSocket(path));
+    assert_eq!(
+        ConnectionAddress::detect("/dev/ttyUSB0").unwrap(),
+        ConnectionAddress::Serial("/dev/ttyUSB0".into())
+    );
+}
+
+#[test]
+fn missing_socket_file_error_names_path() {
+    let err = RetryingTcpOrSerial::new("unix:///tmp/uniconnect-missing.sock".parse().unwrap())
+        .build()
+        .err()
+        .unwrap();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+    assert!(err.to_string().contains("/tmp/uniconnect-missing.sock"));
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::Future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::RetryingTcpStream;
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+
+// Accept one connection per banner and send it
+fn serve_banners(listener: TcpListener, banners: Vec<&'static [u8]>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for banner in banners {
+            let (mut peer, _) = listener.accept().unwrap();
+            peer.write_all(banner).unwrap();
+        }
+    })
+}
+
+fn banner_validated(addr: &std::net::SocketAddr) -> RetryingTcpStream {
+    RetryingTcpStream::connect(addr).with_validator(|ts| {
+        tokio::io::read_exact(ts, [0u8; 4]).map(|(ts, banner)| (ts, &banner == b"OK\r\n"))
+    })
+}
+
+#[test]
+fn validator_consumes_banner() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = banner_validated(&listener.local_addr().unwrap());
+    let server = serve_banners(listener, vec![b"OK\r\nhello"]);
+
+    let (stream, data) = rt
+        .block_on(tokio::io::read_exact(stream, [0u8; 5]))
+        .unwrap();
+    assert_eq!(&data, b"hello");
+    assert_eq!(stream.reconnect_count(), 0);
+    server.join().unwrap();
+}
+
+#[test]
+fn rejected_banner_reconnects() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = banner_validated(&listener.local_addr().unwrap());
+    let server = serve_banners(listener, vec![b"NO\r\nignored", b"OK\r\nhi"]);
+
+    let (stream, data) = rt
+        .block_on(tokio::io::read_exact(stream, [0u8; 2]))
+        .unwrap();
+    assert_eq!(&data, b"hi");
+    assert_eq!(stream.reconnect_count(), 1);
+    server.join().unwrap();
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::Future;
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::{RetryingTcpStream, TcpStreamSettings};
+
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+// Accept and close first connection, then send `ok` to the next one
+fn close_first_connection(listener: TcpListener) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        let (peer, _) = listener.accept().unwrap();
+        drop(peer);
+        let (mut peer, _) = listener.accept().unwrap();
+        peer.write_all(b"ok").unwrap();
+    })
+}
+
+fn connect_to(listener: &TcpListener, warmup_probe: bool) -> RetryingTcpStream {
+    let mut settings = TcpStreamSettings::default();
+    settings.set_warmup_probe(warmup_probe);
+    RetryingTcpStream::connect_with_settings(&listener.local_addr().unwrap(), settings)
+}
+
+#[test]
+fn probe_reconnects_when_peer_closed_after_accept() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = connect_to(&listener, true);
+    let server = close_first_connection(listener);
+    // FIN arrives before the stream is first polled
+    thread::sleep(Duration::from_millis(50));
+
+    let (stream, data) = rt
+        .block_on(tokio::io::read_exact(stream, [0u8; 2]))
+        .unwrap();
+    assert_eq!(&data, b"ok");
+    assert_eq!(stream.reconnect_count(), 1);
+    server.join().unwrap();
+}
+
+#[test]
+fn without_probe_closed_peer_is_seen_by_reader() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let stream = connect_to(&listener, false);
+    let _server = close_first_connection(listener);
+    thread::sleep(Duration::from_millis(50));
+
+    let err = rt
+        .block_on(tokio::io::read_exact(stream, [0u8; 2]).map(drop))
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "websocket")]
+
+use futures::{Future, Sink, Stream};
+use tokio::net::TcpListener;
+use tokio_tungstenite::tungstenite::Message;
+use tokio_uniconnect::websocket::websocket_connect;
+
+#[test]
+fn binary_round_trip_with_ping() {
+    let listener = TcpListener::bind(&"127.0.0.1:0".parse().unwrap()).unwrap();
+    let url = format!("ws://{}", listener.local_addr().unwrap());
+
+    // server sends ping and a message, then expects pong and echo of the message
+    let server = listener
+        .incoming()
+        .into_future()
+        .map_err(|(err, _)| panic!("accept failed: {}", err))
+        .and_then(|(socket, _)| {
+            tokio_tungstenite::accept_async(socket.unwrap()).map_err(|err| panic!("{}", err))
+        })
+        .and_then(|ws| {
+            ws.send(Message::Ping(b"ping".to_vec()))
+                .and_then(|ws| ws.send(Message::Binary(b"hello".to_vec())))
+                .and_then(|ws| ws.take(2).collect())
+                .map_err(|err| panic!("{}", err))
+        });
+
+    let client = websocket_connect(&url).and_then(|conn| {
+        tokio::io::read_exact(conn, [0u8; 5])
+            .and_then(|(conn, buf)| tokio::io::write_all(conn, buf))
+            .and_then(|(conn, _)| tokio::io::flush(conn))
+    });
+
+    let mut rt = tokio::runtime::Runtime::new().unwrap();
+    let (received, _conn) = rt.block_on(server.join(client)).unwrap();
+
+    assert_eq!(
+        received,
+        vec![
+            Message::Pong(b"ping".to_vec()),
+            Message::Binary(b"hello".to_vec())
+        ]
+    );
+}
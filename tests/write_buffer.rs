@@ -0,0 +1,93 @@
+#![cfg(feature = "retrying-tcp")]
+
+use tokio::prelude::{future, Future};
+use tokio::runtime::current_thread::Runtime;
+use tokio_uniconnect::retrying_tcp_stream::{OverflowPolicy, RetryingTcpStream, TcpStreamSettings};
+
+use std::io::{ErrorKind, Read, Write};
+use std::net::TcpListener;
+
+fn connecting_stream(
+    listener: &TcpListener,
+    capacity: Option<usize>,
+    policy: OverflowPolicy,
+) -> RetryingTcpStream {
+    let mut settings = TcpStreamSettings::default();
+    settings.set_write_buffer(capacity, policy);
+    RetryingTcpStream::connect_with_settings(&listener.local_addr().unwrap(), settings)
+}
+
+// Writes must run inside a task
+fn write(rt: &mut Runtime, stream: &mut RetryingTcpStream, data: &[u8]) -> std::io::Result<usize> {
+    rt.block_on(future::lazy(|| future::ok::<_, ()>(stream.write(data))))
+        .unwrap()
+}
+
+// Flush `stream` and return everything the peer received until close
+fn flush_and_receive(
+    rt: &mut Runtime,
+    stream: RetryingTcpStream,
+    listener: &TcpListener,
+) -> Vec<u8> {
+    let stream = rt.block_on(tokio::io::flush(stream)).unwrap();
+    drop(stream);
+    let (mut peer, _) = listener.accept().unwrap();
+    let mut received = Vec::new();
+    peer.read_to_end(&mut received).unwrap();
+    received
+}
+
+#[test]
+fn writes_while_connecting_are_sent_once_connected() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = connecting_stream(&listener, Some(16), OverflowPolicy::Block);
+
+    assert_eq!(write(&mut rt, &mut stream, b"first ").unwrap(), 6);
+    assert_eq!(write(&mut rt, &mut stream, b"second").unwrap(), 6);
+    assert_eq!(stream.queued_write_len(), 12);
+    assert_eq!(
+        flush_and_receive(&mut rt, stream, &listener),
+        b"first second"
+    );
+}
+
+#[test]
+fn block_policy_accepts_only_what_fits() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = connecting_stream(&listener, Some(4), OverflowPolicy::Block);
+
+    assert_eq!(write(&mut rt, &mut stream, b"abcdef").unwrap(), 4);
+    let err = write(&mut rt, &mut stream, b"gh").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(flush_and_receive(&mut rt, stream, &listener), b"abcd");
+}
+
+#[test]
+fn drop_policy_keeps_newest_bytes() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = connecting_stream(&listener, Some(4), OverflowPolicy::Drop);
+
+    assert_eq!(write(&mut rt, &mut stream, b"abcd").unwrap(), 4);
+    assert_eq!(write(&mut rt, &mut stream, b"ef").unwrap(), 2);
+    assert_eq!(stream.queued_write_len(), 4);
+    assert_eq!(flush_and_receive(&mut rt, stream, &listener), b"cdef");
+}
+
+#[test]
+fn without_buffer_write_while_connecting_would_block() {
+    let mut rt = Runtime::new().unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let mut stream = connecting_stream(&listener, None, OverflowPolicy::Block);
+
+    let err = write(&mut rt, &mut stream, b"data").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WouldBlock);
+    assert_eq!(stream.queued_write_len(), 0);
+    // nothing is lost on the caller side, the same write succeeds once connected
+    let (stream, _) = rt
+        .block_on(tokio::io::write_all(stream, b"data".to_vec()).map_err(drop))
+        .unwrap();
+    assert_eq!(flush_and_receive(&mut rt, stream, &listener), b"data");
+}